@@ -0,0 +1,247 @@
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+use crate::{atomic, i18n, relative_name, relative_path};
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Format {
+    Zip,
+    #[value(name = "tar.gz")]
+    TarGz,
+}
+
+impl Format {
+    /// Guess the archive format from the output file name.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".zip") {
+            Some(Format::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Format::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Modification time of `file` for its zip entry, in local time like zip
+/// tools use. None for times zip can't hold, before 1980.
+fn zip_time(file: &Path) -> io::Result<Option<zip::DateTime>> {
+    let modified = DateTime::<Local>::from(fs::metadata(file)?.modified()?);
+    Ok(zip::DateTime::try_from(modified.naive_local()).ok())
+}
+
+/// Zip entries are named by `relative_name`, as zip names are text.
+fn write_zip(out: &mut File, root: &Path, files: &[PathBuf]) -> io::Result<()> {
+    let mut writer = zip::ZipWriter::new(out);
+
+    for file in files {
+        let mut options = SimpleFileOptions::default();
+        if let Some(time) = zip_time(file)? {
+            options = options.last_modified_time(time);
+        }
+        writer.start_file(relative_name(root, file), options)?;
+        io::copy(&mut File::open(file)?, &mut writer)?;
+    }
+
+    writer.finish()?;
+    Ok(())
+}
+
+fn write_tar_gz(out: &mut File, root: &Path, files: &[PathBuf]) -> io::Result<()> {
+    let mut builder = tar::Builder::new(GzEncoder::new(out, Compression::default()));
+
+    for file in files {
+        builder.append_path_with_name(file, relative_path(root, file))?;
+    }
+
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Package `files` into the archive `out`, keeping their paths relative to
+/// `root`. With `options.dry_run`, only print what would be added.
+pub fn write_archive(
+    out: &Path,
+    format: Format,
+    root: &Path,
+    files: &[PathBuf],
+    options: atomic::Options,
+) -> Result<(), String> {
+    if options.dry_run {
+        for file in files {
+            println!(
+                "{}",
+                i18n::trf(
+                    "would archive {} as {}",
+                    &[&file.display(), &relative_name(root, file)]
+                )
+            );
+        }
+        return Ok(());
+    }
+
+    atomic::write_with(out, options.backup, |file| match format {
+        Format::Zip => write_zip(file, root, files),
+        Format::TarGz => write_tar_gz(file, root, files),
+    })
+    .map_err(|e| i18n::trf("can't write '{}': {}", &[&out.display(), &e]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    fn sample_tree() -> (TempDir, Vec<PathBuf>) {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("sub")).unwrap();
+        fs::write(root.path().join("a.md"), "a").unwrap();
+        fs::write(root.path().join("sub").join("b.md"), "b").unwrap();
+        let files = vec![
+            root.path().join("a.md"),
+            root.path().join("sub").join("b.md"),
+        ];
+        (root, files)
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path(Path::new("out.zip")), Some(Format::Zip));
+        assert_eq!(
+            Format::from_path(Path::new("out.tar.gz")),
+            Some(Format::TarGz)
+        );
+        assert_eq!(Format::from_path(Path::new("out.TGZ")), Some(Format::TarGz));
+        assert_eq!(Format::from_path(Path::new("out.rar")), None);
+    }
+
+    #[test]
+    fn test_write_zip() {
+        let (root, files) = sample_tree();
+        let out = TempDir::new().unwrap();
+        let path = out.path().join("out.zip");
+        write_archive(
+            &path,
+            Format::Zip,
+            root.path(),
+            &files,
+            atomic::Options::default(),
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut names: Vec<_> = archive
+            .file_names()
+            .map(|n| n.unwrap().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["a.md", "sub/b.md"]);
+
+        let mut content = String::new();
+        archive
+            .by_name("sub/b.md")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "b");
+    }
+
+    #[test]
+    fn test_write_zip_keeps_mtime() {
+        let (root, files) = sample_tree();
+        let noon = NaiveDate::from_ymd_opt(2025, 3, 14)
+            .unwrap()
+            .and_hms_opt(12, 30, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap();
+        File::options()
+            .write(true)
+            .open(&files[0])
+            .unwrap()
+            .set_modified(SystemTime::from(noon))
+            .unwrap();
+        let out = TempDir::new().unwrap();
+        let path = out.path().join("out.zip");
+        write_archive(
+            &path,
+            Format::Zip,
+            root.path(),
+            &files,
+            atomic::Options::default(),
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let time = archive.by_name("a.md").unwrap().last_modified().unwrap();
+        assert_eq!(
+            (
+                time.year(),
+                time.month(),
+                time.day(),
+                time.hour(),
+                time.minute()
+            ),
+            (2025, 3, 14, 12, 30)
+        );
+    }
+
+    #[test]
+    fn test_write_tar_gz() {
+        let (root, files) = sample_tree();
+        let out = TempDir::new().unwrap();
+        let path = out.path().join("out.tar.gz");
+        write_archive(
+            &path,
+            Format::TarGz,
+            root.path(),
+            &files,
+            atomic::Options::default(),
+        )
+        .unwrap();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&path).unwrap()));
+        let names: Vec<_> = archive
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["a.md", "sub/b.md"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_tar_gz_keeps_raw_names() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let root = TempDir::new().unwrap();
+        let file = root.path().join(OsStr::from_bytes(b"caf\xe9.md"));
+        fs::write(&file, "").unwrap();
+        let out = TempDir::new().unwrap();
+        let path = out.path().join("out.tar.gz");
+        write_archive(
+            &path,
+            Format::TarGz,
+            root.path(),
+            &[file],
+            atomic::Options::default(),
+        )
+        .unwrap();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&path).unwrap()));
+        let entry = archive.entries().unwrap().next().unwrap().unwrap();
+        assert_eq!(entry.path_bytes().as_ref(), b"caf\xe9.md");
+    }
+}
@@ -0,0 +1,121 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// How commands write their output files.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Options {
+    /// Only print what would be written.
+    pub dry_run: bool,
+    /// Keep the previous version of each file replaced as `NAME.bak`.
+    pub backup: bool,
+}
+
+/// `path` with `prefix` and `suffix` around its file name.
+pub fn sibling(path: &Path, prefix: &str, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(prefix);
+    name.push(path.file_name().unwrap_or_default());
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Path of the backup of `path`.
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, "", ".bak")
+}
+
+/// The file a symlink at `path` leads to, following links in turn, or
+/// `path` itself. The target needn't exist.
+fn link_target(path: &Path) -> io::Result<PathBuf> {
+    let mut path = path.to_path_buf();
+    for _ in 0..40 {
+        match fs::read_link(&path) {
+            Ok(target) => path = path.parent().unwrap_or(Path::new("")).join(target),
+            Err(_) => return Ok(path),
+        }
+    }
+    Err(io::Error::other(format!(
+        "too many levels of symbolic links at '{}'",
+        path.display()
+    )))
+}
+
+/// Create the file at `path` by passing `write` a temporary file next to it,
+/// then renaming that over `path`. A failed or interrupted write leaves the
+/// old file as it was. A symlink at `path` is kept, and the file it leads to
+/// written instead. With `backup`, the previous version is kept as
+/// `NAME.bak` (for `--backup`).
+pub fn write_with(
+    path: &Path,
+    backup: bool,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    let path = &link_target(path)?;
+    let temp = sibling(path, ".", &format!(".{}.tmp", std::process::id()));
+    let result = File::create_new(&temp).and_then(|mut file| {
+        write(&mut file)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        if backup && path.exists() {
+            fs::copy(path, backup_path(path))?;
+        }
+        fs::rename(&temp, path)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Write `content` to `path` through a temporary file, like `write_with`.
+pub fn write(path: &Path, content: impl AsRef<[u8]>, backup: bool) -> io::Result<()> {
+    write_with(path, backup, |file| file.write_all(content.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("note.md");
+        write(&path, "one", false).unwrap();
+        write(&path, "two", false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        assert!(!backup_path(&path).exists());
+
+        let failed = write_with(&path, false, |file| {
+            file.write_all(b"half")?;
+            Err(io::Error::other("disk full"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        write(&path, "three", true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "three");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "two");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_through_symlink() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("real")).unwrap();
+        let real = dir.path().join("real").join("note.md");
+        fs::write(&real, "one").unwrap();
+        let link = dir.path().join("note.md");
+        std::os::unix::fs::symlink("real/note.md", &link).unwrap();
+
+        write(&link, "two", true).unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "two");
+        assert_eq!(fs::read_to_string(backup_path(&real)).unwrap(), "one");
+        assert!(!backup_path(&link).exists());
+    }
+}
@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::Path;
+
+/// Extended attribute holding the Finder tags of a file on macOS, as a
+/// binary property list of `name\ncolor` strings.
+const FINDER_TAGS: &str = "com.apple.metadata:_kMDItemUserTags";
+
+/// An `--xattr` condition: the attribute is set, with `value` if given.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    name: String,
+    value: Option<String>,
+}
+
+/// Parse an `--xattr` condition, `NAME` or `NAME=VALUE`.
+pub fn parse_condition(text: &str) -> Result<Condition, String> {
+    let (name, value) = match text.split_once('=') {
+        Some((name, value)) => (name, Some(value.to_string())),
+        None => (text, None),
+    };
+    if name.is_empty() {
+        return Err(format!(
+            "invalid condition '{}' (expected NAME or NAME=VALUE)",
+            text
+        ));
+    }
+    Ok(Condition {
+        name: name.to_string(),
+        value,
+    })
+}
+
+impl Condition {
+    /// Whether the file at `path` has the attribute, with the value asked
+    /// for. Files whose attributes can't be read never match.
+    pub fn matches(&self, path: &Path) -> bool {
+        match xattr::get(path, &self.name) {
+            Ok(Some(value)) => self
+                .value
+                .as_ref()
+                .is_none_or(|expected| value == expected.as_bytes()),
+            _ => false,
+        }
+    }
+}
+
+/// Finder tag names of the file at `path`; none where Finder tags aren't
+/// supported.
+pub fn finder_tags(path: &Path) -> Vec<String> {
+    let Ok(Some(data)) = xattr::get(path, FINDER_TAGS) else {
+        return Vec::new();
+    };
+    parse_finder_tags(&data)
+}
+
+fn parse_finder_tags(data: &[u8]) -> Vec<String> {
+    let Ok(tags) = plist::from_bytes::<Vec<String>>(data) else {
+        return Vec::new();
+    };
+    tags.into_iter()
+        .map(|tag| match tag.split_once('\n') {
+            Some((name, _color)) => name.to_string(),
+            None => tag,
+        })
+        .collect()
+}
+
+/// Whether the file at `path` has the Finder tag `name` (ignoring case, as
+/// Finder does).
+pub fn has_finder_tag(path: &Path, name: &str) -> bool {
+    finder_tags(path)
+        .iter()
+        .any(|tag| tag.to_lowercase() == name.to_lowercase())
+}
+
+/// Whether the file at `path` is executable: by its permission bits on
+/// Unix.
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Whether the file at `path` is executable: a PE or ELF binary, or a
+/// script with a `#!` line, going by its first bytes.
+#[cfg(not(unix))]
+pub fn is_executable(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut magic = [0; 4];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| is_executable_magic(&magic))
+}
+
+/// Whether a file starting with `magic` is a PE or ELF binary or a script.
+#[cfg(any(not(unix), test))]
+fn is_executable_magic(magic: &[u8]) -> bool {
+    magic.starts_with(b"MZ") || magic.starts_with(b"\x7fELF") || magic.starts_with(b"#!")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_condition() {
+        assert_eq!(
+            parse_condition("user.project=alpha").unwrap(),
+            Condition {
+                name: "user.project".to_string(),
+                value: Some("alpha".to_string()),
+            }
+        );
+        assert_eq!(parse_condition("user.flag").unwrap().value, None);
+        assert!(parse_condition("=x").is_err());
+    }
+
+    #[test]
+    fn test_parse_finder_tags() {
+        let tags = vec!["Red\n6".to_string(), "Work".to_string()];
+        let mut data = Vec::new();
+        plist::to_writer_binary(&mut data, &tags).unwrap();
+        assert_eq!(parse_finder_tags(&data), vec!["Red", "Work"]);
+        assert!(parse_finder_tags(b"not a plist").is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_condition_matches() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("a.jpg");
+        std::fs::write(&path, "").unwrap();
+        if xattr::set(&path, "user.project", b"alpha").is_err() {
+            // The file system of the temporary directory has no user xattrs
+            return;
+        }
+
+        assert!(parse_condition("user.project").unwrap().matches(&path));
+        assert!(
+            parse_condition("user.project=alpha")
+                .unwrap()
+                .matches(&path)
+        );
+        assert!(!parse_condition("user.project=beta").unwrap().matches(&path));
+        assert!(!parse_condition("user.other").unwrap().matches(&path));
+    }
+
+    #[test]
+    fn test_is_executable_magic() {
+        assert!(is_executable_magic(b"MZ\x90\x00"));
+        assert!(is_executable_magic(b"\x7fELF"));
+        assert!(is_executable_magic(b"#!/b"));
+        assert!(!is_executable_magic(b"# Ti"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_is_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("run.sh");
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        assert!(!is_executable(&path));
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(is_executable(&path));
+    }
+}
@@ -0,0 +1,152 @@
+use chrono::{Locale, NaiveDate};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::traversal::{WalkContext, WalkOptions};
+use crate::{
+    LinkStyle, TimeField, date_heading, file_iterator, file_time, get_date, i18n, local_date,
+};
+
+fn parse_dates(text: &str) -> Result<Vec<NaiveDate>, String> {
+    text.lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| get_date(Some(line)).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// Read dates, one per line, from `source` (a file name, or `-` for stdin).
+pub fn read_dates(source: &str) -> Result<Vec<NaiveDate>, String> {
+    let text = if source == "-" {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("can't read dates from stdin: {}", e))?;
+        text
+    } else {
+        fs::read_to_string(source).map_err(|e| i18n::trf("can't read '{}': {}", &[&source, &e]))?
+    };
+
+    parse_dates(&text)
+}
+
+/// The files under `root` ending with `suffix` on each of `dates`, in time
+/// order. The tree is walked once for all dates.
+pub fn find_files(
+    root: &Path,
+    suffix: &str,
+    field: TimeField,
+    dates: &[NaiveDate],
+    options: &WalkOptions,
+) -> HashMap<NaiveDate, Vec<PathBuf>> {
+    let wanted: HashSet<NaiveDate> = dates.iter().copied().collect();
+    let mut by_date: HashMap<NaiveDate, Vec<(PathBuf, SystemTime)>> = HashMap::new();
+    let context = WalkContext::new(options);
+    let matching =
+        file_iterator(root, &context).filter(|path| context.scopes.matches_suffix(path, suffix));
+    for path in matching {
+        let Some(time) = file_time(&path, field) else {
+            continue;
+        };
+        let date = local_date(time);
+        if wanted.contains(&date) {
+            by_date.entry(date).or_default().push((path, time));
+        }
+    }
+
+    by_date
+        .into_iter()
+        .map(|(date, mut files)| {
+            files.sort_by_key(|(_, time)| *time);
+            (date, files.into_iter().map(|(path, _)| path).collect())
+        })
+        .collect()
+}
+
+/// Report `files`, the files of each of `dates`, under a heading per date.
+pub fn report(
+    dates: &[NaiveDate],
+    files: &[Vec<PathBuf>],
+    locale: Option<Locale>,
+    style: &LinkStyle,
+) -> String {
+    let mut out = String::new();
+    for (i, (date, files)) in dates.iter().zip(files).enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("## {}\n\n", date_heading(*date, locale)));
+        for path in files {
+            out.push_str(&style.format(path));
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_dates_skips_blank_lines() {
+        let dates = parse_dates("2025-03-14\n\n  2025-03-15  \n").unwrap();
+        assert_eq!(dates.len(), 2);
+        assert_eq!(dates[1].to_string(), "2025-03-15");
+    }
+
+    #[test]
+    fn test_parse_dates_reports_line() {
+        let err = parse_dates("2025-03-14\nbad\n").unwrap_err();
+        assert!(err.starts_with("line 2: Invalid date format"));
+    }
+
+    #[test]
+    fn test_find_files_groups_by_date() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("a.md")).unwrap();
+        File::create(temp_dir.path().join("b.txt")).unwrap();
+
+        let today = Local::now().date_naive();
+        let old = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let found = find_files(
+            temp_dir.path(),
+            ".md",
+            TimeField::Modified,
+            &[old, today],
+            &WalkOptions::default(),
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[&today], vec![temp_dir.path().join("a.md")]);
+    }
+
+    #[test]
+    fn test_report_groups_by_date() {
+        let today = Local::now().date_naive();
+        let old = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let path = PathBuf::from("notes/a.md");
+        let result = report(
+            &[old, today],
+            &[vec![], vec![path.clone()]],
+            None,
+            &LinkStyle::default(),
+        );
+
+        assert_eq!(
+            result,
+            format!(
+                "## 2020-01-01\n\n\n## {}\n\n{}\n",
+                today,
+                LinkStyle::default().format(&path)
+            )
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::frontmatter::{self, Generated};
+use crate::{atomic, dryrun, i18n, relative_name};
+
+/// The content of `files` in one markdown document: each file under a
+/// heading with its path relative to `root`, without its frontmatter, and
+/// separated from the next by a horizontal rule.
+pub fn bundle(root: &Path, files: &[PathBuf]) -> Result<String, String> {
+    let mut parts = Vec::new();
+    for file in files {
+        let bytes =
+            fs::read(file).map_err(|e| i18n::trf("can't read '{}': {}", &[&file.display(), &e]))?;
+        let content = String::from_utf8_lossy(&bytes);
+        let body = frontmatter::body(&content).trim();
+        parts.push(format!("# {}\n\n{}\n", relative_name(root, file), body));
+    }
+    Ok(parts.join("\n---\n\n"))
+}
+
+/// Write the bundle of `files` to `out`, or with `options.dry_run` only print the
+/// change as a diff. `out` itself is left out, in case it's one of the
+/// matching files from an earlier run. The bundle starts with `frontmatter`
+/// when given.
+pub fn write_bundle(
+    out: &Path,
+    root: &Path,
+    files: &[PathBuf],
+    frontmatter: Option<&Generated>,
+    options: atomic::Options,
+) -> Result<usize, String> {
+    let out_path = fs::canonicalize(out).ok();
+    let files: Vec<PathBuf> = files
+        .iter()
+        .filter(|file| out_path.is_none() || fs::canonicalize(file).ok() != out_path)
+        .cloned()
+        .collect();
+
+    let mut content = frontmatter.map_or_else(String::new, |f| f.render(files.len()));
+    content.push_str(&bundle(root, &files)?);
+    if options.dry_run {
+        let old = fs::read_to_string(out).ok();
+        print!("{}", dryrun::diff(out, old.as_deref(), &content));
+        return Ok(files.len());
+    }
+    atomic::write(out, content, options.backup)
+        .map_err(|e| i18n::trf("can't write '{}': {}", &[&out.display(), &e]))?;
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_bundle() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("sub")).unwrap();
+        let a = root.path().join("a.md");
+        let b = root.path().join("sub").join("b.md");
+        fs::write(&a, "---\ntags: [x]\n---\n\n# A\n\nalpha\n").unwrap();
+        fs::write(&b, "beta\n").unwrap();
+
+        assert_eq!(
+            bundle(root.path(), &[a, b]).unwrap(),
+            "# a.md\n\n# A\n\nalpha\n\n---\n\n# sub/b.md\n\nbeta\n"
+        );
+    }
+
+    #[test]
+    fn test_write_bundle_skips_output() {
+        let root = TempDir::new().unwrap();
+        let a = root.path().join("a.md");
+        let out = root.path().join("digest.md");
+        fs::write(&a, "alpha").unwrap();
+        fs::write(&out, "old digest").unwrap();
+
+        let count = write_bundle(
+            &out,
+            root.path(),
+            &[a, out.clone()],
+            None,
+            atomic::Options::default(),
+        )
+        .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(fs::read_to_string(&out).unwrap(), "# a.md\n\nalpha\n");
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::{Datelike, Days, Locale, Months, NaiveDate};
+use std::path::PathBuf;
+
+use crate::group::{self, GroupBy};
+use crate::{DateRange, LinkStyle, TimeField, date_heading, toc};
+
+/// First days of the months `range` touches.
+fn months(range: DateRange) -> Vec<NaiveDate> {
+    let mut months = Vec::new();
+    let mut month = range.start.with_day(1);
+    while let Some(first) = month.filter(|first| *first <= range.end) {
+        months.push(first);
+        month = first.checked_add_months(Months::new(1));
+    }
+    months
+}
+
+/// `date` formatted with `format`, for `locale` when given.
+fn format_date(date: NaiveDate, format: &str, locale: Option<Locale>) -> String {
+    match locale {
+        Some(locale) => date.format_localized(format, locale).to_string(),
+        None => date.format(format).to_string(),
+    }
+}
+
+/// Markdown table of the month starting on `first`, a row per week from
+/// Monday to Sunday. Days with files show their count, linked to the day's
+/// section.
+fn month_table(
+    first: NaiveDate,
+    count: impl Fn(NaiveDate) -> usize,
+    locale: Option<Locale>,
+) -> String {
+    let monday = first - Days::new(first.weekday().num_days_from_monday() as u64);
+    let week = |start: NaiveDate| (0..7).map(move |i| start + Days::new(i));
+
+    let names: Vec<String> = week(monday)
+        .map(|day| format_date(day, "%a", locale))
+        .collect();
+    let mut out = format!("| {} |\n", names.join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(7)));
+
+    let mut start = monday;
+    while start.month() == first.month() || start < first {
+        let cells: Vec<String> = week(start)
+            .map(|day| match (day.month() == first.month(), count(day)) {
+                (false, _) => String::new(),
+                (true, 0) => day.day().to_string(),
+                (true, n) => {
+                    let anchor = toc::slug(&date_heading(day, locale));
+                    format!("{} [{}](#{})", day.day(), n, anchor)
+                }
+            })
+            .collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        start = start + Days::new(7);
+    }
+    out
+}
+
+/// A calendar of the months of `range`, with the number of `files` per day,
+/// followed by a section listing the files of each day.
+pub fn calendar(
+    files: &[PathBuf],
+    range: DateRange,
+    field: TimeField,
+    locale: Option<Locale>,
+    style: &LinkStyle,
+) -> String {
+    let by_date = group::by_date(files, field);
+    let count = |day: NaiveDate| by_date.get(&day).map_or(0, Vec::len);
+
+    let mut out = String::new();
+    for (i, first) in months(range).into_iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("## {}\n\n", format_date(first, "%B %Y", locale)));
+        out.push_str(&month_table(first, count, locale));
+    }
+
+    let days = group::render(files, GroupBy::Day, field, locale, style);
+    if !days.is_empty() {
+        out.push('\n');
+        out.push_str(&days);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_months() {
+        let range = DateRange {
+            start: date(1, 31),
+            end: date(3, 1),
+        };
+        assert_eq!(months(range), vec![date(1, 1), date(2, 1), date(3, 1)]);
+        assert_eq!(months(date(3, 14).into()), vec![date(3, 1)]);
+    }
+
+    #[test]
+    fn test_month_table() {
+        let count = |day: NaiveDate| if day == date(2, 14) { 3 } else { 0 };
+        // February 2025 starts on a Saturday and ends on a Friday
+        assert_eq!(
+            month_table(date(2, 1), count, None),
+            "| Mon | Tue | Wed | Thu | Fri | Sat | Sun |\n\
+             | --- | --- | --- | --- | --- | --- | --- |\n\
+             |  |  |  |  |  | 1 | 2 |\n\
+             | 3 | 4 | 5 | 6 | 7 | 8 | 9 |\n\
+             | 10 | 11 | 12 | 13 | 14 [3](#2025-02-14) | 15 | 16 |\n\
+             | 17 | 18 | 19 | 20 | 21 | 22 | 23 |\n\
+             | 24 | 25 | 26 | 27 | 28 |  |  |\n"
+        );
+    }
+
+    #[test]
+    fn test_month_table_localized() {
+        let table = month_table(
+            date(3, 1),
+            |day| (day == date(3, 14)) as usize,
+            Some(Locale::fr_FR),
+        );
+        assert!(table.starts_with("| lun. | mar. |"));
+        assert!(table.contains("| 14 [1](#14-mars-2025) |"));
+    }
+}
@@ -0,0 +1,178 @@
+use chrono::{Locale, NaiveDate};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::forge::Commit;
+use crate::{DateRange, LinkStyle, TimeField, date_heading, group, i18n, path_text, relative_path};
+
+/// The top-level directory of `file` under `root`, or `.` for files right
+/// in it.
+fn top_directory(root: &Path, file: &Path) -> String {
+    let relative = relative_path(root, file);
+    let mut parts = relative.iter();
+    match (parts.next(), parts.next()) {
+        (Some(top), Some(_)) => path_text(top),
+        _ => ".".to_string(),
+    }
+}
+
+/// CHANGELOG-style document of `files` under `root` over `range`: a heading
+/// per day, newest first, with the subjects of that day's `commits` and the
+/// files under a heading per top-level directory.
+pub fn render(
+    root: &Path,
+    files: &[PathBuf],
+    commits: &[Commit],
+    range: DateRange,
+    field: TimeField,
+    locale: Option<Locale>,
+    style: &LinkStyle,
+) -> String {
+    let title = if range.start == range.end {
+        i18n::trf("Changes for {}", &[&date_heading(range.end, locale)])
+    } else {
+        i18n::trf(
+            "Changes from {} to {}",
+            &[
+                &date_heading(range.start, locale),
+                &date_heading(range.end, locale),
+            ],
+        )
+    };
+    let mut out = format!("# {}\n", title);
+
+    let by_date = group::by_date(files, field);
+    let mut commits_by_date: BTreeMap<NaiveDate, Vec<&Commit>> = BTreeMap::new();
+    for commit in commits {
+        commits_by_date
+            .entry(commit.time.date_naive())
+            .or_default()
+            .push(commit);
+    }
+    let mut days: Vec<NaiveDate> = by_date
+        .keys()
+        .chain(commits_by_date.keys())
+        .copied()
+        .collect();
+    days.sort_unstable_by(|a, b| b.cmp(a));
+    days.dedup();
+
+    for day in days {
+        out.push_str(&format!("\n## {}\n", date_heading(day, locale)));
+        if let Some(commits) = commits_by_date.get(&day) {
+            out.push('\n');
+            // Newest first, like the days
+            for commit in commits.iter().rev() {
+                out.push_str(&commit.entry());
+                out.push('\n');
+            }
+        }
+        let mut directories: BTreeMap<String, Vec<&PathBuf>> = BTreeMap::new();
+        for file in by_date.get(&day).into_iter().flatten() {
+            directories
+                .entry(top_directory(root, file))
+                .or_default()
+                .push(file);
+        }
+        for (directory, files) in directories {
+            out.push_str(&format!("\n### {}\n\n", directory));
+            for file in files {
+                out.push_str(&style.format(file));
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+    use std::fs::{self, File};
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, day).unwrap()
+    }
+
+    /// A file modified at noon on `date`.
+    fn file_on(dir: &TempDir, name: &str, date: NaiveDate) -> PathBuf {
+        let path = dir.path().join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        let noon = date
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap();
+        File::create(&path)
+            .unwrap()
+            .set_modified(SystemTime::from(noon))
+            .unwrap();
+        path
+    }
+
+    fn commit(short: &str, subject: &str, date: NaiveDate, hour: u32) -> Commit {
+        Commit {
+            short: short.to_string(),
+            subject: subject.to_string(),
+            time: Local
+                .from_local_datetime(&date.and_hms_opt(hour, 0, 0).unwrap())
+                .unwrap(),
+            files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_top_directory() {
+        let root = Path::new("root");
+        assert_eq!(top_directory(root, Path::new("root/a.md")), ".");
+        assert_eq!(top_directory(root, Path::new("root/docs/api/a.md")), "docs");
+    }
+
+    #[test]
+    fn test_render() {
+        let dir = TempDir::new().unwrap();
+        let files = vec![
+            file_on(&dir, "docs/a.md", date(10)),
+            file_on(&dir, "b.md", date(10)),
+            file_on(&dir, "src/c.rs", date(12)),
+        ];
+        let commits = [
+            commit("abc1234", "Add docs", date(10), 9),
+            commit("def5678", "Fix typo", date(10), 17),
+            commit("0123abc", "Tidy up", date(11), 9),
+        ];
+        let style = LinkStyle::default();
+        let target = |name: &str| style.target(&dir.path().join(name));
+        let range = DateRange {
+            start: date(10),
+            end: date(12),
+        };
+
+        assert_eq!(
+            render(
+                dir.path(),
+                &files,
+                &commits,
+                range,
+                TimeField::Modified,
+                None,
+                &style
+            ),
+            format!(
+                "# Changes from 2025-03-10 to 2025-03-12\n\n\
+                 ## 2025-03-12\n\n### src\n\n- [c.rs]({})\n\n\
+                 ## 2025-03-11\n\n- Tidy up (`0123abc`)\n\n\
+                 ## 2025-03-10\n\n- Fix typo (`def5678`)\n- Add docs (`abc1234`)\n\n\
+                 ### .\n\n- [b.md]({})\n\n### docs\n\n- [a.md]({})\n",
+                target("src/c.rs"),
+                target("b.md"),
+                target("docs/a.md")
+            )
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::{atomic, i18n};
+
+/// Time of the last successful run recorded in `path`, or `None` when there
+/// was none yet.
+pub fn read(path: &str) -> Result<Option<SystemTime>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(i18n::trf("can't read '{}': {}", &[&path, &e])),
+    };
+
+    let time = DateTime::parse_from_rfc3339(text.trim())
+        .map_err(|e| format!("invalid checkpoint '{}': {}", path, e))?;
+    Ok(Some(time.into()))
+}
+
+/// Record `time` as the time of the last successful run.
+pub fn write(path: &str, time: SystemTime, backup: bool) -> Result<(), String> {
+    let time: DateTime<Utc> = time.into();
+    let text = time.to_rfc3339_opts(SecondsFormat::Nanos, true) + "\n";
+    atomic::write(Path::new(path), text, backup)
+        .map_err(|e| i18n::trf("can't write '{}': {}", &[&path, &e]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("checkpoint");
+        let path = path.to_str().unwrap();
+
+        assert_eq!(read(path).unwrap(), None);
+        let now = SystemTime::now();
+        write(path, now, false).unwrap();
+        assert_eq!(read(path).unwrap(), Some(now));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("checkpoint");
+        fs::write(&path, "yesterday").unwrap();
+
+        let err = read(path.to_str().unwrap()).unwrap_err();
+        assert!(err.starts_with("invalid checkpoint"));
+    }
+}
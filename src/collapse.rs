@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use crate::{LinkStyle, relative_name};
+
+/// `files` as a list where files sharing a name get one bullet: the first of
+/// them, with the others nested beneath it, labelled with their path under
+/// the root of `roots` they're in.
+pub fn render(files: &[PathBuf], roots: &[&Path], style: &LinkStyle) -> String {
+    let mut names: Vec<Vec<&PathBuf>> = Vec::new();
+    let mut index: HashMap<Option<&OsStr>, usize> = HashMap::new();
+    for file in files {
+        let slot = *index.entry(file.file_name()).or_insert_with(|| {
+            names.push(Vec::new());
+            names.len() - 1
+        });
+        names[slot].push(file);
+    }
+
+    let mut out = String::new();
+    for same in names {
+        out.push_str(&style.format(same[0]));
+        out.push('\n');
+        for file in &same[1..] {
+            let root = roots
+                .iter()
+                .find(|root| file.starts_with(root))
+                .copied()
+                .unwrap_or(Path::new(""));
+            let entry = style.entry_as(file, &relative_name(root, file));
+            // Nested a level deeper, along with any table of contents
+            out.push_str(&format!("  - {}\n", entry.replace('\n', "\n  ")));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let root = Path::new("src");
+        let files: Vec<PathBuf> = ["src/a/mod.rs", "src/main.rs", "src/b/c/mod.rs"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let style = LinkStyle::default();
+        assert_eq!(
+            render(&files, &[root], &style),
+            "- [mod.rs](src/a/mod.rs)\n  - [b/c/mod.rs](src/b/c/mod.rs)\n- [main.rs](src/main.rs)\n"
+        );
+    }
+}
@@ -0,0 +1,608 @@
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ignore::gitignore::GitignoreBuilder;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml::{Spanned, Value};
+
+use crate::{dirconfig, get_date, i18n};
+
+/// Prefix of the environment variables holding settings, e.g. `MDFILES_SUFFIX`.
+const ENV_PREFIX: &str = "MDFILES_";
+
+/// Keys of the local config file read by the walk, not as settings, with the
+/// option overriding each.
+const DIR_KEYS: &[(&str, &str)] = &[("suffixes", "suffix"), ("exclude", "exclude-root")];
+
+/// Where the value of a setting came from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    CommandLine,
+    Env(String),
+    File(PathBuf),
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::CommandLine => write!(f, "command line"),
+            Source::Env(name) => write!(f, "${}", name),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// The value of an option, and where it came from.
+#[derive(Debug, PartialEq)]
+pub struct Setting {
+    pub key: String,
+    pub value: Value,
+    pub source: Source,
+}
+
+/// The effective configuration: every option with a value, after merging the
+/// command line, the environment and the config files.
+#[derive(Debug, Default)]
+pub struct Config {
+    pub settings: Vec<Setting>,
+}
+
+impl Config {
+    /// The configuration in TOML, with the source of each value.
+    pub fn render(&self) -> String {
+        self.settings
+            .iter()
+            .map(|setting| {
+                format!(
+                    "{} = {}  # {}\n",
+                    setting.key, setting.value, setting.source
+                )
+            })
+            .collect()
+    }
+
+    /// Whether the option `key` was set on the command line or in the
+    /// environment, above `mdfiles.toml` in the working directory.
+    pub fn is_given(&self, key: &str) -> bool {
+        self.settings.iter().any(|setting| {
+            setting.key == key && matches!(setting.source, Source::CommandLine | Source::Env(_))
+        })
+    }
+}
+
+/// The user's config file: `$XDG_CONFIG_HOME/mdfiles/config.toml`, with
+/// `XDG_CONFIG_HOME` defaulting to `~/.config`.
+pub fn user_file() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(dir.join("mdfiles").join("config.toml"))
+}
+
+/// Options that can be set outside the command line.
+fn options(command: &Command) -> impl Iterator<Item = &Arg> {
+    command.get_arguments().filter(|arg| {
+        arg.get_long().is_some() && !matches!(arg.get_id().as_str(), "help" | "version")
+    })
+}
+
+/// Settings from the `MDFILES_*` environment variables.
+fn read_env(command: &Command) -> Vec<(String, Value, Source)> {
+    options(command)
+        .filter_map(|arg| {
+            let name = format!("{}{}", ENV_PREFIX, arg.get_id().as_str().to_uppercase());
+            let value = env::var(&name).ok()?;
+            Some((
+                arg.get_id().to_string(),
+                Value::String(value),
+                Source::Env(name),
+            ))
+        })
+        .collect()
+}
+
+/// Settings from the config file at `path`, if there's one.
+fn read_file(path: &Path) -> Result<Vec<(String, Value, Source)>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(i18n::trf("can't read '{}': {}", &[&path.display(), &e])),
+    };
+    let table: toml::Table = text
+        .parse()
+        .map_err(|e| i18n::trf("can't parse '{}': {}", &[&path.display(), &e]))?;
+    Ok(table
+        .into_iter()
+        .map(|(key, value)| (key, value, Source::File(path.to_path_buf())))
+        .collect())
+}
+
+/// A single value as command line text.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Integer(_) | Value::Float(_) | Value::Boolean(_) | Value::Datetime(_) => {
+            Some(value.to_string())
+        }
+        Value::Array(_) | Value::Table(_) => None,
+    }
+}
+
+/// A flag's value: a boolean, or its text in an environment variable.
+fn flag(value: &Value) -> Option<bool> {
+    match value {
+        Value::Boolean(on) => Some(*on),
+        Value::String(text) => match text.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Some(true),
+            "" | "0" | "false" | "no" | "off" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Command line arguments setting `arg` to `value`.
+fn to_args(arg: &Arg, value: &Value) -> Result<Vec<OsString>, String> {
+    let long = arg.get_long().unwrap_or_default();
+    match arg.get_action() {
+        ArgAction::SetTrue => match flag(value) {
+            Some(true) => Ok(vec![format!("--{}", long).into()]),
+            Some(false) => Ok(Vec::new()),
+            None => Err(format!("'{}' must be true or false", long)),
+        },
+        ArgAction::Append => {
+            let items: Vec<Option<String>> = match value {
+                Value::Array(items) => items.iter().map(scalar).collect(),
+                // A table of KEY=VALUE options, as in `[entry-template]`
+                Value::Table(table) => table
+                    .iter()
+                    .map(|(key, value)| scalar(value).map(|text| format!("{}={}", key, text)))
+                    .collect(),
+                value => vec![scalar(value)],
+            };
+            items
+                .into_iter()
+                .map(|item| {
+                    item.map(|text| format!("--{}={}", long, text).into())
+                        .ok_or_else(|| format!("'{}' takes a list of values", long))
+                })
+                .collect()
+        }
+        // Options with an optional value, as in `--shuffle[=SEED]`, are also flags
+        _ if matches!(value, Value::Boolean(_))
+            && arg
+                .get_num_args()
+                .is_some_and(|range| range.min_values() == 0) =>
+        {
+            Ok(match value {
+                Value::Boolean(true) => vec![format!("--{}", long).into()],
+                _ => Vec::new(),
+            })
+        }
+        _ => scalar(value)
+            .map(|text| vec![format!("--{}={}", long, text).into()])
+            .ok_or_else(|| format!("'{}' takes a single value", long)),
+    }
+}
+
+/// Whether `a` and `b` can't be used together.
+fn conflict(command: &Command, a: &Arg, b: &Arg) -> bool {
+    command
+        .get_arg_conflicts_with(a)
+        .iter()
+        .any(|arg| arg.get_id() == b.get_id())
+        || command
+            .get_arg_conflicts_with(b)
+            .iter()
+            .any(|arg| arg.get_id() == a.get_id())
+}
+
+/// Parse `argv` with `command`, filling in the options it leaves out from, in
+/// order of precedence, the `MDFILES_*` environment variables, `mdfiles.toml`
+/// in the working directory and the user's config file. Settings conflicting
+/// with ones of higher precedence are dropped.
+pub fn load(mut command: Command, argv: Vec<OsString>) -> Result<(ArgMatches, Config), String> {
+    command.build();
+    // Errors are left for the parse with the settings to report
+    let (given, checking): (Vec<&Arg>, bool) = match command.clone().try_get_matches_from(&argv) {
+        Ok(matches) => (
+            options(&command)
+                .filter(|arg| {
+                    matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+                })
+                .collect(),
+            // Config files are written and checked without reading them
+            matches.subcommand().is_some_and(|(name, config)| {
+                name == "config" && config.subcommand_name() != Some("show")
+            }),
+        ),
+        Err(_) => (Vec::new(), false),
+    };
+
+    let mut layers = Vec::new();
+    let mut dir_keys = Vec::new();
+    if !checking {
+        layers.extend(read_env(&command));
+        let (keys, settings) = read_file(Path::new(dirconfig::CONFIG_FILE))?
+            .into_iter()
+            .partition(|(key, _, _)| DIR_KEYS.iter().any(|(dir_key, _)| dir_key == key));
+        dir_keys = keys;
+        layers.extend(settings);
+        if let Some(path) = user_file() {
+            layers.extend(read_file(&path)?);
+        }
+    }
+
+    let mut taken = given;
+    let mut sources = HashMap::new();
+    let mut extra = Vec::new();
+    for (key, value, source) in layers {
+        let id = key.replace('-', "_");
+        let Some(arg) = options(&command).find(|arg| arg.get_id() == id.as_str()) else {
+            return Err(format!("unknown setting '{}' in {}", key, source));
+        };
+        if taken
+            .iter()
+            .any(|other| other.get_id() == arg.get_id() || conflict(&command, arg, other))
+        {
+            continue;
+        }
+        extra.extend(to_args(arg, &value).map_err(|e| format!("{} in {}", e, source))?);
+        taken.push(arg);
+        sources.insert(id, source);
+    }
+
+    let argv: Vec<OsString> = argv
+        .iter()
+        .take(1)
+        .chain(&extra)
+        .chain(argv.iter().skip(1))
+        .cloned()
+        .collect();
+    let matches = command.clone().get_matches_from(argv);
+    let mut config = effective(&command, &matches, sources);
+    // The walk reads these itself, unless an option overrides them
+    for (key, value, source) in dir_keys {
+        let overridden = DIR_KEYS
+            .iter()
+            .any(|(dir_key, option)| *dir_key == key && config.is_given(option));
+        if !overridden {
+            config.settings.push(Setting { key, value, source });
+        }
+    }
+    Ok((matches, config))
+}
+
+/// The command line `values` of `arg` as a setting's value.
+fn as_value(arg: &Arg, values: Vec<String>) -> Option<Value> {
+    match arg.get_action() {
+        ArgAction::SetTrue => Some(Value::Boolean(values.first()? == "true")),
+        ArgAction::Append => Some(Value::Array(
+            values.into_iter().map(Value::String).collect(),
+        )),
+        _ => match values.into_iter().next() {
+            Some(text) => Some(text.parse().map_or(Value::String(text), Value::Integer)),
+            // Given without its optional value
+            None if arg
+                .get_num_args()
+                .is_some_and(|range| range.min_values() == 0) =>
+            {
+                Some(Value::Boolean(true))
+            }
+            None => None,
+        },
+    }
+}
+
+/// The options set in `matches`, with the sources of the settings that came
+/// from outside the command line.
+fn effective(
+    command: &Command,
+    matches: &ArgMatches,
+    mut sources: HashMap<String, Source>,
+) -> Config {
+    let settings = options(command)
+        .filter_map(|arg| {
+            let id = arg.get_id().as_str();
+            let values: Vec<String> = matches
+                .get_raw(id)?
+                .map(|value| value.to_string_lossy().into_owned())
+                .collect();
+            let value = as_value(arg, values)?;
+            let source = match matches.value_source(id) {
+                Some(ValueSource::DefaultValue) => Source::Default,
+                _ => sources.remove(id).unwrap_or(Source::CommandLine),
+            };
+            Some(Setting {
+                key: arg.get_long()?.to_string(),
+                value,
+                source,
+            })
+        })
+        .collect();
+    Config { settings }
+}
+
+/// A config file listing every option, commented out, with its help and
+/// default value.
+pub fn scaffold(mut command: Command) -> String {
+    command.build();
+    let mut out = String::from(
+        "# mdfiles settings, keyed by the options' long names. Uncomment a setting\n\
+         # to use it; command line options and MDFILES_* environment variables\n\
+         # take precedence.\n",
+    );
+    for arg in options(&command) {
+        out.push('\n');
+        if let Some(help) = arg.get_help() {
+            out.push_str(&format!("# {}\n", help));
+        }
+        let choices: Vec<_> = arg
+            .get_possible_values()
+            .iter()
+            .filter(|_| arg.get_action().takes_values())
+            .map(|value| value.get_name().to_string())
+            .collect();
+        if !choices.is_empty() {
+            out.push_str(&format!("# One of: {}\n", choices.join(", ")));
+        }
+        let defaults = arg
+            .get_default_values()
+            .iter()
+            .map(|value| value.to_string_lossy().into_owned())
+            .collect();
+        let value = as_value(arg, defaults).unwrap_or_else(|| match arg.get_action() {
+            ArgAction::Append => Value::Array(Vec::new()),
+            _ => {
+                let name = arg.get_value_names().and_then(|names| names.first());
+                Value::String(name.map_or("VALUE".to_string(), |name| name.to_string()))
+            }
+        });
+        out.push_str(&format!(
+            "#{} = {}\n",
+            arg.get_long().unwrap_or_default(),
+            value
+        ));
+    }
+    out
+}
+
+/// Options taking a date, checked like the command line does.
+const DATE_KEYS: &[&str] = &["date", "from", "to"];
+
+/// The per-directory keys of a `mdfiles.toml`, with the spans of the
+/// exclude patterns.
+#[derive(Deserialize)]
+struct DirKeys {
+    #[serde(default)]
+    suffixes: Option<Spanned<Vec<String>>>,
+    #[serde(default)]
+    exclude: Vec<Spanned<String>>,
+}
+
+/// Problems in the config file `text`, with the byte ranges they're at.
+/// `dir_keys` allows the per-directory keys of `mdfiles.toml`.
+pub fn validate(command: &Command, text: &str, dir_keys: bool) -> Vec<(Range<usize>, String)> {
+    let spanned = |e: toml::de::Error| (e.span().unwrap_or(0..0), e.message().to_string());
+    let table: BTreeMap<Spanned<String>, Spanned<Value>> = match toml::from_str(text) {
+        Ok(table) => table,
+        Err(e) => return vec![spanned(e)],
+    };
+
+    let mut problems = Vec::new();
+    if dir_keys {
+        match toml::from_str::<DirKeys>(text) {
+            Ok(keys) => {
+                if let Some(suffixes) = keys.suffixes
+                    && suffixes.get_ref().is_empty()
+                {
+                    problems.push((suffixes.span(), "no suffixes, nothing matches".to_string()));
+                }
+                let mut builder = GitignoreBuilder::new(".");
+                for pattern in &keys.exclude {
+                    if let Err(e) = builder.add_line(None, pattern.get_ref()) {
+                        problems.push((pattern.span(), e.to_string()));
+                    }
+                }
+            }
+            Err(e) => problems.push(spanned(e)),
+        }
+    }
+
+    for (key, value) in &table {
+        if dir_keys && DIR_KEYS.iter().any(|(dir_key, _)| dir_key == key.get_ref()) {
+            continue;
+        }
+        let id = key.get_ref().replace('-', "_");
+        let Some(arg) = options(command).find(|arg| arg.get_id() == id.as_str()) else {
+            problems.push((key.span(), format!("unknown setting '{}'", key.get_ref())));
+            continue;
+        };
+        let args = match to_args(arg, value.get_ref()) {
+            Ok(args) => args,
+            Err(e) => {
+                problems.push((value.span(), e));
+                continue;
+            }
+        };
+        // Only the values are checked: a file may hold settings that are
+        // only used together, or that the command line overrides
+        let argv = std::iter::once(OsString::from("mdfiles")).chain(args.iter().cloned());
+        if let Err(e) = command.clone().try_get_matches_from(argv)
+            && matches!(
+                e.kind(),
+                ErrorKind::InvalidValue | ErrorKind::ValueValidation
+            )
+        {
+            let message = e.render().to_string();
+            let message = message.lines().next().unwrap_or_default();
+            problems.push((
+                value.span(),
+                message.trim_start_matches("error: ").to_string(),
+            ));
+        }
+        if DATE_KEYS.contains(&id.as_str())
+            && let Some(text) = scalar(value.get_ref())
+            && let Err(e) = get_date(Some(&text))
+        {
+            problems.push((value.span(), e));
+        }
+    }
+    problems.sort_by_key(|(span, _)| span.start);
+    problems
+}
+
+/// Line and column (both from 1) of the byte `offset` in `text`.
+fn position(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Problems in the config file at `path`, as `PATH:LINE:COLUMN: MESSAGE`.
+pub fn check(command: &Command, path: &Path) -> Result<Vec<String>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| i18n::trf("can't read '{}': {}", &[&path.display(), &e]))?;
+    let dir_keys = path.file_name() == Some(OsStr::new(dirconfig::CONFIG_FILE));
+    Ok(validate(command, &text, dir_keys)
+        .into_iter()
+        .map(|(span, message)| {
+            let (line, column) = position(&text, span.start);
+            format!("{}:{}:{}: {}", path.display(), line, column, message)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_args() {
+        let command = Command::new("test")
+            .arg(Arg::new("strict").long("strict").action(ArgAction::SetTrue))
+            .arg(Arg::new("tag").long("tag").action(ArgAction::Append))
+            .arg(Arg::new("jobs").long("jobs"))
+            .arg(Arg::new("shuffle").long("shuffle").num_args(0..=1));
+        let arg = |id: &str| {
+            command
+                .get_arguments()
+                .find(|arg| arg.get_id() == id)
+                .unwrap()
+        };
+
+        assert_eq!(
+            to_args(arg("strict"), &Value::String("yes".into())).unwrap(),
+            vec![OsString::from("--strict")]
+        );
+        assert!(
+            to_args(arg("strict"), &Value::Boolean(false))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(to_args(arg("strict"), &Value::Integer(2)).is_err());
+        assert_eq!(
+            to_args(
+                arg("tag"),
+                &"tag = [\"a\", \"b\"]".parse::<toml::Table>().unwrap()["tag"]
+            )
+            .unwrap(),
+            vec![OsString::from("--tag=a"), OsString::from("--tag=b")]
+        );
+        assert_eq!(
+            to_args(
+                arg("tag"),
+                &"[tag]\npng = \"embed\"".parse::<toml::Table>().unwrap()["tag"]
+            )
+            .unwrap(),
+            vec![OsString::from("--tag=png=embed")]
+        );
+        assert_eq!(
+            to_args(arg("jobs"), &Value::Integer(4)).unwrap(),
+            vec![OsString::from("--jobs=4")]
+        );
+        assert!(to_args(arg("jobs"), &Value::Array(Vec::new())).is_err());
+        assert_eq!(
+            to_args(arg("shuffle"), &Value::Boolean(true)).unwrap(),
+            vec![OsString::from("--shuffle")]
+        );
+        assert_eq!(
+            to_args(arg("shuffle"), &Value::Integer(42)).unwrap(),
+            vec![OsString::from("--shuffle=42")]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let command = Command::new("test")
+            .arg(Arg::new("suffix").long("suffix"))
+            .arg(Arg::new("date").long("date"))
+            .arg(
+                Arg::new("jobs")
+                    .long("jobs")
+                    .value_parser(clap::value_parser!(u32)),
+            );
+        let text = "suffix = \".md\"\njobs = -1\ndate = \"2025-02-30\"\ncolor = true\n\
+                    exclude = [\"{a,b\"]\n";
+
+        let problems: Vec<_> = validate(&command, text, true)
+            .into_iter()
+            .map(|(span, message)| (position(text, span.start), message))
+            .collect();
+        assert_eq!(problems.len(), 4);
+        assert_eq!(problems[0].0, (2, 8));
+        assert!(problems[0].1.starts_with("invalid value '-1' for '--jobs"));
+        assert_eq!(
+            problems[1],
+            (
+                (3, 8),
+                "Invalid date format (should be YYYY-MM-DD)".to_string()
+            )
+        );
+        assert_eq!(problems[2], ((4, 1), "unknown setting 'color'".to_string()));
+        assert_eq!(problems[3].0, (5, 12));
+
+        assert_eq!(
+            validate(&command, "exclude = []\n", false)[0].1,
+            "unknown setting 'exclude'"
+        );
+        assert_eq!(validate(&command, "suffix = \n", false)[0].0.start, 9);
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let command = Command::new("test")
+            .arg(
+                Arg::new("suffix")
+                    .long("suffix")
+                    .default_value(".md")
+                    .help("File suffix to match"),
+            )
+            .arg(Arg::new("strict").long("strict").action(ArgAction::SetTrue));
+        let text = scaffold(command.clone());
+        assert!(text.contains("\n# File suffix to match\n#suffix = \".md\"\n"));
+        assert!(text.contains("\n#strict = false\n"));
+
+        let uncommented: String = text
+            .lines()
+            .filter_map(|line| line.strip_prefix('#'))
+            .filter(|line| !line.starts_with(' '))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert!(validate(&command, &uncommented, false).is_empty());
+    }
+}
@@ -0,0 +1,298 @@
+use chrono::{DateTime, Local, SecondsFormat};
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::traversal::WalkOptions;
+use crate::{atomic, i18n, index, status};
+
+/// How soon a stop is noticed while waiting for the next refresh.
+const TICK: Duration = Duration::from_millis(100);
+
+/// How long `start` waits for the daemon to write its pid file.
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long `stop` waits for the daemon to finish its current update.
+const STOP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Path of the pid file of the daemon keeping the index at `db` up to date.
+pub fn pid_path(db: &Path) -> PathBuf {
+    atomic::sibling(db, "", ".pid")
+}
+
+/// Path of the log of a daemon started in the background for `db`.
+pub fn log_path(db: &Path) -> PathBuf {
+    atomic::sibling(db, "", ".log")
+}
+
+/// Send `signal` to the process with `pid`.
+#[cfg(unix)]
+fn signal(pid: u32, signal: libc::c_int) -> std::io::Result<()> {
+    let pid = libc::pid_t::try_from(pid).map_err(std::io::Error::other)?;
+    // SAFETY: kill has no memory effects, and the pid is positive
+    match unsafe { libc::kill(pid, signal) } {
+        0 => Ok(()),
+        _ => Err(std::io::Error::last_os_error()),
+    }
+}
+
+/// Whether a process with `pid` is running.
+#[cfg(unix)]
+fn alive(pid: u32) -> bool {
+    match signal(pid, 0) {
+        Ok(()) => true,
+        // It exists, but belongs to someone else
+        Err(e) => e.raw_os_error() == Some(libc::EPERM),
+    }
+}
+
+/// Whether a process with `pid` is running, as `tasklist` lists it.
+#[cfg(not(unix))]
+fn alive(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .stderr(Stdio::null())
+        .output()
+        .is_ok_and(|output| {
+            String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid))
+        })
+}
+
+/// The pid of the daemon with the pid file at `path`, if it's running.
+fn running(path: &Path) -> Option<u32> {
+    let pid = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    alive(pid).then_some(pid)
+}
+
+/// Fail when a daemon is already updating `db`.
+fn check_not_running(db: &Path) -> Result<(), String> {
+    match running(&pid_path(db)) {
+        Some(pid) => Err(format!(
+            "a daemon is already updating '{}' (pid {})",
+            db.display(),
+            pid
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Ask the process with `pid` to stop with SIGTERM.
+#[cfg(unix)]
+fn terminate(pid: u32) -> Result<(), String> {
+    signal(pid, libc::SIGTERM).map_err(|e| format!("can't stop the daemon (pid {}): {}", pid, e))
+}
+
+/// Stop the process with `pid`. Windows has no SIGTERM, so it's killed.
+#[cfg(not(unix))]
+fn terminate(pid: u32) -> Result<(), String> {
+    let status = Command::new("taskkill")
+        .args(["/F", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .status()
+        .map_err(|e| format!("can't run 'taskkill': {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("can't stop the daemon (pid {})", pid))
+    }
+}
+
+/// Start a daemon for `db` in the background: this program again with
+/// `args` and `--foreground`, detached from the terminal, with its messages
+/// appended to `db.log`. Returns its pid once it has written its pid file.
+pub fn start(db: &Path, args: impl IntoIterator<Item = OsString>) -> Result<u32, String> {
+    check_not_running(db)?;
+    let log_file = log_path(db);
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_file)
+        .map_err(|e| format!("can't open '{}': {}", log_file.display(), e))?;
+    let program = env::current_exe().map_err(|e| format!("can't find mdfiles: {}", e))?;
+
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .arg("--foreground")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(log);
+    // In a session of its own, without a controlling terminal, so neither
+    // Ctrl-C nor closing the terminal reaches it
+    #[cfg(unix)]
+    // SAFETY: setsid is async-signal-safe and touches no memory of the parent
+    unsafe {
+        std::os::unix::process::CommandExt::pre_exec(&mut command, || match libc::setsid() {
+            -1 => Err(std::io::Error::last_os_error()),
+            _ => Ok(()),
+        });
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("can't start the daemon: {}", e))?;
+
+    let pid_file = pid_path(db);
+    let started = Instant::now();
+    while started.elapsed() < START_TIMEOUT {
+        if running(&pid_file) == Some(child.id()) {
+            return Ok(child.id());
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(format!(
+                "the daemon exited ({}), see '{}'",
+                status,
+                log_file.display()
+            ));
+        }
+        thread::sleep(TICK);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    Err(format!(
+        "the daemon didn't start within {}s, see '{}'",
+        START_TIMEOUT.as_secs(),
+        log_file.display()
+    ))
+}
+
+/// Stop the daemon updating `db`, waiting for it to finish its current
+/// update and remove its pid file. Returns its pid.
+pub fn stop(db: &Path) -> Result<u32, String> {
+    let pid_file = pid_path(db);
+    let pid =
+        running(&pid_file).ok_or_else(|| format!("no daemon is updating '{}'", db.display()))?;
+    terminate(pid)?;
+    // A killed daemon leaves its pid file behind
+    #[cfg(not(unix))]
+    let _ = fs::remove_file(&pid_file);
+
+    let started = Instant::now();
+    while pid_file.exists() && alive(pid) {
+        if started.elapsed() > STOP_TIMEOUT {
+            return Err(format!("the daemon (pid {}) is still running", pid));
+        }
+        thread::sleep(TICK);
+    }
+    Ok(pid)
+}
+
+/// Keep the index of `root` at `db` up to date, building it first if there
+/// isn't one, and updating it every `interval` until stopped with SIGTERM
+/// or Ctrl-C. The daemon's pid is in `db.pid` while it runs. Failed updates
+/// are reported and retried at the next interval.
+pub fn run(
+    db: &Path,
+    root: &Path,
+    interval: Duration,
+    options: &WalkOptions,
+) -> Result<(), String> {
+    check_not_running(db)?;
+    let pid_file = pid_path(db);
+    let stop = Arc::new(AtomicBool::new(false));
+    let handler = Arc::clone(&stop);
+    ctrlc::set_handler(move || handler.store(true, Ordering::Relaxed))
+        .map_err(|e| format!("can't handle signals: {}", e))?;
+    atomic::write(&pid_file, format!("{}\n", std::process::id()), false)
+        .map_err(|e| i18n::trf("can't write '{}': {}", &[&pid_file.display(), &e]))?;
+
+    let result = refresh_until(db, root, interval, options, &stop);
+    let _ = fs::remove_file(&pid_file);
+    result
+}
+
+fn refresh_until(
+    db: &Path,
+    root: &Path,
+    interval: Duration,
+    options: &WalkOptions,
+    stop: &AtomicBool,
+) -> Result<(), String> {
+    if !db.exists() {
+        let count = index::build(db, root, false, options)?;
+        status::say(i18n::trn(
+            "indexed {} files into {}",
+            count,
+            &[&count, &db.display()],
+        ));
+    }
+    status::say(i18n::trf(
+        "updating {} every {}s (pid {})",
+        &[&db.display(), &interval.as_secs(), &std::process::id()],
+    ));
+    while !stop.load(Ordering::Relaxed) {
+        match index::update(db, root, options) {
+            Ok(refresh) if refresh.scanned == 0 && refresh.edited == 0 => {}
+            Ok(refresh) => status::say(refresh.describe(db)),
+            Err(e) => status::warn(e),
+        }
+        let started = Instant::now();
+        while !stop.load(Ordering::Relaxed) && started.elapsed() < interval {
+            thread::sleep(TICK);
+        }
+    }
+    status::say(i18n::tr("stopped"));
+    Ok(())
+}
+
+/// Whether a daemon is updating the index at `db`, and what the index holds.
+pub fn report(db: &Path) -> Result<String, String> {
+    let mut out = match running(&pid_path(db)) {
+        Some(pid) => i18n::trf("daemon: running (pid {})", &[&pid]),
+        None => i18n::tr("daemon: not running").to_string(),
+    };
+    out.push('\n');
+    let summary = index::summary(db)?;
+    out.push_str(&(i18n::trf("index: {}", &[&db.display()]) + "\n"));
+    let files = if summary.content {
+        "files: {} in {} directories, with content"
+    } else {
+        "files: {} in {} directories"
+    };
+    let files = i18n::trn(files, summary.dirs, &[&summary.files, &summary.dirs]);
+    out.push_str(&(files + "\n"));
+    if let Ok(modified) = fs::metadata(db).and_then(|metadata| metadata.modified()) {
+        let modified = DateTime::<Local>::from(modified);
+        let modified = modified.to_rfc3339_opts(SecondsFormat::Secs, true);
+        out.push_str(&(i18n::trf("changed: {}", &[&modified]) + "\n"));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join("a.md"), "").unwrap();
+        let db = root.path().join("index.db");
+        assert!(report(&db).is_err());
+
+        index::build(&db, root.path(), false, &WalkOptions::default()).unwrap();
+        let text = report(&db).unwrap();
+        assert!(text.starts_with("daemon: not running\n"));
+        assert!(text.contains("files: 1 in 1 directory\n"));
+
+        fs::write(pid_path(&db), format!("{}\n", std::process::id())).unwrap();
+        assert!(
+            report(&db)
+                .unwrap()
+                .starts_with(&format!("daemon: running (pid {})\n", std::process::id()))
+        );
+        assert!(run(&db, root.path(), Duration::ZERO, &WalkOptions::default()).is_err());
+
+        i18n::set_lang(i18n::Lang::De);
+        let text = report(&db).unwrap();
+        i18n::set_lang(i18n::Lang::En);
+        assert!(text.starts_with("Dienst: läuft (PID "));
+        assert!(text.contains("Dateien: 1 in 1 Verzeichnis\n"));
+    }
+}
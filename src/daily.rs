@@ -0,0 +1,412 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::lock::Lock;
+use crate::{LinkStyle, atomic, dryrun, i18n, markdown, media, toc};
+
+pub const DEFAULT_HEADING: &str = "## Files";
+
+/// Settings of the Obsidian "Daily notes" core plugin, stored in
+/// `.obsidian/daily-notes.json`. Missing keys fall back to Obsidian's defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DailyNoteSettings {
+    folder: String,
+    format: String,
+    template: String,
+}
+
+fn read_settings(vault: &Path) -> Result<DailyNoteSettings, String> {
+    let path = vault.join(".obsidian").join("daily-notes.json");
+    if !path.exists() {
+        return Ok(DailyNoteSettings::default());
+    }
+
+    let data = fs::read_to_string(&path)
+        .map_err(|e| i18n::trf("can't read '{}': {}", &[&path.display(), &e]))?;
+    serde_json::from_str(&data)
+        .map_err(|e| i18n::trf("can't parse '{}': {}", &[&path.display(), &e]))
+}
+
+/// Convert a moment.js date format (as used by Obsidian) to a chrono format.
+fn moment_to_strftime(format: &str) -> String {
+    const TOKENS: &[(&str, &str)] = &[
+        ("YYYY", "%Y"),
+        ("YY", "%y"),
+        ("MMMM", "%B"),
+        ("MMM", "%b"),
+        ("MM", "%m"),
+        ("M", "%-m"),
+        ("DDDD", "%j"),
+        ("DD", "%d"),
+        ("D", "%-d"),
+        ("dddd", "%A"),
+        ("ddd", "%a"),
+        ("ww", "%V"),
+        ("E", "%u"),
+    ];
+
+    let mut out = String::new();
+    let mut rest = format;
+    'outer: while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            // Bracketed text is a literal
+            let end = rest.find(']').unwrap_or(rest.len());
+            out.push_str(&rest[1..end].replace('%', "%%"));
+            rest = &rest[(end + 1).min(rest.len())..];
+            continue;
+        }
+
+        for (token, spec) in TOKENS {
+            if let Some(tail) = rest.strip_prefix(token) {
+                out.push_str(spec);
+                rest = tail;
+                continue 'outer;
+            }
+        }
+
+        if c == '%' {
+            out.push_str("%%");
+        } else {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+fn note_name(settings: &DailyNoteSettings, date: NaiveDate) -> String {
+    let format = if settings.format.is_empty() {
+        "YYYY-MM-DD"
+    } else {
+        &settings.format
+    };
+
+    date.format(&moment_to_strftime(format)).to_string()
+}
+
+fn note_path(vault: &Path, settings: &DailyNoteSettings, date: NaiveDate) -> PathBuf {
+    let folder = settings.folder.trim_matches('/');
+    let mut path = vault.to_path_buf();
+    if !folder.is_empty() {
+        path.push(folder);
+    }
+    path.push(format!("{}.md", note_name(settings, date)));
+    path
+}
+
+/// Initial content of a new note, taken from the configured template if any.
+fn new_note_content(vault: &Path, settings: &DailyNoteSettings, date: NaiveDate) -> String {
+    let template = settings.template.trim_matches('/');
+    if template.is_empty() {
+        return String::new();
+    }
+
+    let mut path = vault.join(template);
+    if path.extension().is_none() {
+        path.set_extension("md");
+    }
+
+    match fs::read_to_string(&path) {
+        Ok(content) => content
+            .replace("{{title}}", &note_name(settings, date))
+            .replace("{{date}}", &date.format("%Y-%m-%d").to_string()),
+        Err(_) => String::new(),
+    }
+}
+
+/// Targets of the markdown links and audio/video players in `content`.
+fn link_targets(content: &str) -> Vec<String> {
+    let mut targets = markdown::link_targets(content);
+    targets.extend(media::player_sources(content));
+    targets
+}
+
+/// Whether `links` has one to `target`, possibly to a heading in it.
+fn has_link(links: &[String], target: &str) -> bool {
+    links.iter().any(|link| {
+        link == target
+            || link
+                .strip_prefix(target)
+                .is_some_and(|rest| rest.starts_with('#'))
+    })
+}
+
+/// Append `entries` to the end of the section starting at `heading`, adding
+/// the section when the note doesn't have it yet.
+fn append_to_section(content: &str, heading: &str, entries: &[String]) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let heading = heading.trim();
+    let level = toc::heading(heading).map_or(usize::MAX, |(level, _)| level);
+    // Headings outside code blocks, by line
+    let headings: Vec<(usize, usize)> = markdown::prose(content)
+        .into_iter()
+        .filter_map(|(i, line)| toc::heading(line).map(|(level, _)| (i, level)))
+        .collect();
+
+    let found = markdown::prose(content)
+        .into_iter()
+        .find(|(_, line)| line.trim() == heading);
+
+    match found {
+        Some((start, _)) => {
+            let end = headings
+                .iter()
+                .find(|&&(i, l)| i > start && l <= level)
+                .map_or(lines.len(), |&(i, _)| i);
+            let last = (start + 1..end)
+                .rev()
+                .find(|&i| !lines[i].trim().is_empty())
+                .unwrap_or(start);
+
+            let mut out: Vec<String> = lines[..=last].iter().map(|l| l.to_string()).collect();
+            if last == start {
+                out.push(String::new());
+            }
+            out.extend(entries.iter().cloned());
+            out.push(String::new());
+            out.extend(lines[end..].iter().map(|l| l.to_string()));
+            let mut text = out.join("\n");
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text
+        }
+        None => {
+            let mut text = content.to_string();
+            if !text.is_empty() {
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push('\n');
+            }
+            text.push_str(heading);
+            text.push_str("\n\n");
+            for entry in entries {
+                text.push_str(entry);
+                text.push('\n');
+            }
+            text
+        }
+    }
+}
+
+/// Write `files` into the daily note for `date` in `vault`, creating the note
+/// if needed. Files already linked from the note are skipped, so re-running
+/// only adds new entries. With `options.dry_run`, only print the change as a diff.
+/// Returns the path of the note.
+pub fn write_daily(
+    vault: &Path,
+    date: NaiveDate,
+    heading: &str,
+    files: &[PathBuf],
+    style: &LinkStyle,
+    options: atomic::Options,
+) -> Result<PathBuf, String> {
+    if !vault.is_dir() {
+        return Err(format!("vault '{}' is not a directory", vault.display()));
+    }
+
+    let settings = read_settings(vault)?;
+    let path = note_path(vault, &settings, date);
+    // Other runs appending to the note wait until this one has written it
+    let _lock = if options.dry_run {
+        None
+    } else {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| i18n::trf("can't create '{}': {}", &[&parent.display(), &e]))?;
+        }
+        Some(Lock::acquire(&path)?)
+    };
+
+    let existing = if path.exists() {
+        Some(
+            fs::read_to_string(&path)
+                .map_err(|e| i18n::trf("can't read '{}': {}", &[&path.display(), &e]))?,
+        )
+    } else {
+        None
+    };
+    let content = existing
+        .clone()
+        .unwrap_or_else(|| new_note_content(vault, &settings, date));
+
+    // Links lead from the note's folder, and the note doesn't list itself
+    let style = style.relative_to(path.parent().unwrap_or(vault));
+    let note = fs::canonicalize(&path).ok();
+    let links = link_targets(&content);
+    let entries: Vec<String> = files
+        .iter()
+        .filter(|file| note.is_none() || fs::canonicalize(file).ok() != note)
+        .filter(|file| !has_link(&links, &style.target(file)))
+        .map(|file| style.format(file))
+        .collect();
+    let updated = append_to_section(&content, heading, &entries);
+
+    if options.dry_run {
+        print!("{}", dryrun::diff(&path, existing.as_deref(), &updated));
+        return Ok(path);
+    }
+    atomic::write(&path, updated, options.backup)
+        .map_err(|e| i18n::trf("can't write '{}': {}", &[&path.display(), &e]))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 4).unwrap()
+    }
+
+    #[test]
+    fn test_moment_default_format() {
+        assert_eq!(moment_to_strftime("YYYY-MM-DD"), "%Y-%m-%d");
+    }
+
+    #[test]
+    fn test_moment_literal_and_names() {
+        let format = moment_to_strftime("[Week] ww, dddd D MMMM YY");
+        assert_eq!(
+            date().format(&format).to_string(),
+            "Week 10, Tuesday 4 March 25"
+        );
+    }
+
+    #[test]
+    fn test_note_path_defaults() {
+        let settings = DailyNoteSettings::default();
+        let path = note_path(Path::new("vault"), &settings, date());
+        assert_eq!(path, Path::new("vault").join("2025-03-04.md"));
+    }
+
+    #[test]
+    fn test_note_path_with_folder_and_nested_format() {
+        let settings = DailyNoteSettings {
+            folder: "Journal/".to_string(),
+            format: "YYYY/MM/YYYY-MM-DD".to_string(),
+            template: String::new(),
+        };
+        let path = note_path(Path::new("vault"), &settings, date());
+        assert_eq!(path, Path::new("vault/Journal/2025/03/2025-03-04.md"));
+    }
+
+    #[test]
+    fn test_append_to_section_adds_missing_section() {
+        let entries = vec!["- [a.md](a.md)".to_string()];
+        let result = append_to_section("# Today\n\nNotes", "## Files", &entries);
+        assert_eq!(result, "# Today\n\nNotes\n\n## Files\n\n- [a.md](a.md)\n");
+    }
+
+    #[test]
+    fn test_append_to_section_keeps_existing_entries() {
+        let content = "# Today\n\n## Files\n\n- [old.md](old.md)\n\n## Later\n\ntext\n";
+        let entries = vec!["- [new.md](new.md)".to_string()];
+        let result = append_to_section(content, "## Files", &entries);
+        assert_eq!(
+            result,
+            "# Today\n\n## Files\n\n- [old.md](old.md)\n- [new.md](new.md)\n\n## Later\n\ntext\n"
+        );
+    }
+
+    #[test]
+    fn test_append_to_section_skips_code_blocks() {
+        let entries = vec!["- [b.md](b.md)".to_string()];
+        let content = "```\n## Files\n```\n## Files\n\n- [a.md](a.md)\n```\n# not a heading\n```\n";
+        assert_eq!(
+            append_to_section(content, "## Files", &entries),
+            "```\n## Files\n```\n## Files\n\n- [a.md](a.md)\n```\n# not a heading\n```\n- [b.md](b.md)\n"
+        );
+    }
+
+    #[test]
+    fn test_append_to_section_empty_section() {
+        let entries = vec!["- [a.md](a.md)".to_string()];
+        let result = append_to_section("## Files\n## Next\n", "## Files", &entries);
+        assert_eq!(result, "## Files\n\n- [a.md](a.md)\n\n## Next\n");
+    }
+
+    #[test]
+    fn test_has_link() {
+        let links = link_targets("see [main](src/main.rs) and src/lib.rs");
+        assert!(has_link(&links, "src/main.rs"));
+        assert!(!has_link(&links, "src/lib.rs"));
+        assert!(!has_link(&links, "main.rs"));
+
+        let links = link_targets(
+            "- [a.md](notes/a.md#intro)\n- <audio controls src=\"memo &amp; more.m4a\"></audio>\n",
+        );
+        assert!(has_link(&links, "notes/a.md"));
+        assert!(has_link(&links, "memo & more.m4a"));
+        assert!(!has_link(&links, "notes/a"));
+        assert!(!has_link(&links, "memo.m4a"));
+    }
+
+    #[test]
+    fn test_write_daily_uses_settings_and_template() {
+        let vault = TempDir::new().unwrap();
+        fs::create_dir(vault.path().join(".obsidian")).unwrap();
+        fs::write(
+            vault.path().join(".obsidian").join("daily-notes.json"),
+            r#"{"folder": "Daily", "template": "Templates/Day"}"#,
+        )
+        .unwrap();
+        fs::create_dir(vault.path().join("Templates")).unwrap();
+        fs::write(vault.path().join("Templates/Day.md"), "# {{title}}\n").unwrap();
+
+        let files = vec![vault.path().join("src/main.rs")];
+        let path = write_daily(
+            vault.path(),
+            date(),
+            DEFAULT_HEADING,
+            &files,
+            &LinkStyle::default(),
+            atomic::Options::default(),
+        )
+        .unwrap();
+
+        assert_eq!(path, vault.path().join("Daily").join("2025-03-04.md"));
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "# 2025-03-04\n\n## Files\n\n- [main.rs](../src/main.rs)\n"
+        );
+    }
+
+    #[test]
+    fn test_write_daily_skips_linked_files() {
+        let vault = TempDir::new().unwrap();
+        let files = vec![vault.path().join("a.md")];
+        write_daily(
+            vault.path(),
+            date(),
+            DEFAULT_HEADING,
+            &files,
+            &LinkStyle::default(),
+            atomic::Options::default(),
+        )
+        .unwrap();
+
+        let files = vec![vault.path().join("a.md"), vault.path().join("b.md")];
+        let path = write_daily(
+            vault.path(),
+            date(),
+            DEFAULT_HEADING,
+            &files,
+            &LinkStyle::default(),
+            atomic::Options::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "## Files\n\n- [a.md](a.md)\n- [b.md](b.md)\n"
+        );
+    }
+}
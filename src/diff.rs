@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::markdown::{link_targets, normalize_target};
+use crate::{LinkStyle, i18n};
+
+/// Link targets listed in a snapshot: a markdown document such as a previous
+/// run's output.
+pub fn read_snapshot(path: &str) -> Result<Vec<PathBuf>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| i18n::trf("can't read '{}': {}", &[&path, &e]))?;
+    Ok(link_targets(&content)
+        .into_iter()
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Files of `new` missing from `old` (added) and of `old` missing from `new`
+/// (removed), by their link targets, keeping their order.
+pub fn compare(
+    old: &[PathBuf],
+    new: &[PathBuf],
+    style: &LinkStyle,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let key = |file: &PathBuf| normalize_target(&style.target(file));
+    let old_set: HashSet<String> = old.iter().map(key).collect();
+    let new_set: HashSet<String> = new.iter().map(key).collect();
+
+    let added = new
+        .iter()
+        .filter(|file| !old_set.contains(&key(file)))
+        .cloned()
+        .collect();
+    let removed = old
+        .iter()
+        .filter(|file| !new_set.contains(&key(file)))
+        .cloned()
+        .collect();
+
+    (added, removed)
+}
+
+/// A `## heading` followed by an entry for each of `files`.
+pub fn section(heading: &str, files: &[PathBuf], style: &LinkStyle) -> String {
+    let mut out = format!("## {}\n\n", heading);
+    for file in files {
+        out.push_str(&style.format(file));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn render(added: &[PathBuf], removed: &[PathBuf], style: &LinkStyle) -> String {
+    format!(
+        "{}\n{}",
+        section(i18n::tr("Added"), added, style),
+        section(i18n::tr("Removed"), removed, style)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn paths(items: &[&str]) -> Vec<PathBuf> {
+        items.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn test_compare() {
+        let old = paths(&["a.md", "./b.md", "c.md"]);
+        let new = paths(&["b.md", "d.md", "a.md"]);
+        let (added, removed) = compare(&old, &new, &LinkStyle::default());
+        assert_eq!(added, paths(&["d.md"]));
+        assert_eq!(removed, paths(&["c.md"]));
+    }
+
+    #[test]
+    fn test_render() {
+        let result = render(&paths(&["x/new.md"]), &[], &LinkStyle::default());
+        assert_eq!(result, "## Added\n\n- [new.md](x/new.md)\n\n## Removed\n\n");
+    }
+
+    #[test]
+    fn test_render_formats_files_once() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("new.md");
+        fs::write(&file, "abc").unwrap();
+        let style = LinkStyle {
+            strip_prefix: Some(dir.path().to_path_buf()),
+            show_size: true,
+            ..LinkStyle::default()
+        };
+        assert_eq!(
+            section("Added", &[file], &style),
+            "## Added\n\n- [new.md](new.md) (3 B)\n"
+        );
+    }
+
+    #[test]
+    fn test_read_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("snap.md");
+        fs::write(&path, "- [a.md](a.md)\n- [b.md](docs/b.md)\n").unwrap();
+        assert_eq!(
+            read_snapshot(path.to_str().unwrap()).unwrap(),
+            paths(&["a.md", "docs/b.md"])
+        );
+    }
+}
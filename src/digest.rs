@@ -0,0 +1,257 @@
+use chrono::{Datelike, Days, Locale, Months, NaiveDate};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::group::{self, GroupBy, week_heading};
+use crate::stats::{bar, by_count, extension, table};
+use crate::{DateRange, LinkStyle, TimeField, date_heading, i18n};
+
+/// How many days `monthly` lists as the busiest.
+const BUSIEST: usize = 5;
+
+/// The seven days ending on `date`.
+pub fn week_ending(date: NaiveDate) -> DateRange {
+    DateRange {
+        start: date - Days::new(6),
+        end: date,
+    }
+}
+
+/// The month `date` is in.
+pub fn month_of(date: NaiveDate) -> DateRange {
+    // Every month has a first day
+    let start = date.with_day(1).unwrap();
+    DateRange {
+        start,
+        end: start + Months::new(1) - Days::new(1),
+    }
+}
+
+/// The month of `date`, as in `2025-03`, or `March 2025` with a locale.
+fn month_heading(date: NaiveDate, locale: Option<Locale>) -> String {
+    match locale {
+        Some(locale) => date.format_localized("%B %Y", locale).to_string(),
+        None => date.format("%Y-%m").to_string(),
+    }
+}
+
+/// A table of the number of files on each day of `range`, with a bar.
+fn day_table(
+    by_date: &BTreeMap<NaiveDate, Vec<&PathBuf>>,
+    range: DateRange,
+    locale: Option<Locale>,
+) -> String {
+    let counts: Vec<(NaiveDate, usize)> = range
+        .days()
+        .map(|day| (day, by_date.get(&day).map_or(0, Vec::len)))
+        .collect();
+    let max = counts.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    let rows: Vec<Vec<String>> = counts
+        .iter()
+        .map(|(day, count)| {
+            vec![
+                date_heading(*day, locale),
+                count.to_string(),
+                bar(*count, max),
+            ]
+        })
+        .collect();
+    table(&[i18n::tr("Date"), i18n::tr("Files"), ""], &rows)
+}
+
+/// "Week of ..." document for `files` of the week `range`: the number of
+/// files on each day, then the files under a heading per day.
+pub fn weekly(
+    files: &[PathBuf],
+    range: DateRange,
+    field: TimeField,
+    locale: Option<Locale>,
+    style: &LinkStyle,
+) -> String {
+    let by_date = group::by_date(files, field);
+    let mut out = format!(
+        "# {}\n\n",
+        i18n::trf("Week of {}", &[&date_heading(range.start, locale)])
+    );
+    out.push_str(&(i18n::trn("Total: {} files", files.len(), &[&files.len()]) + "\n\n"));
+    out.push_str(&day_table(&by_date, range, locale));
+    let days = group::render(files, GroupBy::Day, field, locale, style);
+    if !days.is_empty() {
+        out.push('\n');
+        out.push_str(&days);
+    }
+    out
+}
+
+/// Month-level rollup of `files` of the month `range`: a section per ISO
+/// week with its daily counts, totals per extension, the busiest days, and
+/// the files grouped by week and day as an appendix.
+pub fn monthly(
+    files: &[PathBuf],
+    range: DateRange,
+    field: TimeField,
+    locale: Option<Locale>,
+    style: &LinkStyle,
+) -> String {
+    let by_date = group::by_date(files, field);
+    let mut out = format!(
+        "# {}\n\n",
+        i18n::trf("Month of {}", &[&month_heading(range.start, locale)])
+    );
+    out.push_str(&(i18n::trn("Total: {} files", files.len(), &[&files.len()]) + "\n\n"));
+
+    // The days of the month by ISO week, with weeks cut at the month's ends
+    let mut weeks: Vec<(String, DateRange)> = Vec::new();
+    for day in range.days() {
+        let heading = week_heading(day);
+        match weeks.last_mut() {
+            Some((last, week)) if *last == heading => week.end = day,
+            _ => weeks.push((heading, day.into())),
+        }
+    }
+    out.push_str(&format!("## {}\n\n", i18n::tr("Weeks")));
+    for (heading, week) in weeks {
+        let count: usize = week
+            .days()
+            .map(|day| by_date.get(&day).map_or(0, Vec::len))
+            .sum();
+        out.push_str(&format!("### {}\n\n", heading));
+        out.push_str(&(i18n::trn("Total: {} files", count, &[&count]) + "\n\n"));
+        out.push_str(&day_table(&by_date, week, locale));
+        out.push('\n');
+    }
+
+    let mut extensions = BTreeMap::new();
+    for file in files {
+        *extensions.entry(extension(file)).or_insert(0) += 1;
+    }
+    let rows: Vec<Vec<String>> = by_count(extensions)
+        .into_iter()
+        .map(|(ext, count)| vec![format!("`{}`", ext), count.to_string()])
+        .collect();
+    out.push_str(&format!("## {}\n\n", i18n::tr("Extensions")));
+    out.push_str(&table(&[i18n::tr("Extension"), i18n::tr("Files")], &rows));
+
+    let mut busiest: Vec<(NaiveDate, usize)> = by_date
+        .iter()
+        .map(|(day, files)| (*day, files.len()))
+        .collect();
+    busiest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let rows: Vec<Vec<String>> = busiest
+        .into_iter()
+        .take(BUSIEST)
+        .map(|(day, count)| vec![date_heading(day, locale), count.to_string()])
+        .collect();
+    out.push_str(&format!("\n## {}\n\n", i18n::tr("Busiest Days")));
+    out.push_str(&table(&[i18n::tr("Date"), i18n::tr("Files")], &rows));
+
+    if !files.is_empty() {
+        out.push_str(&format!("\n## {}\n\n", i18n::tr("Files")));
+        out.push_str(&group::render_at(
+            files,
+            GroupBy::Week,
+            field,
+            locale,
+            style,
+            3,
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+    }
+
+    /// A file modified at noon on `date`.
+    fn file_on(dir: &TempDir, name: &str, date: NaiveDate) -> PathBuf {
+        let path = dir.path().join(name);
+        let noon = date
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap();
+        File::create(&path)
+            .unwrap()
+            .set_modified(SystemTime::from(noon))
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_weekly() {
+        let dir = TempDir::new().unwrap();
+        let files = vec![
+            file_on(&dir, "a.md", date(3, 10)),
+            file_on(&dir, "b.md", date(3, 14)),
+            file_on(&dir, "c.md", date(3, 10)),
+        ];
+        let style = LinkStyle::default();
+        let target = |name: &str| style.target(&dir.path().join(name));
+        let week = week_ending(date(3, 16));
+        assert_eq!(week.start, date(3, 10));
+
+        let text = weekly(&files, week, TimeField::Modified, None, &style);
+        assert!(
+            text.starts_with("# Week of 2025-03-10\n\nTotal: 3 files\n\n| Date | Files |  |\n")
+        );
+        assert!(text.contains(&format!("| 2025-03-10 | 2 | {} |\n", "█".repeat(20))));
+        assert!(text.contains("| 2025-03-11 | 0 |  |\n"));
+        assert!(text.contains("| 2025-03-16 | 0 |  |\n"));
+        assert!(text.ends_with(&format!(
+            "\n## 2025-03-10\n\n- [a.md]({})\n- [c.md]({})\n\n## 2025-03-14\n\n- [b.md]({})\n",
+            target("a.md"),
+            target("c.md"),
+            target("b.md")
+        )));
+
+        let empty = weekly(&[], week, TimeField::Modified, None, &style);
+        assert!(empty.ends_with("| 2025-03-16 | 0 |  |\n"));
+    }
+
+    #[test]
+    fn test_monthly() {
+        let dir = TempDir::new().unwrap();
+        let files = vec![
+            file_on(&dir, "a.md", date(3, 3)),
+            file_on(&dir, "b.txt", date(3, 14)),
+            file_on(&dir, "c.md", date(3, 14)),
+            file_on(&dir, "d.md", date(3, 31)),
+        ];
+        let style = LinkStyle::default();
+        let target = |name: &str| style.target(&dir.path().join(name));
+        let month = month_of(date(3, 14));
+        assert_eq!((month.start, month.end), (date(3, 1), date(3, 31)));
+        assert_eq!(month_of(date(2, 10)).end, date(2, 28));
+
+        let text = monthly(&files, month, TimeField::Modified, None, &style);
+        assert!(text.starts_with("# Month of 2025-03\n\nTotal: 4 files\n\n## Weeks\n\n"));
+        // March 1st is a Saturday, and March 31st the Monday of the next week
+        assert!(text.contains(
+            "### 2025-W09\n\nTotal: 0 files\n\n| Date | Files |  |\n|---|---|---|\n\
+             | 2025-03-01 | 0 |  |\n| 2025-03-02 | 0 |  |\n\n### 2025-W10\n\nTotal: 1 file\n\n"
+        ));
+        assert!(text.contains("### 2025-W14\n\nTotal: 1 file\n\n"));
+        assert!(text.contains("| `.md` | 3 |\n| `.txt` | 1 |\n"));
+        assert!(text.contains(
+            "## Busiest Days\n\n| Date | Files |\n|---|---|\n\
+             | 2025-03-14 | 2 |\n| 2025-03-03 | 1 |\n| 2025-03-31 | 1 |\n"
+        ));
+        assert!(text.ends_with(&format!(
+            "\n## Files\n\n### 2025-W10\n\n#### 2025-03-03\n\n- [a.md]({})\n\n\
+             ### 2025-W11\n\n#### 2025-03-14\n\n- [b.txt]({})\n- [c.md]({})\n\n\
+             ### 2025-W14\n\n#### 2025-03-31\n\n- [d.md]({})\n",
+            target("a.md"),
+            target("b.txt"),
+            target("c.md"),
+            target("d.md")
+        )));
+    }
+}
@@ -0,0 +1,256 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::{has_suffix, status};
+
+pub const CONFIG_FILE: &str = "mdfiles.toml";
+
+/// An `mdfiles.toml` file: overrides for the files beneath its directory.
+/// Other keys are settings, read from the working directory's file only.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DirConfig {
+    /// Suffixes to match instead of `--suffix`.
+    pub suffixes: Option<Vec<String>>,
+    /// Patterns (gitignore syntax) of paths to leave out.
+    pub exclude: Vec<String>,
+}
+
+impl DirConfig {
+    pub fn parse(text: &str) -> Result<DirConfig, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+}
+
+/// The overrides in effect in a directory: the nearest suffixes, and the
+/// exclusions of every config from there up.
+#[derive(Default)]
+struct Scope {
+    suffixes: Option<Vec<String>>,
+    excludes: Vec<Gitignore>,
+}
+
+/// Keys of the working directory's `mdfiles.toml` left out because settings
+/// of higher precedence replace them: `--suffix` and `--exclude-root` given
+/// on the command line or in the environment.
+#[derive(Clone, Debug, Default)]
+pub struct Overridden {
+    pub suffixes: bool,
+    pub exclude: bool,
+}
+
+/// A directory as walked: its absolute path and scope, if it has one.
+type Walked = Option<Rc<(PathBuf, Rc<Scope>)>>;
+
+/// The overrides of the directories looked up so far, each read once. A
+/// walk keeps its own, so it sees the configs' current content.
+#[derive(Default)]
+pub struct Scopes {
+    /// Absolute path and scope of the directories as walked.
+    walked: RefCell<HashMap<PathBuf, Walked>>,
+    /// The directory looked up last, since entries of a directory come in a
+    /// row.
+    last: RefCell<Option<(PathBuf, Walked)>>,
+    /// The working directory, canonicalized, and the keys of its config
+    /// that are overridden, if any are.
+    working: Option<(PathBuf, Overridden)>,
+}
+
+fn warn(path: &Path, e: impl std::fmt::Display) {
+    let message = format!("{}: {}", path.display(), e);
+    status::warn(&message);
+}
+
+/// Read the config of `dir`, if it has one. Broken configs are skipped with
+/// a warning.
+fn load(dir: &Path) -> Option<DirConfig> {
+    let path = dir.join(CONFIG_FILE);
+    let text = fs::read_to_string(&path).ok()?;
+    DirConfig::parse(&text).inspect_err(|e| warn(&path, e)).ok()
+}
+
+/// Scope of the absolute directory `dir`: `parent`, updated with its own
+/// config, less the `overridden` keys.
+fn with_config(dir: &Path, parent: Rc<Scope>, overridden: Option<&Overridden>) -> Rc<Scope> {
+    let Some(mut config) = load(dir) else {
+        return parent;
+    };
+    if let Some(overridden) = overridden {
+        if overridden.suffixes {
+            config.suffixes = None;
+        }
+        if overridden.exclude {
+            config.exclude.clear();
+        }
+    }
+    let mut excludes = parent.excludes.clone();
+    if !config.exclude.is_empty() {
+        let mut builder = GitignoreBuilder::new(dir);
+        for pattern in &config.exclude {
+            if let Err(e) = builder.add_line(None, pattern) {
+                warn(&dir.join(CONFIG_FILE), e);
+            }
+        }
+        match builder.build() {
+            Ok(matcher) => excludes.push(matcher),
+            Err(e) => warn(&dir.join(CONFIG_FILE), e),
+        }
+    }
+    Rc::new(Scope {
+        suffixes: config.suffixes.or_else(|| parent.suffixes.clone()),
+        excludes,
+    })
+}
+
+impl Scopes {
+    /// Scopes leaving out the `overridden` keys of the working directory's
+    /// config.
+    pub fn new(overridden: &Overridden) -> Self {
+        let working = (overridden.suffixes || overridden.exclude)
+            .then(|| std::env::current_dir().and_then(fs::canonicalize).ok())
+            .flatten()
+            .map(|dir| (dir, overridden.clone()));
+        Scopes {
+            working,
+            ..Scopes::default()
+        }
+    }
+
+    /// Absolute path and scope of the directory `dir`. Directories are
+    /// resolved from their parent as walked, so only the top of a walk is
+    /// canonicalized.
+    fn scope(&self, dir: &Path) -> Walked {
+        if let Some(found) = self.walked.borrow().get(dir).cloned() {
+            return found;
+        }
+
+        let child = |parent: &Path, name: &OsStr| {
+            self.scope(parent)
+                .map(|parent| (parent.0.join(name), parent.1.clone()))
+        };
+        let parent = match (dir.parent(), dir.file_name()) {
+            (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => child(parent, name),
+            _ => dir
+                .canonicalize()
+                .ok()
+                .and_then(|dir| match (dir.parent(), dir.file_name()) {
+                    (Some(parent), Some(name)) => child(parent, name),
+                    _ => Some((dir, Rc::default())),
+                }),
+        };
+        let found = parent.map(|(dir, parent)| {
+            let overridden = self
+                .working
+                .as_ref()
+                .filter(|(working, _)| *working == dir)
+                .map(|(_, overridden)| overridden);
+            let scope = with_config(&dir, parent, overridden);
+            Rc::new((dir, scope))
+        });
+        self.walked
+            .borrow_mut()
+            .insert(dir.to_path_buf(), found.clone());
+        found
+    }
+
+    /// Absolute path and scope of the directory holding `path`.
+    fn scope_of(&self, path: &Path) -> Walked {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        if let Some((dir, found)) = &*self.last.borrow()
+            && dir.as_os_str() == parent.as_os_str()
+        {
+            return found.clone();
+        }
+        let found = self.scope(parent);
+        *self.last.borrow_mut() = Some((parent.to_path_buf(), found.clone()));
+        found
+    }
+
+    /// Whether `path` is excluded by the config of a directory above it.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(dir) = self.scope_of(path) else {
+            return false;
+        };
+        let (dir, scope) = &*dir;
+        if scope.excludes.is_empty() {
+            return false;
+        }
+        let Some(name) = path.file_name() else {
+            return false;
+        };
+        let path = dir.join(name);
+        scope
+            .excludes
+            .iter()
+            .any(|matcher| matcher.matched(&path, is_dir).is_ignore())
+    }
+
+    /// Whether the file at `path` has the suffix searched for in its
+    /// directory: one of the suffixes of the nearest config, or `suffix`.
+    pub fn matches_suffix(&self, path: &Path, suffix: &str) -> bool {
+        match self
+            .scope_of(path)
+            .as_deref()
+            .and_then(|(_, scope)| scope.suffixes.as_ref())
+        {
+            Some(suffixes) => suffixes.iter().any(|suffix| has_suffix(path, suffix)),
+            None => has_suffix(path, suffix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            DirConfig::parse("suffixes = [\".jpg\", \".png\"]\nexclude = [\"raw/\"]\n").unwrap(),
+            DirConfig {
+                suffixes: Some(vec![".jpg".to_string(), ".png".to_string()]),
+                exclude: vec!["raw/".to_string()],
+            }
+        );
+        assert_eq!(DirConfig::parse("").unwrap(), DirConfig::default());
+        assert_eq!(
+            DirConfig::parse("suffix = \".md\"").unwrap(),
+            DirConfig::default()
+        );
+        assert!(DirConfig::parse("exclude = \"raw/\"").is_err());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let root = TempDir::new().unwrap();
+        let photos = root.path().join("photos");
+        let raw = photos.join("raw");
+        fs::create_dir_all(&raw).unwrap();
+        fs::write(
+            photos.join(CONFIG_FILE),
+            "suffixes = [\".jpg\"]\nexclude = [\"raw/\", \"*.tmp.jpg\"]\n",
+        )
+        .unwrap();
+        let scopes = Scopes::default();
+
+        assert!(scopes.matches_suffix(&root.path().join("a.md"), ".md"));
+        assert!(!scopes.matches_suffix(&photos.join("a.md"), ".md"));
+        assert!(scopes.matches_suffix(&photos.join("b.JPG.jpg"), ".md"));
+        assert!(scopes.matches_suffix(&raw.join("c.jpg"), ".md"));
+
+        assert!(scopes.is_excluded(&raw, true));
+        assert!(scopes.is_excluded(&photos.join("d.tmp.jpg"), false));
+        assert!(!scopes.is_excluded(&photos.join("d.jpg"), false));
+        assert!(!scopes.is_excluded(&root.path().join("raw"), true));
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::Path;
+
+/// Lines of unchanged text shown around a change.
+const CONTEXT: usize = 3;
+
+/// Unified diff of writing `new` over `old` (`None` for a new file) at
+/// `path`, in a single hunk spanning the first to the last changed line.
+/// Empty when nothing changes.
+pub fn diff(path: &Path, old: Option<&str>, new: &str) -> String {
+    let old_lines: Vec<&str> = old.unwrap_or_default().lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix == old_lines.len() && prefix == new_lines.len() {
+        return String::new();
+    }
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let start = prefix.saturating_sub(CONTEXT);
+    let old_end = old_lines.len() - suffix;
+    let new_end = new_lines.len() - suffix;
+    let after = suffix.min(CONTEXT);
+    // Ranges start at the line before them when they're empty
+    let range = |len: usize| {
+        let first = if len == 0 { start } else { start + 1 };
+        format!("{},{}", first, len)
+    };
+
+    let mut out = format!(
+        "--- {}\n+++ {}\n@@ -{} +{} @@\n",
+        old.map_or("/dev/null".into(), |_| path.display().to_string()),
+        path.display(),
+        range(old_end + after - start),
+        range(new_end + after - start),
+    );
+    for line in &old_lines[start..prefix] {
+        out.push_str(&format!(" {}\n", line));
+    }
+    for line in &old_lines[prefix..old_end] {
+        out.push_str(&format!("-{}\n", line));
+    }
+    for line in &new_lines[prefix..new_end] {
+        out.push_str(&format!("+{}\n", line));
+    }
+    for line in &old_lines[old_end..old_end + after] {
+        out.push_str(&format!(" {}\n", line));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let path = Path::new("note.md");
+        let old = "# Note\n\n## Files\n\n- a\n\n## Later\n\n1\n2\n3\n4\n";
+        let new = "# Note\n\n## Files\n\n- a\n- b\n\n## Later\n\n1\n2\n3\n4\n";
+        assert_eq!(
+            diff(path, Some(old), new),
+            "--- note.md\n+++ note.md\n@@ -3,6 +3,7 @@\n \
+             ## Files\n \n - a\n+- b\n \n ## Later\n \n"
+        );
+        assert_eq!(diff(path, Some(old), old), "");
+        assert_eq!(
+            diff(path, None, "- a\n"),
+            "--- /dev/null\n+++ note.md\n@@ -0,0 +1,1 @@\n+- a\n"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use std::cell::RefCell;
+
+thread_local! {
+    static ERRORS: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Start collecting traversal and metadata errors (for `--strict`).
+pub fn collect() {
+    ERRORS.with(|errors| *errors.borrow_mut() = Some(Vec::new()));
+}
+
+/// Record an error that was skipped over. Ignored unless collecting.
+pub fn record(message: String) {
+    ERRORS.with(|errors| {
+        if let Some(errors) = errors.borrow_mut().as_mut() {
+            errors.push(message);
+        }
+    });
+}
+
+/// The errors recorded so far, clearing the list.
+pub fn take() -> Vec<String> {
+    ERRORS.with(|errors| {
+        errors
+            .borrow_mut()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_ignored_unless_collecting() {
+        record("lost".to_string());
+        assert!(take().is_empty());
+    }
+
+    #[test]
+    fn test_collect_and_take() {
+        collect();
+        record("one".to_string());
+        record("two".to_string());
+        assert_eq!(take(), vec!["one", "two"]);
+        assert!(take().is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::{i18n, status};
+
+/// Placeholders of a command template, longest first so `{//}` isn't read
+/// as `{/}` followed by `/}`.
+const PLACEHOLDERS: [&str; 5] = ["{//}", "{/.}", "{/}", "{.}", "{}"];
+
+/// Split a command template into words, shell style.
+fn parse_template(template: &str) -> Result<Vec<String>, String> {
+    match shlex::split(template) {
+        Some(words) if !words.is_empty() => Ok(words),
+        Some(_) => Err("empty command".to_string()),
+        None => Err(format!("can't parse command '{}'", template)),
+    }
+}
+
+/// Value of `placeholder` for `path`.
+fn placeholder_value(placeholder: &str, path: &Path) -> OsString {
+    let name = path.file_name().unwrap_or(path.as_os_str());
+    match placeholder {
+        "{/}" => name.to_os_string(),
+        "{//}" => path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(OsString::from("."), |parent| {
+                parent.as_os_str().to_os_string()
+            }),
+        "{.}" => path.with_extension("").into_os_string(),
+        "{/.}" => Path::new(name).file_stem().unwrap_or(name).to_os_string(),
+        _ => path.as_os_str().to_os_string(),
+    }
+}
+
+/// Replace the placeholders in `word` with the parts of `path`. Returns
+/// `None` when `word` has no placeholder.
+fn expand(word: &str, path: &Path) -> Option<OsString> {
+    let mut out = OsString::new();
+    let mut rest = word;
+    let mut found = false;
+
+    while let Some(start) = rest.find('{') {
+        out.push(&rest[..start]);
+        rest = &rest[start..];
+        match PLACEHOLDERS.iter().find(|p| rest.starts_with(*p)) {
+            Some(placeholder) => {
+                out.push(placeholder_value(placeholder, path));
+                rest = &rest[placeholder.len()..];
+                found = true;
+            }
+            None => {
+                out.push("{");
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push(rest);
+
+    found.then_some(out)
+}
+
+/// Arguments for running `words` on `path`. The path is appended when no
+/// word refers to it.
+fn file_args(words: &[String], path: &Path) -> Vec<OsString> {
+    let mut found = false;
+    let mut args: Vec<OsString> = words
+        .iter()
+        .map(|word| match expand(word, path) {
+            Some(expanded) => {
+                found = true;
+                expanded
+            }
+            None => OsString::from(word),
+        })
+        .collect();
+
+    if !found {
+        args.push(path.as_os_str().to_os_string());
+    }
+    args
+}
+
+/// Arguments for running `words` once on all `paths`. A `{}` word is
+/// replaced by all the paths; they're appended when there's none.
+fn batch_args(words: &[String], paths: &[PathBuf]) -> Vec<OsString> {
+    let mut found = false;
+    let mut args = Vec::new();
+    for word in words {
+        if word == "{}" {
+            found = true;
+            args.extend(paths.iter().map(|path| path.as_os_str().to_os_string()));
+        } else {
+            args.push(OsString::from(word));
+        }
+    }
+
+    if !found {
+        args.extend(paths.iter().map(|path| path.as_os_str().to_os_string()));
+    }
+    args
+}
+
+/// Run a command, returning whether it succeeded. Failures are reported on
+/// stderr. With `capture`, the command's output is buffered and written in
+/// one piece once it's done, so concurrent commands don't interleave.
+fn run(args: &[OsString], capture: bool) -> bool {
+    let program = &args[0];
+    let mut command = Command::new(program);
+    command.args(&args[1..]);
+
+    let status = if capture {
+        command.output().map(|output| {
+            let _ = io::stdout().lock().write_all(&output.stdout);
+            let _ = io::stderr().lock().write_all(&output.stderr);
+            output.status
+        })
+    } else {
+        command.status()
+    };
+
+    match status {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            let message = i18n::trf("'{}' failed: {}", &[&program.to_string_lossy(), &status]);
+            status::say(i18n::trf("error: {}", &[&message]));
+            false
+        }
+        Err(e) => {
+            let message = i18n::trf("can't run '{}': {}", &[&program.to_string_lossy(), &e]);
+            status::say(i18n::trf("error: {}", &[&message]));
+            false
+        }
+    }
+}
+
+/// Run the `template` command once per file, up to `jobs` at a time. All
+/// files are processed even when some commands fail.
+pub fn exec(template: &str, files: &[PathBuf], jobs: usize) -> Result<(), String> {
+    let words = parse_template(template)?;
+    let jobs = jobs.clamp(1, files.len().max(1));
+    let capture = jobs > 1;
+    let next = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let lang = i18n::lang();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                i18n::set_lang(lang);
+                while let Some(file) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    if !run(&file_args(&words, file), capture) {
+                        failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    match failed.into_inner() {
+        0 => Ok(()),
+        n => Err(format!("{} of {} commands failed", n, files.len())),
+    }
+}
+
+/// Run the `template` command once with all the files.
+pub fn exec_batch(template: &str, files: &[PathBuf]) -> Result<(), String> {
+    let words = parse_template(template)?;
+    if files.is_empty() || run(&batch_args(&words, files), false) {
+        Ok(())
+    } else {
+        Err("command failed".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(template: &str) -> Vec<String> {
+        parse_template(template).unwrap()
+    }
+
+    #[test]
+    fn test_parse_template() {
+        assert_eq!(
+            words("grep -H 'TODO: ' {}"),
+            vec!["grep", "-H", "TODO: ", "{}"]
+        );
+        assert!(parse_template("  ").is_err());
+        assert!(parse_template("echo 'unterminated").is_err());
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let path = Path::new("notes/2025/day.md");
+        let expand = |word| expand(word, path).unwrap();
+        assert_eq!(expand("{}"), "notes/2025/day.md");
+        assert_eq!(expand("{/}"), "day.md");
+        assert_eq!(expand("{//}"), "notes/2025");
+        assert_eq!(expand("{.}"), "notes/2025/day");
+        assert_eq!(expand("{/.}"), "day");
+        assert_eq!(expand("out/{/.}.html"), "out/day.html");
+        assert_eq!(expand("{x}{}"), "{x}notes/2025/day.md");
+    }
+
+    #[test]
+    fn test_expand_without_placeholder() {
+        assert_eq!(expand("--flag={x}", Path::new("a.md")), None);
+    }
+
+    #[test]
+    fn test_parent_of_bare_name() {
+        assert_eq!(expand("{//}", Path::new("a.md")).unwrap(), ".");
+    }
+
+    #[test]
+    fn test_file_args_appends_path() {
+        let path = Path::new("a.md");
+        assert_eq!(file_args(&words("wc -l"), path), vec!["wc", "-l", "a.md"]);
+        assert_eq!(
+            file_args(&words("cp {} {/.}.bak"), path),
+            vec!["cp", "a.md", "a.bak"]
+        );
+    }
+
+    #[test]
+    fn test_batch_args() {
+        let paths = vec![PathBuf::from("a.md"), PathBuf::from("b.md")];
+        assert_eq!(
+            batch_args(&words("wc -l"), &paths),
+            vec!["wc", "-l", "a.md", "b.md"]
+        );
+        assert_eq!(
+            batch_args(&words("tar cf out.tar {} --verbose"), &paths),
+            vec!["tar", "cf", "out.tar", "a.md", "b.md", "--verbose"]
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{DateRange, i18n, url_path};
+
+/// Output of `git args` run in `dir`.
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("can't run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed in '{}': {}",
+            args.join(" "),
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The last commit that touched a file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LastCommit {
+    pub hash: String,
+    /// Abbreviated hash, as git shows it.
+    pub short: String,
+    pub author: String,
+    /// Author date, in RFC 3339.
+    pub date: String,
+}
+
+/// The last commit touching `path`, or `None` when it's not in a git checkout
+/// or not committed.
+pub fn last_commit(path: &Path) -> Option<LastCommit> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let name = path.file_name()?.to_str()?;
+    let log = git(
+        dir.unwrap_or(Path::new(".")),
+        &["log", "-1", "--format=%H%x00%h%x00%an%x00%aI", "--", name],
+    )
+    .ok()?;
+    let mut fields = log.split('\0').map(str::to_string);
+    Some(LastCommit {
+        hash: fields.next().filter(|hash| !hash.is_empty())?,
+        short: fields.next()?,
+        author: fields.next()?,
+        date: fields.next()?,
+    })
+}
+
+/// A commit of the checkout, for changelogs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commit {
+    /// Abbreviated hash, as git shows it.
+    pub short: String,
+    pub subject: String,
+    /// Author time.
+    pub time: DateTime<Local>,
+    /// The files it touched.
+    pub files: Vec<PathBuf>,
+}
+
+impl Commit {
+    /// The commit as a list entry: its subject and abbreviated hash.
+    pub fn entry(&self) -> String {
+        format!("- {} (`{}`)", self.subject, self.short)
+    }
+
+    /// The commit as a line under a file's entry: its abbreviated hash and
+    /// subject.
+    pub fn provenance(&self) -> String {
+        format!("  - {} — {}", self.short, self.subject)
+    }
+
+    /// Whether the commit touched the file at `path`.
+    pub fn touches(&self, path: &Path) -> bool {
+        fs::canonicalize(path).is_ok_and(|path| self.files.contains(&path))
+    }
+}
+
+/// The commits of the git checkout `dir` is in authored on the days of
+/// `range`, oldest first.
+pub fn commits(dir: &Path, range: DateRange) -> Result<Vec<Commit>, String> {
+    let since = format!("--since={} 00:00:00", range.start);
+    let until = format!("--until={} 23:59:59", range.end);
+    let top = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?);
+    let top = fs::canonicalize(&top).unwrap_or(top);
+    let log = git(
+        dir,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--reverse",
+            "--name-only",
+            "--format=%x01%h%x00%aI%x00%s",
+            &since,
+            &until,
+        ],
+    )?;
+    let mut commits: Vec<Commit> = log
+        .split('\x01')
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut fields = lines.next()?.splitn(3, '\0');
+            let short = fields.next()?.to_string();
+            let time = DateTime::parse_from_rfc3339(fields.next()?).ok()?;
+            Some(Commit {
+                short,
+                subject: fields.next()?.to_string(),
+                time: time.with_timezone(&Local),
+                files: lines
+                    .filter(|line| !line.is_empty())
+                    .map(|line| top.join(line))
+                    .collect(),
+            })
+        })
+        // --since and --until go by commit time
+        .filter(|commit| range.contains(commit.time.date_naive()))
+        .collect();
+    commits.sort_by_key(|commit| commit.time);
+    Ok(commits)
+}
+
+/// Path of the repository in a remote URL: `org/repo` in
+/// `https://github.com/org/repo.git` or `git@github.com:org/repo.git`.
+fn repo_path(remote: &str) -> Option<String> {
+    let rest = match remote.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?.1,
+        None => remote.split_once(':')?.1,
+    };
+    let path = rest.trim_matches('/').trim_end_matches(".git");
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+/// Links to the files of a git checkout as of its current commit, on the
+/// forge hosting the repository.
+#[derive(Clone, Debug)]
+pub struct Permalinks {
+    template: String,
+    repo: String,
+    commit: String,
+    top: PathBuf,
+}
+
+impl Permalinks {
+    /// Permalinks for the checkout `root` is in, made from `template`, where
+    /// `{repo}` is replaced with the repository's path on the forge (taken
+    /// from the `origin` remote), `{ref}` with the commit and `{path}` with
+    /// the file's path in the repository.
+    pub fn discover(root: &Path, template: &str) -> Result<Self, String> {
+        let top = git(root, &["rev-parse", "--show-toplevel"])?;
+        let commit = git(root, &["rev-parse", "HEAD"])?;
+        let top =
+            fs::canonicalize(&top).map_err(|e| i18n::trf("can't read '{}': {}", &[&top, &e]))?;
+
+        let mut repo = String::new();
+        if template.contains("{repo}") {
+            let remote = git(root, &["remote", "get-url", "origin"])?;
+            repo = repo_path(&remote)
+                .ok_or_else(|| format!("can't tell the repository of remote '{}'", remote))?;
+        }
+
+        Ok(Permalinks {
+            template: template.to_string(),
+            repo,
+            commit,
+            top,
+        })
+    }
+
+    /// Permalinks to the GitHub-style repository at `repo_url`.
+    pub fn github(root: &Path, repo_url: &str) -> Result<Self, String> {
+        let template = format!("{}/blob/{{ref}}/{{path}}", repo_url.trim_end_matches('/'));
+        Permalinks::discover(root, &template)
+    }
+
+    /// Permalink of `path`, or `None` when it's outside the checkout.
+    pub fn url(&self, path: &Path) -> Option<String> {
+        let path = fs::canonicalize(path).ok()?;
+        if !path.starts_with(&self.top) {
+            return None;
+        }
+        let path = url_path(&self.top, &path);
+        Some(
+            self.template
+                .replace("{repo}", &self.repo)
+                .replace("{ref}", &self.commit)
+                .replace("{path}", &path),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_repo_path() {
+        assert_eq!(
+            repo_path("https://github.com/org/repo.git").as_deref(),
+            Some("org/repo")
+        );
+        assert_eq!(
+            repo_path("git@github.com:org/repo.git").as_deref(),
+            Some("org/repo")
+        );
+        assert_eq!(
+            repo_path("ssh://git@gitlab.example.com:2222/group/sub/project").as_deref(),
+            Some("group/sub/project")
+        );
+        assert_eq!(repo_path("https://github.com/"), None);
+        assert_eq!(repo_path("/srv/git/repo"), None);
+    }
+
+    #[test]
+    fn test_commits() {
+        let repo = TempDir::new().unwrap();
+        fs::create_dir(repo.path().join("docs")).unwrap();
+        let note = repo.path().join("docs").join("ü note.md");
+        fs::write(&note, "").unwrap();
+        fs::write(repo.path().join("b.md"), "").unwrap();
+        run_git(repo.path(), &["init", "-q"]);
+        run_git(repo.path(), &["add", "docs"]);
+        run_git(repo.path(), &["commit", "-q", "-m", "Add the note"]);
+        run_git(repo.path(), &["add", "b.md"]);
+        run_git(repo.path(), &["commit", "-q", "-m", "Add b"]);
+
+        let today = Local::now().date_naive();
+        let commits = commits(&repo.path().join("docs"), today.into()).unwrap();
+        let subjects: Vec<&str> = commits.iter().map(|c| c.subject.as_str()).collect();
+        assert_eq!(subjects, ["Add the note", "Add b"]);
+        assert!(commits[0].touches(&note));
+        assert!(!commits[1].touches(&note));
+        assert!(commits[1].touches(&repo.path().join("b.md")));
+        assert_eq!(
+            commits[0].provenance(),
+            format!("  - {} — Add the note", commits[0].short)
+        );
+    }
+
+    #[test]
+    fn test_permalinks() {
+        let repo = TempDir::new().unwrap();
+        fs::create_dir(repo.path().join("docs")).unwrap();
+        let note = repo.path().join("docs").join("a note.md");
+        fs::write(&note, "").unwrap();
+        run_git(repo.path(), &["init", "-q"]);
+        run_git(repo.path(), &["add", "."]);
+        run_git(repo.path(), &["commit", "-q", "-m", "init"]);
+        let commit = git(repo.path(), &["rev-parse", "HEAD"]).unwrap();
+
+        let links =
+            Permalinks::github(&repo.path().join("docs"), "https://github.com/org/repo/").unwrap();
+        assert_eq!(
+            links.url(&note),
+            Some(format!(
+                "https://github.com/org/repo/blob/{}/docs/a%20note.md",
+                commit
+            ))
+        );
+
+        // No origin remote to take {repo} from
+        let template = "https://gitlab.example.com/{repo}/-/blob/{ref}/{path}";
+        assert!(Permalinks::discover(repo.path(), template).is_err());
+        run_git(
+            repo.path(),
+            &[
+                "remote",
+                "add",
+                "origin",
+                "git@gitlab.example.com:group/sub/project.git",
+            ],
+        );
+        let links = Permalinks::discover(repo.path(), template).unwrap();
+        assert_eq!(
+            links.url(&note),
+            Some(format!(
+                "https://gitlab.example.com/group/sub/project/-/blob/{}/docs/a%20note.md",
+                commit
+            ))
+        );
+
+        let last = last_commit(&note).unwrap();
+        assert_eq!(last.hash, commit);
+        assert_eq!(last.author, "Test");
+        assert!(commit.starts_with(&last.short));
+        fs::write(repo.path().join("new.md"), "").unwrap();
+        assert_eq!(last_commit(&repo.path().join("new.md")), None);
+
+        let today = Local::now().date_naive();
+        let found = commits(&repo.path().join("docs"), today.into()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].subject, "init");
+        assert!(commit.starts_with(&found[0].short));
+        let yesterday = today.pred_opt().unwrap();
+        assert_eq!(commits(repo.path(), yesterday.into()).unwrap(), []);
+
+        let outside = TempDir::new().unwrap();
+        assert_eq!(links.url(outside.path()), None);
+        assert_eq!(last_commit(&outside.path().join("a.md")), None);
+        assert!(Permalinks::github(outside.path(), "https://github.com/org/repo").is_err());
+    }
+}
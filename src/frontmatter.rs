@@ -0,0 +1,317 @@
+use chrono::{Local, NaiveDate, SecondsFormat};
+use std::fs;
+use std::path::Path;
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::config::{Config, Source};
+use crate::{DateRange, errors};
+
+/// The YAML block between `---` lines at the very start of `content`, and
+/// the rest of the document.
+fn split(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn block(content: &str) -> Option<&str> {
+    split(content).map(|(block, _)| block)
+}
+
+/// `content` without its frontmatter.
+pub fn body(content: &str) -> &str {
+    split(content).map_or(content, |(_, body)| body)
+}
+
+/// The frontmatter of a markdown document, when it has one and it's a YAML
+/// mapping.
+pub fn parse(content: &str) -> Option<Yaml> {
+    let docs = YamlLoader::load_from_str(block(content)?).ok()?;
+    docs.into_iter()
+        .next()
+        .filter(|doc| matches!(doc, Yaml::Hash(_)))
+}
+
+/// Text of a scalar value.
+pub fn scalar_text(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Op {
+    Exists,
+    Equals(String),
+    Contains(String),
+}
+
+/// A frontmatter condition: `KEY`, `KEY = VALUE` or `KEY contains VALUE`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matcher {
+    key: String,
+    op: Op,
+}
+
+/// Parse a `--frontmatter` condition.
+pub fn parse_matcher(text: &str) -> Result<Matcher, String> {
+    let text = text.trim();
+    let matcher = |key: &str, op| -> Result<Matcher, String> {
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(format!(
+                "invalid condition '{}' (expected KEY, KEY = VALUE or KEY contains VALUE)",
+                text
+            ));
+        }
+        Ok(Matcher {
+            key: key.to_string(),
+            op,
+        })
+    };
+
+    if let Some((key, value)) = text.split_once(" contains ") {
+        matcher(key, Op::Contains(value.trim().to_string()))
+    } else if let Some((key, value)) = text.split_once('=') {
+        matcher(key, Op::Equals(value.trim().to_string()))
+    } else {
+        matcher(text, Op::Exists)
+    }
+}
+
+impl Matcher {
+    /// Whether the `frontmatter` mapping satisfies the condition. `contains`
+    /// looks for an equal item in lists and for a substring in text.
+    pub fn matches(&self, frontmatter: &Yaml) -> bool {
+        let value = &frontmatter[self.key.as_str()];
+        match &self.op {
+            Op::Exists => !matches!(value, Yaml::BadValue),
+            Op::Equals(expected) => scalar_text(value).is_some_and(|text| text == *expected),
+            Op::Contains(expected) => match value {
+                Yaml::Array(items) => items
+                    .iter()
+                    .any(|item| scalar_text(item).is_some_and(|text| text == *expected)),
+                _ => scalar_text(value).is_some_and(|text| text.contains(expected.as_str())),
+            },
+        }
+    }
+}
+
+/// Whether the file at `path` has frontmatter satisfying all of `matchers`.
+pub fn matches_file(path: &Path, matchers: &[Matcher]) -> bool {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            errors::record(format!("{}: {}", path.display(), e));
+            return false;
+        }
+    };
+
+    parse(&content).is_some_and(|frontmatter| matchers.iter().all(|m| m.matches(&frontmatter)))
+}
+
+/// `value` as YAML.
+fn yaml(value: &toml::Value) -> Yaml {
+    match value {
+        toml::Value::String(text) => Yaml::String(text.clone()),
+        toml::Value::Integer(i) => Yaml::Integer(*i),
+        toml::Value::Float(f) => Yaml::Real(f.to_string()),
+        toml::Value::Boolean(b) => Yaml::Boolean(*b),
+        toml::Value::Datetime(time) => Yaml::String(time.to_string()),
+        toml::Value::Array(items) => Yaml::Array(items.iter().map(yaml).collect()),
+        toml::Value::Table(table) => Yaml::Hash(
+            table
+                .iter()
+                .map(|(key, value)| (Yaml::String(key.clone()), yaml(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Frontmatter of the documents mdfiles writes, for `--frontmatter-out`:
+/// the dates they cover, when they were generated, how many files they list
+/// and the settings that picked them.
+#[derive(Clone, Debug)]
+pub struct Generated {
+    range: DateRange,
+    query: Hash,
+}
+
+impl Generated {
+    /// Frontmatter for documents covering `range`, with the settings in
+    /// `config` that aren't defaults as the query.
+    pub fn new(range: DateRange, config: &Config) -> Self {
+        let query = config
+            .settings
+            .iter()
+            .filter(|setting| {
+                !matches!(setting.source, Source::Default) && setting.key != "frontmatter-out"
+            })
+            .map(|setting| (Yaml::String(setting.key.clone()), yaml(&setting.value)))
+            .collect();
+        Generated { range, query }
+    }
+
+    /// The same frontmatter for a document covering just `date`.
+    pub fn on(&self, date: NaiveDate) -> Generated {
+        self.over(date.into())
+    }
+
+    /// The same frontmatter for a document covering `range`.
+    pub fn over(&self, range: DateRange) -> Generated {
+        Generated {
+            range,
+            query: self.query.clone(),
+        }
+    }
+
+    /// The frontmatter block of a document listing `count` files.
+    pub fn render(&self, count: usize) -> String {
+        let mut fields = Hash::new();
+        let mut field = |key: &str, value: Yaml| fields.insert(Yaml::String(key.into()), value);
+        let day = |date: NaiveDate| Yaml::String(date.format("%Y-%m-%d").to_string());
+        if self.range.start == self.range.end {
+            field("date", day(self.range.end));
+        } else {
+            field("from", day(self.range.start));
+            field("to", day(self.range.end));
+        }
+        field(
+            "generated",
+            Yaml::String(Local::now().to_rfc3339_opts(SecondsFormat::Secs, true)),
+        );
+        field("files", Yaml::Integer(count as i64));
+        if !self.query.is_empty() {
+            field("query", Yaml::Hash(self.query.clone()));
+        }
+
+        let mut out = String::new();
+        // Writing to a String can't fail
+        let _ = YamlEmitter::new(&mut out).dump(&Yaml::Hash(fields));
+        out + "\n---\n\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: Kickoff\nstatus: active\nstatus_note: waiting on review\ntags: [project-x, meeting]\npriority: 2\n---\n# Kickoff\n";
+
+    fn matches(condition: &str) -> bool {
+        let frontmatter = parse(NOTE).unwrap();
+        parse_matcher(condition).unwrap().matches(&frontmatter)
+    }
+
+    #[test]
+    fn test_block() {
+        assert_eq!(block("---\na: 1\n---\nbody"), Some("a: 1\n"));
+        assert_eq!(block("---\r\na: 1\r\n...\r\n"), Some("a: 1\r\n"));
+        assert_eq!(block("# Title\n---\na: 1\n---\n"), None);
+        assert_eq!(block("---\na: 1\n"), None);
+    }
+
+    #[test]
+    fn test_body() {
+        assert_eq!(body("---\na: 1\n---\n# Title\n"), "# Title\n");
+        assert_eq!(body("# Title\n"), "# Title\n");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(parse(NOTE).is_some());
+        assert!(parse("---\n- a\n- b\n---\n").is_none());
+        assert!(parse("---\na: [\n---\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_matcher() {
+        assert_eq!(
+            parse_matcher("tags contains project-x").unwrap(),
+            Matcher {
+                key: "tags".to_string(),
+                op: Op::Contains("project-x".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_matcher("status = done").unwrap(),
+            Matcher {
+                key: "status".to_string(),
+                op: Op::Equals("done".to_string()),
+            }
+        );
+        assert!(parse_matcher("").is_err());
+        assert!(parse_matcher("two words").is_err());
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("tags contains project-x"));
+        assert!(!matches("tags contains project"));
+        assert!(matches("status_note contains review"));
+        assert!(matches("status = active"));
+        assert!(!matches("status = done"));
+        assert!(matches("priority = 2"));
+        assert!(matches("title"));
+        assert!(!matches("due"));
+    }
+
+    #[test]
+    fn test_generated() {
+        let date = |day| chrono::NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let config = Config {
+            settings: vec![
+                crate::config::Setting {
+                    key: "suffix".into(),
+                    value: toml::Value::String(".md".into()),
+                    source: Source::CommandLine,
+                },
+                crate::config::Setting {
+                    key: "tag".into(),
+                    value: toml::Value::Array(vec![toml::Value::String("work".into())]),
+                    source: Source::Env("MDFILES_TAG".into()),
+                },
+                crate::config::Setting {
+                    key: "jobs".into(),
+                    value: toml::Value::Integer(4),
+                    source: Source::Default,
+                },
+            ],
+        };
+        let range = DateRange {
+            start: date(10),
+            end: date(14),
+        };
+        let text = Generated::new(range, &config).render(3);
+        let doc = parse(&text).unwrap();
+        assert_eq!(doc["from"].as_str(), Some("2025-03-10"));
+        assert_eq!(doc["to"].as_str(), Some("2025-03-14"));
+        assert!(doc["generated"].as_str().is_some());
+        assert_eq!(doc["files"].as_i64(), Some(3));
+        assert_eq!(doc["query"]["suffix"].as_str(), Some(".md"));
+        assert_eq!(doc["query"]["tag"][0].as_str(), Some("work"));
+        assert!(doc["query"]["jobs"].is_badvalue());
+        assert!(body(&text).trim().is_empty());
+
+        let range = DateRange {
+            start: date(14),
+            end: date(14),
+        };
+        let doc = parse(&Generated::new(range, &Config::default()).render(0)).unwrap();
+        assert_eq!(doc["date"].as_str(), Some("2025-03-14"));
+        assert!(doc["query"].is_badvalue());
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::{Datelike, Locale, NaiveDate};
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::{LinkStyle, TimeField, date_heading, file_time, local_date};
+
+/// How the listed files are grouped under headings.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum GroupBy {
+    Day,
+    Week,
+}
+
+/// ISO week of `date`, as in `2025-W11`.
+pub fn week_heading(date: NaiveDate) -> String {
+    let week = date.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// `files` by the local date of their `field` timestamp, keeping their order
+/// within a day.
+pub fn by_date(files: &[PathBuf], field: TimeField) -> BTreeMap<NaiveDate, Vec<&PathBuf>> {
+    let mut by_date: BTreeMap<NaiveDate, Vec<&PathBuf>> = BTreeMap::new();
+    for file in files {
+        if let Some(time) = file_time(file, field) {
+            by_date.entry(local_date(time)).or_default().push(file);
+        }
+    }
+    by_date
+}
+
+/// `files` under a heading per day, or per ISO week with a sub-heading per
+/// day, in date order. Files keep their order within a day.
+pub fn render(
+    files: &[PathBuf],
+    group: GroupBy,
+    field: TimeField,
+    locale: Option<Locale>,
+    style: &LinkStyle,
+) -> String {
+    render_at(files, group, field, locale, style, 2)
+}
+
+/// `render` with the top headings at `level`, for listings inside a larger
+/// document.
+pub fn render_at(
+    files: &[PathBuf],
+    group: GroupBy,
+    field: TimeField,
+    locale: Option<Locale>,
+    style: &LinkStyle,
+    level: usize,
+) -> String {
+    let top = "#".repeat(level);
+    let sub = "#".repeat(level + 1);
+    let mut out = String::new();
+    let mut week = None;
+    for (date, files) in by_date(files, field) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let day_level = match group {
+            GroupBy::Day => &top,
+            GroupBy::Week => {
+                let heading = week_heading(date);
+                if week.as_ref() != Some(&heading) {
+                    out.push_str(&format!("{} {}\n\n", top, heading));
+                    week = Some(heading);
+                }
+                &sub
+            }
+        };
+        out.push_str(&format!("{} {}\n\n", day_level, date_heading(date, locale)));
+        for file in files {
+            out.push_str(&style.format(file));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+    }
+
+    /// A file modified at noon on `date`.
+    fn file_on(dir: &TempDir, name: &str, date: NaiveDate) -> PathBuf {
+        let path = dir.path().join(name);
+        let noon = date
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap();
+        File::create(&path)
+            .unwrap()
+            .set_modified(SystemTime::from(noon))
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_week_heading() {
+        assert_eq!(week_heading(date(3, 14)), "2025-W11");
+        // ISO weeks belong to the year of their Thursday
+        assert_eq!(week_heading(date(12, 29)), "2026-W01");
+    }
+
+    #[test]
+    fn test_render() {
+        let dir = TempDir::new().unwrap();
+        let files = vec![
+            file_on(&dir, "a.md", date(3, 10)),
+            file_on(&dir, "b.md", date(3, 14)),
+            file_on(&dir, "c.md", date(3, 10)),
+            file_on(&dir, "d.md", date(3, 17)),
+        ];
+        let style = LinkStyle::default();
+        let target = |name: &str| style.target(&dir.path().join(name));
+
+        assert_eq!(
+            render(&files, GroupBy::Week, TimeField::Modified, None, &style),
+            format!(
+                "## 2025-W11\n\n### 2025-03-10\n\n- [a.md]({})\n- [c.md]({})\n\n\
+                 ### 2025-03-14\n\n- [b.md]({})\n\n\
+                 ## 2025-W12\n\n### 2025-03-17\n\n- [d.md]({})\n",
+                target("a.md"),
+                target("c.md"),
+                target("b.md"),
+                target("d.md")
+            )
+        );
+
+        let by_day = render(&files[..2], GroupBy::Day, TimeField::Modified, None, &style);
+        assert_eq!(
+            by_day,
+            format!(
+                "## 2025-03-10\n\n- [a.md]({})\n\n## 2025-03-14\n\n- [b.md]({})\n",
+                target("a.md"),
+                target("b.md")
+            )
+        );
+    }
+}
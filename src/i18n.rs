@@ -0,0 +1,460 @@
+use clap::ValueEnum;
+use std::cell::Cell;
+use std::fmt::Display;
+
+/// Language of headings, summaries and status messages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Lang {
+    #[default]
+    En,
+    De,
+    He,
+}
+
+thread_local! {
+    static LANG: Cell<Lang> = const { Cell::new(Lang::En) };
+}
+
+/// English text of each message, with its German and Hebrew translations.
+/// `{}` placeholders are filled in order by `trf`.
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("Files for {}", "Dateien vom {}", "קבצים מתאריך {}"),
+    (
+        "Files from {} to {}",
+        "Dateien vom {} bis {}",
+        "קבצים מ־{} עד {}",
+    ),
+    ("Added", "Hinzugefügt", "נוספו"),
+    ("Removed", "Entfernt", "הוסרו"),
+    ("Disappeared", "Verschwunden", "נעלמו"),
+    ("Changed Size", "Größe geändert", "גודל השתנה"),
+    ("Changed", "Geändert", "שונו"),
+    ("Missing", "Fehlend", "חסרים"),
+    ("New", "Neu", "חדשים"),
+    ("Links", "Links", "קישורים"),
+    ("Backlinks", "Rückverweise", "קישורים נכנסים"),
+    ("Extensions", "Endungen", "סיומות"),
+    ("Extension", "Endung", "סיומת"),
+    ("Directories", "Verzeichnisse", "תיקיות"),
+    ("Directory", "Verzeichnis", "תיקייה"),
+    ("Days", "Tage", "ימים"),
+    ("Date", "Datum", "תאריך"),
+    ("Files", "Dateien", "קבצים"),
+    ("Week of {}", "Woche vom {}", "שבוע מ־{}"),
+    ("Month of {}", "Monat {}", "חודש {}"),
+    ("Weeks", "Wochen", "שבועות"),
+    ("Busiest Days", "Aktivste Tage", "הימים העמוסים ביותר"),
+    ("Changes for {}", "Änderungen vom {}", "שינויים מתאריך {}"),
+    (
+        "Changes from {} to {}",
+        "Änderungen vom {} bis {}",
+        "שינויים מ־{} עד {}",
+    ),
+    ("(none)", "(keine)", "(אין)"),
+    ("just now", "gerade eben", "כרגע"),
+    ("1 minute ago", "vor 1 Minute", "לפני דקה"),
+    ("{} minutes ago", "vor {} Minuten", "לפני {} דקות"),
+    ("1 hour ago", "vor 1 Stunde", "לפני שעה"),
+    ("{} hours ago", "vor {} Stunden", "לפני {} שעות"),
+    ("1 day ago", "vor 1 Tag", "לפני יום"),
+    ("{} days ago", "vor {} Tagen", "לפני {} ימים"),
+    ("1 week ago", "vor 1 Woche", "לפני שבוע"),
+    ("{} weeks ago", "vor {} Wochen", "לפני {} שבועות"),
+    ("1 month ago", "vor 1 Monat", "לפני חודש"),
+    ("{} months ago", "vor {} Monaten", "לפני {} חודשים"),
+    ("1 year ago", "vor 1 Jahr", "לפני שנה"),
+    ("{} years ago", "vor {} Jahren", "לפני {} שנים"),
+    ("error: {}", "Fehler: {}", "שגיאה: {}"),
+    ("warning: {}", "Warnung: {}", "אזהרה: {}"),
+    ("updated {}", "aktualisiert: {}", "עודכן {}"),
+    ("wrote {}", "geschrieben: {}", "נכתב {}"),
+    (
+        "no matching files, nothing written",
+        "keine passenden Dateien, nichts geschrieben",
+        "אין קבצים תואמים, לא נכתב דבר",
+    ),
+    ("mailed to {}", "gemailt an {}", "נשלח בדואר אל {}"),
+    (
+        "updating {} every {}s (pid {})",
+        "aktualisiert {} alle {}s (PID {})",
+        "מעדכן את {} כל {} שניות (pid {})",
+    ),
+    ("stopped", "beendet", "נעצר"),
+    (
+        "started the daemon (pid {}), logging to {}",
+        "Dienst gestartet (PID {}), Protokoll in {}",
+        "השירות הופעל (pid {}), יומן ב־{}",
+    ),
+    (
+        "stopped the daemon (pid {})",
+        "Dienst beendet (PID {})",
+        "השירות נעצר (pid {})",
+    ),
+    (
+        "listening on http://{}:{}",
+        "lauscht auf http://{}:{}",
+        "מאזין ב־http://{}:{}",
+    ),
+    (
+        "can't send response: {}",
+        "kann Antwort nicht senden: {}",
+        "לא ניתן לשלוח תשובה: {}",
+    ),
+    (
+        "'{}' failed: {}",
+        "'{}' fehlgeschlagen: {}",
+        "'{}' נכשל: {}",
+    ),
+    (
+        "can't run '{}': {}",
+        "kann '{}' nicht ausführen: {}",
+        "לא ניתן להריץ את '{}': {}",
+    ),
+    (
+        "would archive {} as {}",
+        "würde {} als {} archivieren",
+        "{} היה מאורכב בשם {}",
+    ),
+    (
+        "would copy {} -> {}",
+        "würde {} -> {} kopieren",
+        "{} היה מועתק -> {}",
+    ),
+    (
+        "would move {} -> {}",
+        "würde {} -> {} verschieben",
+        "{} היה מועבר -> {}",
+    ),
+    ("would remove {}", "würde {} entfernen", "{} היה נמחק"),
+    (
+        "can't copy '{}' to '{}': {}",
+        "kann '{}' nicht nach '{}' kopieren: {}",
+        "לא ניתן להעתיק את '{}' אל '{}': {}",
+    ),
+    (
+        "can't move '{}' to '{}': {}",
+        "kann '{}' nicht nach '{}' verschieben: {}",
+        "לא ניתן להעביר את '{}' אל '{}': {}",
+    ),
+    (
+        "can't read '{}': {}",
+        "kann '{}' nicht lesen: {}",
+        "לא ניתן לקרוא את '{}': {}",
+    ),
+    (
+        "can't write '{}': {}",
+        "kann '{}' nicht schreiben: {}",
+        "לא ניתן לכתוב את '{}': {}",
+    ),
+    (
+        "can't create '{}': {}",
+        "kann '{}' nicht anlegen: {}",
+        "לא ניתן ליצור את '{}': {}",
+    ),
+    (
+        "can't parse '{}': {}",
+        "kann '{}' nicht verarbeiten: {}",
+        "לא ניתן לפענח את '{}': {}",
+    ),
+    (
+        "root directory '{}' does not exist",
+        "Wurzelverzeichnis '{}' existiert nicht",
+        "תיקיית השורש '{}' אינה קיימת",
+    ),
+    (
+        "Invalid date format (should be YYYY-MM-DD)",
+        "Ungültiges Datumsformat (erwartet JJJJ-MM-TT)",
+        "תבנית תאריך לא תקינה (צריכה להיות YYYY-MM-DD)",
+    ),
+    (
+        "would mail {} to {}",
+        "würde {} an {} mailen",
+        "{} היו נשלחים אל {}",
+    ),
+    (
+        "daemon: running (pid {})",
+        "Dienst: läuft (PID {})",
+        "שירות: פועל (pid {})",
+    ),
+    (
+        "daemon: not running",
+        "Dienst: läuft nicht",
+        "שירות: לא פועל",
+    ),
+    ("index: {}", "Index: {}", "אינדקס: {}"),
+    ("changed: {}", "geändert: {}", "שונה: {}"),
+    (
+        "rescanned {} and {}, {} in {}",
+        "{} und {} neu durchsucht, {} in {}",
+        "נסרקו מחדש {} ו־{}, {} ב־{}",
+    ),
+    (
+        "{}, {} scanned, {}",
+        "{}, {} durchsucht, {}",
+        "נסרקו {} ו־{}, {}",
+    ),
+];
+
+/// Singular and plural forms of messages with a count, each translated like
+/// the `MESSAGES`. All three languages use the singular for one only.
+const PLURALS: &[[(&str, &str, &str); 2]] = &[
+    [
+        ("{} file", "{} Datei", "{} קובץ"),
+        ("{} files", "{} Dateien", "{} קבצים"),
+    ],
+    [
+        ("{} directory", "{} Verzeichnis", "{} תיקייה"),
+        ("{} directories", "{} Verzeichnisse", "{} תיקיות"),
+    ],
+    [
+        ("{} match", "{} Treffer", "{} התאמה"),
+        ("{} matches", "{} Treffer", "{} התאמות"),
+    ],
+    [
+        ("{} line", "{} Zeile", "{} שורה"),
+        ("{} lines", "{} Zeilen", "{} שורות"),
+    ],
+    [
+        ("{} word", "{} Wort", "{} מילה"),
+        ("{} words", "{} Wörter", "{} מילים"),
+    ],
+    [
+        ("Total: {} file", "Gesamt: {} Datei", "סך הכול: {} קובץ"),
+        ("Total: {} files", "Gesamt: {} Dateien", "סך הכול: {} קבצים"),
+    ],
+    [
+        (
+            "archived {} file into {}",
+            "{} Datei archiviert in {}",
+            "{} קובץ אורכב לתוך {}",
+        ),
+        (
+            "archived {} files into {}",
+            "{} Dateien archiviert in {}",
+            "{} קבצים אורכבו לתוך {}",
+        ),
+    ],
+    [
+        (
+            "bundled {} file into {}",
+            "{} Datei gebündelt in {}",
+            "{} קובץ אוחד לתוך {}",
+        ),
+        (
+            "bundled {} files into {}",
+            "{} Dateien gebündelt in {}",
+            "{} קבצים אוחדו לתוך {}",
+        ),
+    ],
+    [
+        (
+            "copied {} file to {}",
+            "{} Datei kopiert nach {}",
+            "{} קובץ הועתק אל {}",
+        ),
+        (
+            "copied {} files to {}",
+            "{} Dateien kopiert nach {}",
+            "{} קבצים הועתקו אל {}",
+        ),
+    ],
+    [
+        (
+            "moved {} file to {}",
+            "{} Datei verschoben nach {}",
+            "{} קובץ הועבר אל {}",
+        ),
+        (
+            "moved {} files to {}",
+            "{} Dateien verschoben nach {}",
+            "{} קבצים הועברו אל {}",
+        ),
+    ],
+    [
+        (
+            "removed {} old report",
+            "{} alter Bericht entfernt",
+            "{} דוח ישן נמחק",
+        ),
+        (
+            "removed {} old reports",
+            "{} alte Berichte entfernt",
+            "{} דוחות ישנים נמחקו",
+        ),
+    ],
+    [
+        ("{} broken link", "{} defekter Link", "{} קישור שבור"),
+        ("{} broken links", "{} defekte Links", "{} קישורים שבורים"),
+    ],
+    [
+        ("in {} file", "in {} Datei", "ב־{} קובץ"),
+        ("in {} files", "in {} Dateien", "ב־{} קבצים"),
+    ],
+    [
+        (
+            "found {} broken link",
+            "{} defekter Link gefunden",
+            "נמצא {} קישור שבור",
+        ),
+        (
+            "found {} broken links",
+            "{} defekte Links gefunden",
+            "נמצאו {} קישורים שבורים",
+        ),
+    ],
+    [
+        (
+            "indexed {} file into {}",
+            "{} Datei indiziert in {}",
+            "{} קובץ נוסף לאינדקס {}",
+        ),
+        (
+            "indexed {} files into {}",
+            "{} Dateien indiziert in {}",
+            "{} קבצים נוספו לאינדקס {}",
+        ),
+    ],
+    [
+        (
+            "{} of {} directory",
+            "{} von {} Verzeichnis",
+            "{} מתוך {} תיקייה",
+        ),
+        (
+            "{} of {} directories",
+            "{} von {} Verzeichnissen",
+            "{} מתוך {} תיקיות",
+        ),
+    ],
+    [
+        ("{} edited file", "{} bearbeitete Datei", "{} קובץ שנערך"),
+        (
+            "{} edited files",
+            "{} bearbeitete Dateien",
+            "{} קבצים שנערכו",
+        ),
+    ],
+    [
+        (
+            "files: {} in {} directory",
+            "Dateien: {} in {} Verzeichnis",
+            "קבצים: {} ב־{} תיקייה",
+        ),
+        (
+            "files: {} in {} directories",
+            "Dateien: {} in {} Verzeichnissen",
+            "קבצים: {} ב־{} תיקיות",
+        ),
+    ],
+    [
+        (
+            "files: {} in {} directory, with content",
+            "Dateien: {} in {} Verzeichnis, mit Inhalt",
+            "קבצים: {} ב־{} תיקייה, כולל תוכן",
+        ),
+        (
+            "files: {} in {} directories, with content",
+            "Dateien: {} in {} Verzeichnissen, mit Inhalt",
+            "קבצים: {} ב־{} תיקיות, כולל תוכן",
+        ),
+    ],
+];
+
+/// Use `lang` for output (for `--lang`).
+pub fn set_lang(lang: Lang) {
+    LANG.with(|current| current.set(lang));
+}
+
+/// The language set for output on this thread, for passing on to others.
+pub fn lang() -> Lang {
+    LANG.with(Cell::get)
+}
+
+/// `text` in the language set for output. Text without a translation is
+/// returned as is.
+pub fn tr(text: &'static str) -> &'static str {
+    MESSAGES
+        .iter()
+        .find(|(en, _, _)| *en == text)
+        .map_or(text, |&message| pick(message))
+}
+
+/// The text of `message` in the language set for output.
+fn pick((en, de, he): (&'static str, &'static str, &'static str)) -> &'static str {
+    match lang() {
+        Lang::En => en,
+        Lang::De => de,
+        Lang::He => he,
+    }
+}
+
+/// The translation of `template` with its `{}` placeholders replaced by
+/// `args`, in order.
+pub fn trf(template: &'static str, args: &[&dyn Display]) -> String {
+    fill(tr(template), args)
+}
+
+/// `trf` of the form of a counted message for `n`: its singular or plural,
+/// the one in `PLURALS` whose English plural is `plural`.
+pub fn trn(plural: &'static str, n: usize, args: &[&dyn Display]) -> String {
+    let template = PLURALS
+        .iter()
+        .find(|[_, other]| other.0 == plural)
+        .map_or(plural, |[one, other]| {
+            pick(if n == 1 { *one } else { *other })
+        });
+    fill(template, args)
+}
+
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::new();
+    for (i, part) in template.split("{}").enumerate() {
+        if let Some(arg) = i.checked_sub(1).and_then(|i| args.get(i)) {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tr() {
+        assert_eq!(tr("Added"), "Added");
+        set_lang(Lang::De);
+        assert_eq!(tr("Added"), "Hinzugefügt");
+        assert_eq!(tr("untranslated"), "untranslated");
+        set_lang(Lang::En);
+    }
+
+    #[test]
+    fn test_trf() {
+        assert_eq!(trf("Week of {}", &[&"3/10"]), "Week of 3/10");
+        set_lang(Lang::He);
+        assert_eq!(trf("changed: {}", &[&"now"]), "שונה: now");
+        set_lang(Lang::En);
+    }
+
+    #[test]
+    fn test_trn() {
+        assert_eq!(trn("Total: {} files", 1, &[&1]), "Total: 1 file");
+        assert_eq!(trn("Total: {} files", 3, &[&3]), "Total: 3 files");
+        assert_eq!(trn("untranslated {}", 1, &[&1]), "untranslated 1");
+        set_lang(Lang::De);
+        assert_eq!(trn("{} lines", 1, &[&1]), "1 Zeile");
+        assert_eq!(trn("{} words", 0, &[&0]), "0 Wörter");
+        set_lang(Lang::En);
+    }
+
+    #[test]
+    fn test_translations_keep_placeholders() {
+        for (en, de, he) in MESSAGES.iter().chain(PLURALS.iter().flatten()) {
+            let count = en.matches("{}").count();
+            assert_eq!(de.matches("{}").count(), count, "{}", en);
+            assert_eq!(he.matches("{}").count(), count, "{}", en);
+        }
+    }
+}
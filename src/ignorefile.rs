@@ -0,0 +1,99 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+
+use crate::{relative_path, status};
+
+pub const IGNORE_FILE: &str = ".mdfilesignore";
+
+/// Exclusions from the `.mdfilesignore` files (gitignore syntax) found in the
+/// search root and its parent directories. Each file applies to paths below
+/// its own directory.
+pub struct IgnoreFiles {
+    root: PathBuf,
+    base: PathBuf,
+    matchers: Vec<Gitignore>,
+}
+
+impl IgnoreFiles {
+    pub fn load(root: &Path) -> Self {
+        let base = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+
+        let mut matchers = Vec::new();
+        for dir in base.ancestors() {
+            let path = dir.join(IGNORE_FILE);
+            if !path.is_file() {
+                continue;
+            }
+
+            let mut builder = GitignoreBuilder::new(dir);
+            if let Some(e) = builder.add(&path) {
+                let message = format!("{}: {}", path.display(), e);
+                status::warn(&message);
+            }
+            match builder.build() {
+                Ok(matcher) => matchers.push(matcher),
+                Err(e) => {
+                    let message = format!("{}: {}", path.display(), e);
+                    status::warn(&message);
+                }
+            }
+        }
+
+        IgnoreFiles {
+            root: root.to_path_buf(),
+            base,
+            matchers,
+        }
+    }
+
+    /// Whether `path`, found while walking the root, is excluded.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.matchers.is_empty() {
+            return false;
+        }
+
+        let path = self.base.join(relative_path(&self.root, path));
+        self.matchers.iter().any(|matcher| {
+            path.starts_with(matcher.path()) && matcher.matched(&path, is_dir).is_ignore()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_no_ignore_files() {
+        let root = TempDir::new().unwrap();
+        let ignores = IgnoreFiles::load(root.path());
+        assert!(!ignores.is_ignored(&root.path().join("a.md"), false));
+    }
+
+    #[test]
+    fn test_ignore_patterns() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join(IGNORE_FILE), "*.log\nbuild/\n!keep.log\n").unwrap();
+
+        let ignores = IgnoreFiles::load(root.path());
+        assert!(ignores.is_ignored(&root.path().join("a.log"), false));
+        assert!(!ignores.is_ignored(&root.path().join("keep.log"), false));
+        assert!(ignores.is_ignored(&root.path().join("build"), true));
+        assert!(!ignores.is_ignored(&root.path().join("build"), false));
+        assert!(!ignores.is_ignored(&root.path().join("a.md"), false));
+    }
+
+    #[test]
+    fn test_ignore_file_in_parent() {
+        let parent = TempDir::new().unwrap();
+        fs::write(parent.path().join(IGNORE_FILE), "/project/skip.md\n").unwrap();
+        let root = parent.path().join("project");
+        fs::create_dir(&root).unwrap();
+
+        let ignores = IgnoreFiles::load(&root);
+        assert!(ignores.is_ignored(&root.join("skip.md"), false));
+        assert!(!ignores.is_ignored(&root.join("keep.md"), false));
+    }
+}
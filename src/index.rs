@@ -0,0 +1,922 @@
+use chrono::{Local, NaiveDate, NaiveTime, TimeDelta};
+use rusqlite::{Connection, params};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    DateRange, LinkStyle, TimeField, dirconfig, errors, i18n, local_date, relative_file,
+    relative_name, traversal, walk,
+};
+
+/// Name of the index database in the root when `--db` isn't given.
+pub const DEFAULT_NAME: &str = ".mdfiles.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS files (
+    path TEXT PRIMARY KEY,
+    dir TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    mtime INTEGER NOT NULL,
+    ctime INTEGER,
+    extension TEXT
+);
+CREATE INDEX IF NOT EXISTS files_dir ON files (dir);
+CREATE INDEX IF NOT EXISTS files_mtime ON files (mtime);
+CREATE INDEX IF NOT EXISTS files_ctime ON files (ctime);
+CREATE TABLE IF NOT EXISTS dirs (
+    path TEXT PRIMARY KEY,
+    mtime INTEGER
+);
+";
+
+/// Full-text table of the content of text files, kept when the index is
+/// built with `--content`.
+const CONTENTS: &str =
+    "CREATE VIRTUAL TABLE IF NOT EXISTS contents USING fts5 (path UNINDEXED, body)";
+
+/// Location of the index of `root`: `db` when given, `.mdfiles.db` in the
+/// root otherwise.
+pub fn db_path(root: &Path, db: Option<&str>) -> PathBuf {
+    match db {
+        Some(db) => PathBuf::from(db),
+        None => root.join(DEFAULT_NAME),
+    }
+}
+
+/// Open (creating if needed) the index database at `path`.
+fn open(path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(path)
+        .map_err(|e| format!("can't open index '{}': {}", path.display(), e))?;
+    conn.execute_batch(SCHEMA)
+        .map_err(|e| format!("can't set up index '{}': {}", path.display(), e))?;
+    Ok(conn)
+}
+
+/// Open the index database at `path`, which must have been built already.
+fn open_existing(path: &Path) -> Result<Connection, String> {
+    if !path.exists() {
+        return Err(format!(
+            "no index at '{}' (run mdfiles index build)",
+            path.display()
+        ));
+    }
+    open(path)
+}
+
+/// Whether the index has a table named `name`.
+fn has_table(conn: &Connection, name: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE name = ?1",
+        [name],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
+/// Content of `file` when it's text: valid UTF-8 without NUL bytes.
+fn text_content(file: &Path) -> Option<String> {
+    let text = String::from_utf8(fs::read(file).ok()?).ok()?;
+    (!text.contains('\0')).then_some(text)
+}
+
+/// `time` as nanoseconds since the Unix epoch, as stored in the index.
+fn nanos(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_nanos() as i64,
+        Err(e) => -(e.duration().as_nanos() as i64),
+    }
+}
+
+/// Start of `date` in local time. A day starting in a daylight saving gap
+/// starts at the end of it.
+fn day_start(date: NaiveDate) -> SystemTime {
+    let midnight = date.and_time(NaiveTime::MIN);
+    midnight
+        .and_local_timezone(Local)
+        .earliest()
+        .or_else(|| {
+            (midnight + TimeDelta::hours(1))
+                .and_local_timezone(Local)
+                .earliest()
+        })
+        .map_or_else(|| midnight.and_utc().into(), Into::into)
+}
+
+/// Index column holding the `field` timestamp.
+fn time_column(field: TimeField) -> &'static str {
+    match field {
+        TimeField::Modified => "mtime",
+        TimeField::Created => "ctime",
+    }
+}
+
+/// A file as stored in the index.
+#[derive(Debug, PartialEq)]
+struct Row {
+    path: String,
+    dir: String,
+    size: u64,
+    mtime: i64,
+    ctime: Option<i64>,
+    extension: Option<String>,
+}
+
+impl Row {
+    fn read(root: &Path, file: &Path) -> Option<Row> {
+        let metadata = match fs::metadata(file) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                errors::record(format!("{}: {}", file.display(), e));
+                return None;
+            }
+        };
+        let mtime = match metadata.modified() {
+            Ok(mtime) => nanos(mtime),
+            Err(e) => {
+                errors::record(format!("{}: {}", file.display(), e));
+                return None;
+            }
+        };
+
+        Some(Row {
+            path: relative_name(root, file),
+            dir: file
+                .parent()
+                .map(|dir| relative_name(root, dir))
+                .unwrap_or_default(),
+            size: metadata.len(),
+            mtime,
+            ctime: metadata.created().ok().map(nanos),
+            extension: file
+                .extension()
+                .map(|ext| ext.to_string_lossy().into_owned()),
+        })
+    }
+}
+
+/// Outcome of an index refresh.
+#[derive(Debug, PartialEq)]
+pub struct Refresh {
+    /// Directories whose files were (re)read.
+    pub scanned: usize,
+    /// Files of the other directories read again, as they were changed in
+    /// place.
+    pub edited: usize,
+    /// Directories under the root.
+    pub dirs: usize,
+    /// Files in the index afterwards.
+    pub files: usize,
+}
+
+impl Refresh {
+    /// The status line reporting the refresh of the index at `db`.
+    pub fn describe(&self, db: &Path) -> String {
+        i18n::trf(
+            "rescanned {} and {}, {} in {}",
+            &[
+                &i18n::trn(
+                    "{} of {} directories",
+                    self.dirs,
+                    &[&self.scanned, &self.dirs],
+                ),
+                &i18n::trn("{} edited files", self.edited, &[&self.edited]),
+                &i18n::trn("{} files", self.files, &[&self.files]),
+                &db.display(),
+            ],
+        )
+    }
+}
+
+/// Bring the index at `db` up to date with `root`. Each directory is
+/// recorded with its modification time when it was scanned, and only the
+/// files of directories that are new or modified since then are read again,
+/// unless `rebuild` asks for all of them. Files of the other directories are
+/// read again when their size or modification time no longer matches the
+/// index, as editing a file in place leaves its directory unchanged. A
+/// rebuild also adds or drops the
+/// content table, as asked by `content`; otherwise it's kept up to date when
+/// the index has one.
+fn refresh(
+    db: &Path,
+    root: &Path,
+    rebuild: bool,
+    content: bool,
+    options: &traversal::WalkOptions,
+) -> Result<Refresh, String> {
+    let error = |e: rusqlite::Error| format!("can't write index '{}': {}", db.display(), e);
+
+    let mut conn = open(db)?;
+    let mut known: HashMap<String, Option<i64>> = HashMap::new();
+    let mut known_files: HashMap<String, (i64, i64)> = HashMap::new();
+    if !rebuild {
+        let mut select = conn
+            .prepare("SELECT path, mtime FROM dirs")
+            .map_err(error)?;
+        known = select
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(error)?
+            .collect::<Result<_, _>>()
+            .map_err(error)?;
+        let mut select = conn
+            .prepare("SELECT path, size, mtime FROM files")
+            .map_err(error)?;
+        known_files = select
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+            .map_err(error)?
+            .collect::<Result<_, _>>()
+            .map_err(error)?;
+    }
+
+    // The index itself may live under the root
+    let db_file = fs::canonicalize(db).ok();
+    let is_db = |file: &Path| {
+        db_file.as_ref().is_some_and(|db| {
+            file.file_name() == db.file_name() && fs::canonicalize(file).ok().as_ref() == Some(db)
+        })
+    };
+
+    let mut seen = HashSet::new();
+    let mut changed = HashMap::new();
+    let mut rows = Vec::new();
+    let mut edited = Vec::new();
+    let context = traversal::WalkContext::new(options);
+    for entry in walk(root, &context) {
+        let path = entry.path();
+        if entry.file_type().is_dir() {
+            let name = relative_name(root, path);
+            let modified = entry
+                .metadata()
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .map(nanos);
+            if modified.is_none() || known.get(&name) != Some(&modified) {
+                changed.insert(name.clone(), modified);
+            }
+            seen.insert(name);
+        } else if entry.file_type().is_file() {
+            let dir = path
+                .parent()
+                .map(|dir| relative_name(root, dir))
+                .unwrap_or_default();
+            if is_db(path) {
+                continue;
+            }
+            if changed.contains_key(&dir) {
+                rows.extend(Row::read(root, path));
+                continue;
+            }
+            let stale = match known_files.get(&relative_name(root, path)) {
+                Some(&(size, mtime)) => entry.metadata().ok().is_none_or(|metadata| {
+                    metadata.len() as i64 != size
+                        || metadata.modified().ok().map(nanos) != Some(mtime)
+                }),
+                None => true,
+            };
+            if stale {
+                edited.extend(Row::read(root, path));
+            }
+        }
+    }
+
+    let tx = conn.transaction().map_err(error)?;
+    if rebuild {
+        tx.execute_batch("DELETE FROM files; DELETE FROM dirs; DROP TABLE IF EXISTS contents;")
+            .map_err(error)?;
+        if content {
+            tx.execute_batch(CONTENTS).map_err(error)?;
+        }
+    }
+    let content = has_table(&tx, "contents").map_err(error)?;
+    let remove_contents =
+        "DELETE FROM contents WHERE path IN (SELECT path FROM files WHERE dir = ?1)";
+
+    for dir in known.keys().filter(|dir| !seen.contains(*dir)) {
+        if content {
+            tx.execute(remove_contents, [dir]).map_err(error)?;
+        }
+        tx.execute("DELETE FROM files WHERE dir = ?1", [dir])
+            .map_err(error)?;
+        tx.execute("DELETE FROM dirs WHERE path = ?1", [dir])
+            .map_err(error)?;
+    }
+    for (dir, modified) in &changed {
+        if content {
+            tx.execute(remove_contents, [dir]).map_err(error)?;
+        }
+        tx.execute("DELETE FROM files WHERE dir = ?1", [dir])
+            .map_err(error)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO dirs (path, mtime) VALUES (?1, ?2)",
+            params![dir, modified],
+        )
+        .map_err(error)?;
+    }
+    for row in &edited {
+        if content {
+            tx.execute("DELETE FROM contents WHERE path = ?1", [&row.path])
+                .map_err(error)?;
+        }
+        tx.execute("DELETE FROM files WHERE path = ?1", [&row.path])
+            .map_err(error)?;
+    }
+    {
+        let mut insert = tx
+            .prepare(
+                "INSERT INTO files (path, dir, size, mtime, ctime, extension)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(error)?;
+        let mut insert_content = if content {
+            Some(
+                tx.prepare("INSERT INTO contents (path, body) VALUES (?1, ?2)")
+                    .map_err(error)?,
+            )
+        } else {
+            None
+        };
+        for row in rows.iter().chain(&edited) {
+            insert
+                .execute(params![
+                    row.path,
+                    row.dir,
+                    row.size as i64,
+                    row.mtime,
+                    row.ctime,
+                    row.extension
+                ])
+                .map_err(error)?;
+            if let Some(insert_content) = &mut insert_content
+                && let Some(text) = text_content(&relative_file(root, &row.path))
+            {
+                insert_content
+                    .execute(params![row.path, text])
+                    .map_err(error)?;
+            }
+        }
+    }
+    let files: i64 = tx
+        .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+        .map_err(error)?;
+    tx.commit().map_err(error)?;
+
+    Ok(Refresh {
+        scanned: changed.len(),
+        edited: edited.len(),
+        dirs: seen.len(),
+        files: files as usize,
+    })
+}
+
+/// Walk `root` and replace the content of the index at `db` with its files,
+/// along with the content of the text ones when `content` is set. Returns
+/// the number of files indexed.
+pub fn build(
+    db: &Path,
+    root: &Path,
+    content: bool,
+    options: &traversal::WalkOptions,
+) -> Result<usize, String> {
+    refresh(db, root, true, content, options).map(|refresh| refresh.files)
+}
+
+/// Update the index at `db`, reading again only the directories that changed
+/// since they were scanned, and the files changed in place in the others.
+pub fn update(db: &Path, root: &Path, options: &traversal::WalkOptions) -> Result<Refresh, String> {
+    open_existing(db)?;
+    refresh(db, root, false, false, options)
+}
+
+/// What an index holds.
+#[derive(Debug, PartialEq)]
+pub struct Summary {
+    pub files: usize,
+    pub dirs: usize,
+    /// Whether file content is indexed, for search.
+    pub content: bool,
+}
+
+/// Counts of what the index at `db` holds.
+pub fn summary(db: &Path) -> Result<Summary, String> {
+    let conn = open_existing(db)?;
+    let error = |e: rusqlite::Error| format!("can't read index '{}': {}", db.display(), e);
+    let count = |table: &str| {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .map(|count| count as usize)
+        .map_err(error)
+    };
+    Ok(Summary {
+        files: count("files")?,
+        dirs: count("dirs")?,
+        content: has_table(&conn, "contents").map_err(error)?,
+    })
+}
+
+/// The files to look for in the index, by their timestamps.
+#[derive(Clone, Copy, Debug)]
+pub enum Period {
+    /// Files whose timestamp falls on one of the dates.
+    Dates(DateRange),
+    /// Files whose timestamp is after `after` and before `before` (when
+    /// set).
+    Between {
+        after: Option<SystemTime>,
+        before: Option<SystemTime>,
+    },
+}
+
+impl From<DateRange> for Period {
+    fn from(dates: DateRange) -> Self {
+        Period::Dates(dates)
+    }
+}
+
+impl From<NaiveDate> for Period {
+    fn from(date: NaiveDate) -> Self {
+        Period::Dates(date.into())
+    }
+}
+
+impl Period {
+    /// Whether `time` falls in the period.
+    pub fn contains(&self, time: SystemTime) -> bool {
+        match self {
+            Period::Dates(dates) => dates.contains(local_date(time)),
+            Period::Between { after, before } => {
+                after.is_none_or(|after| time > after) && before.is_none_or(|before| time < before)
+            }
+        }
+    }
+
+    /// Timestamps of the period, in nanoseconds: `[start, end)`.
+    fn bounds(&self) -> (i64, i64) {
+        match self {
+            Period::Dates(dates) => (
+                nanos(day_start(dates.start)),
+                nanos(day_start(dates.end + TimeDelta::days(1))),
+            ),
+            Period::Between { after, before } => (
+                after.map_or(i64::MIN, |after| nanos(after) + 1),
+                before.map_or(i64::MAX, nanos),
+            ),
+        }
+    }
+}
+
+/// Indexed files under `root` ending with `suffix` whose `field` timestamp
+/// is in `period`, oldest first, like `find_files` on the live tree with
+/// `options`.
+pub fn query(
+    db: &Path,
+    root: &Path,
+    suffix: &str,
+    period: Period,
+    field: TimeField,
+    options: &traversal::WalkOptions,
+) -> Result<Vec<PathBuf>, String> {
+    let error = |e: rusqlite::Error| format!("can't read index '{}': {}", db.display(), e);
+
+    let conn = open_existing(db)?;
+    let column = time_column(field);
+    let (start, end) = period.bounds();
+    let mut statement = conn
+        .prepare(&format!(
+            "SELECT path FROM files WHERE {column} >= ?1 AND {column} < ?2 ORDER BY {column}, path"
+        ))
+        .map_err(error)?;
+    let paths = statement
+        .query_map(params![start, end], |row| row.get::<_, String>(0))
+        .map_err(error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(error)?;
+
+    let scopes = dirconfig::Scopes::new(&options.overridden);
+    Ok(paths
+        .into_iter()
+        .map(|path| relative_file(root, &path))
+        .filter(|path| scopes.matches_suffix(path, suffix))
+        .collect())
+}
+
+/// A full-text search hit.
+#[derive(Debug, PartialEq)]
+pub struct Hit {
+    pub path: PathBuf,
+    /// Text around the matches, which are in bold.
+    pub snippet: String,
+}
+
+/// Indexed text files under `root` ending with `suffix`, whose `field`
+/// timestamp is in `period` and whose content matches the FTS5 `query`,
+/// best matches first.
+pub fn search(
+    db: &Path,
+    root: &Path,
+    suffix: &str,
+    query: &str,
+    period: Period,
+    field: TimeField,
+    options: &traversal::WalkOptions,
+) -> Result<Vec<Hit>, String> {
+    let error = |e: rusqlite::Error| format!("can't search index '{}': {}", db.display(), e);
+
+    let conn = open_existing(db)?;
+    if !has_table(&conn, "contents").map_err(error)? {
+        return Err(format!(
+            "index '{}' has no content (run mdfiles index build --content)",
+            db.display()
+        ));
+    }
+
+    let column = time_column(field);
+    let (start, end) = period.bounds();
+    let mut statement = conn
+        .prepare(&format!(
+            "SELECT files.path, snippet(contents, 1, '**', '**', '…', 12)
+             FROM contents JOIN files ON files.path = contents.path
+             WHERE contents MATCH ?1 AND files.{column} >= ?2 AND files.{column} < ?3
+             ORDER BY rank"
+        ))
+        .map_err(error)?;
+    let hits = statement
+        .query_map(params![query, start, end], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(error)?;
+
+    let scopes = dirconfig::Scopes::new(&options.overridden);
+    Ok(hits
+        .into_iter()
+        .map(|(path, snippet)| Hit {
+            path: relative_file(root, &path),
+            snippet: snippet.split_whitespace().collect::<Vec<_>>().join(" "),
+        })
+        .filter(|hit| scopes.matches_suffix(&hit.path, suffix))
+        .collect())
+}
+
+/// Search hits as markdown: the file's entry with the snippet quoted under
+/// it.
+pub fn render_hits(hits: &[Hit], style: &LinkStyle) -> String {
+    hits.iter()
+        .map(|hit| format!("{}\n  > {}\n", style.format(&hit.path), hit.snippet))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traversal::WalkOptions;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn rows(db: &Path) -> Vec<Row> {
+        let conn = Connection::open(db).unwrap();
+        let mut select = conn
+            .prepare("SELECT path, dir, size, mtime, ctime, extension FROM files ORDER BY path")
+            .unwrap();
+        select
+            .query_map([], |row| {
+                Ok(Row {
+                    path: row.get(0)?,
+                    dir: row.get(1)?,
+                    size: row.get::<_, i64>(2)? as u64,
+                    mtime: row.get(3)?,
+                    ctime: row.get(4)?,
+                    extension: row.get(5)?,
+                })
+            })
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn test_nanos() {
+        assert_eq!(nanos(UNIX_EPOCH + Duration::new(2, 5)), 2_000_000_005);
+        assert_eq!(nanos(UNIX_EPOCH - Duration::from_secs(10)), -10_000_000_000);
+    }
+
+    #[test]
+    fn test_build() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("sub")).unwrap();
+        fs::write(root.path().join("a.md"), "alpha").unwrap();
+        fs::write(root.path().join("sub").join("Makefile"), "all:").unwrap();
+        let db = db_path(root.path(), None);
+
+        assert_eq!(
+            build(&db, root.path(), false, &WalkOptions::default()).unwrap(),
+            2
+        );
+        let indexed = rows(&db);
+        assert_eq!(
+            indexed.iter().map(|r| r.path.as_str()).collect::<Vec<_>>(),
+            vec!["a.md", "sub/Makefile"]
+        );
+        assert_eq!(indexed[0].dir, "");
+        assert_eq!(indexed[1].dir, "sub");
+        assert_eq!(indexed[0].size, 5);
+        assert_eq!(indexed[0].extension.as_deref(), Some("md"));
+        assert_eq!(indexed[1].extension, None);
+        let modified = fs::metadata(root.path().join("a.md"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(indexed[0].mtime, nanos(modified));
+    }
+
+    #[test]
+    fn test_day_start() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        assert_eq!(crate::local_date(day_start(date)), date);
+        let before = day_start(date) - std::time::Duration::from_secs(1);
+        assert_eq!(crate::local_date(before), date.pred_opt().unwrap());
+    }
+
+    #[test]
+    fn test_query() {
+        let root = TempDir::new().unwrap();
+        let old = root.path().join("old.md");
+        let new = root.path().join("new.md");
+        fs::write(&old, "").unwrap();
+        fs::write(&new, "").unwrap();
+        fs::write(root.path().join("new.txt"), "").unwrap();
+        let yesterday = SystemTime::now() - Duration::from_secs(86400);
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(yesterday)
+            .unwrap();
+        let db = root.path().join("index.db");
+        build(&db, root.path(), false, &WalkOptions::default()).unwrap();
+
+        let today = Local::now().date_naive();
+        let period = Period::Dates(today.into());
+        let found = query(
+            &db,
+            root.path(),
+            ".md",
+            period,
+            TimeField::Modified,
+            &WalkOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(found, vec![new.clone()]);
+
+        let range = DateRange {
+            start: today - TimeDelta::days(1),
+            end: today,
+        };
+        let period = Period::Dates(range);
+        let found = query(
+            &db,
+            root.path(),
+            ".md",
+            period,
+            TimeField::Modified,
+            &WalkOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(found, vec![old, new.clone()]);
+
+        let period = Period::Between {
+            after: Some(yesterday + Duration::from_secs(1)),
+            before: None,
+        };
+        let found = query(
+            &db,
+            root.path(),
+            ".md",
+            period,
+            TimeField::Modified,
+            &WalkOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(found, vec![new]);
+    }
+
+    #[test]
+    fn test_query_without_index() {
+        let root = TempDir::new().unwrap();
+        let db = root.path().join("index.db");
+        let today = Local::now().date_naive();
+        let period = Period::Dates(today.into());
+        let result = query(
+            &db,
+            root.path(),
+            ".md",
+            period,
+            TimeField::Modified,
+            &WalkOptions::default(),
+        );
+        assert!(result.unwrap_err().contains("run mdfiles index build"));
+        assert!(!db.exists());
+    }
+
+    #[test]
+    fn test_update() {
+        let root = TempDir::new().unwrap();
+        let dir = TempDir::new().unwrap();
+        for sub in ["same", "added", "gone"] {
+            fs::create_dir(root.path().join(sub)).unwrap();
+            fs::write(root.path().join(sub).join("a.md"), "").unwrap();
+        }
+        let db = dir.path().join("index.db");
+        assert!(update(&db, root.path(), &WalkOptions::default()).is_err());
+        build(&db, root.path(), false, &WalkOptions::default()).unwrap();
+
+        let unchanged = update(&db, root.path(), &WalkOptions::default()).unwrap();
+        assert_eq!(
+            unchanged,
+            Refresh {
+                scanned: 0,
+                edited: 0,
+                dirs: 4,
+                files: 3
+            }
+        );
+
+        // A file changed in place is read again, though its directory doesn't
+        // change
+        fs::write(root.path().join("same").join("a.md"), "more").unwrap();
+        fs::write(root.path().join("added").join("b.md"), "").unwrap();
+        fs::remove_dir_all(root.path().join("gone")).unwrap();
+        let refreshed = update(&db, root.path(), &WalkOptions::default()).unwrap();
+        assert_eq!(
+            refreshed,
+            Refresh {
+                scanned: 2,
+                edited: 1,
+                dirs: 3,
+                files: 3
+            }
+        );
+        assert_eq!(
+            summary(&db).unwrap(),
+            Summary {
+                files: 3,
+                dirs: 3,
+                content: false
+            }
+        );
+        assert_eq!(
+            rows(&db)
+                .iter()
+                .map(|r| (r.path.as_str(), r.size))
+                .collect::<Vec<_>>(),
+            vec![("added/a.md", 0), ("added/b.md", 0), ("same/a.md", 4)]
+        );
+    }
+
+    #[test]
+    fn test_text_content() {
+        let dir = TempDir::new().unwrap();
+        let text = dir.path().join("a.md");
+        let binary = dir.path().join("a.bin");
+        fs::write(&text, "héllo").unwrap();
+        fs::write(&binary, b"a\0b").unwrap();
+        assert_eq!(text_content(&text).as_deref(), Some("héllo"));
+        assert_eq!(text_content(&binary), None);
+    }
+
+    #[test]
+    fn test_search() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("sub")).unwrap();
+        let a = root.path().join("a.md");
+        fs::write(&a, "# Plan\n\nWe ship the\nrocket on Friday.\n").unwrap();
+        fs::write(root.path().join("b.md"), "Nothing here.").unwrap();
+        fs::write(root.path().join("sub").join("c.txt"), "rocket fuel").unwrap();
+        let db = root.path().join("index.db");
+        let today = Period::Dates(Local::now().date_naive().into());
+
+        build(&db, root.path(), false, &WalkOptions::default()).unwrap();
+        let result = search(
+            &db,
+            root.path(),
+            ".md",
+            "rocket",
+            today,
+            TimeField::Modified,
+            &WalkOptions::default(),
+        );
+        assert!(result.unwrap_err().contains("--content"));
+
+        build(&db, root.path(), true, &WalkOptions::default()).unwrap();
+        let hits = search(
+            &db,
+            root.path(),
+            ".md",
+            "rocket",
+            today,
+            TimeField::Modified,
+            &WalkOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            hits,
+            vec![Hit {
+                path: a.clone(),
+                snippet: "# Plan We ship the **rocket** on Friday.".to_string(),
+            }]
+        );
+        let hits = search(
+            &db,
+            root.path(),
+            ".md",
+            "nothing",
+            today,
+            TimeField::Modified,
+            &WalkOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(
+            search(
+                &db,
+                root.path(),
+                ".md",
+                "\"unbalanced",
+                today,
+                TimeField::Modified,
+                &WalkOptions::default(),
+            )
+            .is_err()
+        );
+
+        // Updates keep the content current, removals included
+        fs::remove_file(root.path().join("b.md")).unwrap();
+        fs::write(root.path().join("sub").join("d.md"), "more rockets").unwrap();
+        update(&db, root.path(), &WalkOptions::default()).unwrap();
+        let hits = search(
+            &db,
+            root.path(),
+            "",
+            "rocket*",
+            today,
+            TimeField::Modified,
+            &WalkOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(hits.len(), 3);
+        let hits = search(
+            &db,
+            root.path(),
+            "",
+            "nothing",
+            today,
+            TimeField::Modified,
+            &WalkOptions::default(),
+        )
+        .unwrap();
+        assert!(hits.is_empty());
+
+        let dirty = Connection::open(&db).unwrap();
+        let stale: i64 = dirty
+            .query_row(
+                "SELECT COUNT(*) FROM contents WHERE path = 'b.md'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(stale, 0);
+
+        // A rebuild without --content drops it
+        build(&db, root.path(), false, &WalkOptions::default()).unwrap();
+        assert!(
+            search(
+                &db,
+                root.path(),
+                "",
+                "rocket",
+                today,
+                TimeField::Modified,
+                &WalkOptions::default(),
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rebuild_drops_removed_files() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join("a.md"), "").unwrap();
+        fs::write(root.path().join("b.md"), "").unwrap();
+        let db = root.path().join("index.db");
+        build(&db, root.path(), false, &WalkOptions::default()).unwrap();
+
+        fs::remove_file(root.path().join("b.md")).unwrap();
+        assert_eq!(
+            build(&db, root.path(), false, &WalkOptions::default()).unwrap(),
+            1
+        );
+        assert_eq!(rows(&db).len(), 1);
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::markdown::{link_targets, percent_decode, wiki_targets};
+use crate::traversal::{WalkContext, WalkOptions};
+use crate::{LinkStyle, errors, file_iterator, i18n, path_text, relative_name};
+
+/// Where a link leads.
+#[derive(Debug, PartialEq)]
+pub enum Target {
+    /// A URL, an anchor in the same document, or an absolute path to a
+    /// file outside the root.
+    External,
+    /// A file under the root.
+    Resolved(PathBuf),
+    /// A file that doesn't exist.
+    Broken,
+}
+
+/// Resolve `.` and `..` components without touching the file system, so
+/// paths reached different ways compare equal.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                out.pop();
+            }
+            _ => out.push(component),
+        }
+    }
+    out
+}
+
+/// Whether a markdown link target is a URL (`https:`, `mailto:`...) rather
+/// than a path.
+fn is_url(target: &str) -> bool {
+    match target.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.len() > 1
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// The notes of a root and an index of all its files for wiki links.
+pub struct Notes {
+    root: PathBuf,
+    pub files: Vec<PathBuf>,
+    by_name: HashMap<String, PathBuf>,
+}
+
+impl Notes {
+    /// Index the files under `root`, walked with `options`; the notes are
+    /// those ending with `suffix`. Wiki links match a file by path or name
+    /// relative to the root, with `.md` optional, ignoring case.
+    pub fn scan(root: &Path, suffix: &str, options: &WalkOptions) -> Notes {
+        let mut files = Vec::new();
+        let mut by_name = HashMap::new();
+
+        let context = WalkContext::new(options);
+        for path in file_iterator(root, &context) {
+            let path = normalize(&path);
+            let name = relative_name(root, &path).to_lowercase();
+            let mut keys = vec![name.clone()];
+            if let Some((_, file_name)) = name.rsplit_once('/') {
+                keys.push(file_name.to_string());
+            }
+            for key in keys {
+                if let Some(stem) = key.strip_suffix(".md") {
+                    by_name.entry(stem.to_string()).or_insert(path.clone());
+                }
+                by_name.entry(key).or_insert(path.clone());
+            }
+
+            if context.scopes.matches_suffix(&path, suffix) {
+                files.push(path);
+            }
+        }
+
+        files.sort();
+        Notes {
+            root: root.to_path_buf(),
+            files,
+            by_name,
+        }
+    }
+
+    fn resolve_markdown(&self, from: &Path, target: &str) -> Target {
+        if target.starts_with('#') || is_url(target) {
+            return Target::External;
+        }
+
+        let path = target.split(['#', '?']).next().unwrap_or(target);
+        let path = percent_decode(path);
+        // `/notes/a.md` is taken from the root, as sites built from the
+        // notes serve them, then as a path on this machine
+        let resolved = match path.strip_prefix('/') {
+            Some(rooted) => self.root.join(rooted),
+            None => from.parent().unwrap_or(Path::new("")).join(&path),
+        };
+
+        let resolved = normalize(&resolved);
+        if resolved.exists() {
+            Target::Resolved(resolved)
+        } else if Path::new(&path).is_absolute() && Path::new(&path).exists() {
+            Target::External
+        } else {
+            Target::Broken
+        }
+    }
+
+    fn resolve_wiki(&self, target: &str) -> Target {
+        let name = target.split('#').next().unwrap_or(target).trim();
+        if name.is_empty() {
+            return Target::External;
+        }
+
+        match self.by_name.get(&name.replace('\\', "/").to_lowercase()) {
+            Some(path) => Target::Resolved(path.clone()),
+            None => Target::Broken,
+        }
+    }
+
+    /// The links of the note at `path`, as written, with where they lead.
+    pub fn links(&self, path: &Path) -> Vec<(String, Target)> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                errors::record(format!("{}: {}", path.display(), e));
+                return Vec::new();
+            }
+        };
+
+        let markdown = link_targets(&content).into_iter().map(|target| {
+            let resolved = self.resolve_markdown(path, &target);
+            (target, resolved)
+        });
+        let wiki = wiki_targets(&content).into_iter().map(|target| {
+            let resolved = self.resolve_wiki(&target);
+            (format!("[[{}]]", target), resolved)
+        });
+        markdown.chain(wiki).collect()
+    }
+
+    /// Files under the root that the note at `path` links to, without
+    /// duplicates.
+    pub fn outgoing(&self, path: &Path) -> Vec<PathBuf> {
+        let mut seen = HashSet::new();
+        self.links(path)
+            .into_iter()
+            .filter_map(|(_, target)| match target {
+                Target::Resolved(target) if seen.insert(target.clone()) => Some(target),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A section listing `files`, or saying there are none.
+fn list(heading: &str, files: &[PathBuf], style: &LinkStyle) -> String {
+    let mut out = format!("### {}\n\n", heading);
+    if files.is_empty() {
+        out.push_str(i18n::tr("(none)"));
+        out.push('\n');
+    }
+    for file in files {
+        out.push_str(&style.format(file));
+        out.push('\n');
+    }
+    out
+}
+
+/// For each of `files`, the notes it links to and the notes linking to it.
+pub fn graph(notes: &Notes, files: &[PathBuf], style: &LinkStyle) -> String {
+    let outgoing: HashMap<&PathBuf, Vec<PathBuf>> = notes
+        .files
+        .iter()
+        .map(|note| (note, notes.outgoing(note)))
+        .collect();
+
+    let mut out = Vec::new();
+    for file in files {
+        let file = normalize(file);
+        let links = outgoing
+            .get(&file)
+            .cloned()
+            .unwrap_or_else(|| notes.outgoing(&file));
+        let backlinks: Vec<PathBuf> = notes
+            .files
+            .iter()
+            .filter(|note| **note != file && outgoing[note].contains(&file))
+            .cloned()
+            .collect();
+
+        out.push(format!(
+            "## {}\n\n{}\n{}",
+            relative_name(&notes.root, &file),
+            list(i18n::tr("Links"), &links, style),
+            list(i18n::tr("Backlinks"), &backlinks, style)
+        ));
+    }
+    out.join("\n")
+}
+
+/// The broken links of each of `files`, as written, leaving out files whose
+/// links are all fine.
+pub fn broken(notes: &Notes, files: &[PathBuf]) -> Vec<(PathBuf, Vec<String>)> {
+    files
+        .iter()
+        .filter_map(|file| {
+            let broken: Vec<String> = notes
+                .links(file)
+                .into_iter()
+                .filter(|(_, target)| *target == Target::Broken)
+                .map(|(link, _)| link)
+                .collect();
+            (!broken.is_empty()).then(|| (file.clone(), broken))
+        })
+        .collect()
+}
+
+/// Markdown report of broken links: a section per file, linking to it.
+pub fn render_broken(broken: &[(PathBuf, Vec<String>)], style: &LinkStyle) -> String {
+    let sections: Vec<String> = broken
+        .iter()
+        .map(|(file, links)| {
+            let name = file.file_name().map(path_text).unwrap_or_default();
+            let mut out = format!("## [{}]({})\n\n", name, style.target(file));
+            for link in links {
+                out.push_str(&format!("- `{}`\n", link));
+            }
+            out
+        })
+        .collect();
+    sections.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vault() -> TempDir {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("dir")).unwrap();
+        fs::write(
+            root.path().join("a.md"),
+            "[b](dir/b.md) [[C]] [web](https://example.com) [[missing]] [top](#top)",
+        )
+        .unwrap();
+        fs::write(root.path().join("dir").join("b.md"), "[a](../a.md#intro)").unwrap();
+        fs::write(
+            root.path().join("c.md"),
+            "[[dir/b|B]] [gone](gone%20file.md)",
+        )
+        .unwrap();
+        root
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(Path::new("./a/../b/./c.md")), Path::new("b/c.md"));
+        assert_eq!(normalize(Path::new("../x.md")), Path::new("../x.md"));
+    }
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url("https://example.com"));
+        assert!(is_url("mailto:me@example.com"));
+        assert!(!is_url("notes/a.md"));
+        assert!(!is_url("C:/notes/a.md"));
+    }
+
+    #[test]
+    fn test_links() {
+        let root = vault();
+        let notes = Notes::scan(root.path(), ".md", &WalkOptions::default());
+        let a = normalize(&root.path().join("a.md"));
+        let b = normalize(&root.path().join("dir").join("b.md"));
+        let c = normalize(&root.path().join("c.md"));
+
+        assert_eq!(
+            notes.links(&a),
+            vec![
+                ("dir/b.md".to_string(), Target::Resolved(b.clone())),
+                ("https://example.com".to_string(), Target::External),
+                ("#top".to_string(), Target::External),
+                ("[[C]]".to_string(), Target::Resolved(c.clone())),
+                ("[[missing]]".to_string(), Target::Broken),
+            ]
+        );
+        assert_eq!(notes.outgoing(&b), vec![a.clone()]);
+        assert_eq!(
+            notes.links(&c)[0],
+            ("gone%20file.md".to_string(), Target::Broken)
+        );
+    }
+
+    #[test]
+    fn test_absolute_targets() {
+        let root = vault();
+        let outside = TempDir::new().unwrap();
+        let other = outside.path().join("other.md");
+        fs::write(&other, "").unwrap();
+        let a = root.path().join("a.md");
+        fs::write(
+            &a,
+            format!(
+                "[b](/dir/b.md) [other]({}) [gone](/gone.md)",
+                other.display()
+            ),
+        )
+        .unwrap();
+
+        let notes = Notes::scan(root.path(), ".md", &WalkOptions::default());
+        assert_eq!(
+            notes.links(&a),
+            vec![
+                (
+                    "/dir/b.md".to_string(),
+                    Target::Resolved(normalize(&root.path().join("dir/b.md")))
+                ),
+                (path_text(&other), Target::External),
+                ("/gone.md".to_string(), Target::Broken),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_graph() {
+        let root = vault();
+        let notes = Notes::scan(root.path(), ".md", &WalkOptions::default());
+        let b = root.path().join("dir").join("b.md");
+        let style = LinkStyle::default();
+
+        let result = graph(&notes, &[b], &style);
+        let a = style.format(&normalize(&root.path().join("a.md")));
+        let c = style.format(&normalize(&root.path().join("c.md")));
+        assert_eq!(
+            result,
+            format!(
+                "## dir/b.md\n\n### Links\n\n{}\n\n### Backlinks\n\n{}\n{}\n",
+                a, a, c
+            )
+        );
+
+        let lone = root.path().join("lone.md");
+        fs::write(&lone, "no links").unwrap();
+        let notes = Notes::scan(root.path(), ".md", &WalkOptions::default());
+        assert_eq!(
+            graph(&notes, &[lone], &style),
+            "## lone.md\n\n### Links\n\n(none)\n\n### Backlinks\n\n(none)\n"
+        );
+    }
+
+    #[test]
+    fn test_broken() {
+        let root = vault();
+        let notes = Notes::scan(root.path(), ".md", &WalkOptions::default());
+        let files: Vec<PathBuf> = ["a.md", "c.md", "dir/b.md"]
+            .iter()
+            .map(|name| root.path().join(name))
+            .collect();
+
+        let broken = broken(&notes, &files);
+        assert_eq!(
+            broken,
+            vec![
+                (files[0].clone(), vec!["[[missing]]".to_string()]),
+                (files[1].clone(), vec!["gone%20file.md".to_string()]),
+            ]
+        );
+
+        let style = LinkStyle::default();
+        let report = render_broken(&broken, &style);
+        assert!(report.starts_with(&format!(
+            "## [a.md]({})\n\n- `[[missing]]`\n\n## [c.md]",
+            style.target(&files[0])
+        )));
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::atomic;
+
+/// How long to wait for another run to release a lock.
+const WAIT: Duration = Duration::from_secs(30);
+/// Age after which a lock is taken to be left over from a crashed run.
+const STALE: Duration = Duration::from_secs(300);
+const RETRY: Duration = Duration::from_millis(100);
+
+/// A lock on a file, held until dropped: a `.NAME.lock` file next to it,
+/// holding a token of its owner. Lock files work across machines sharing a
+/// synced folder, where advisory locks don't; the token makes sure a run
+/// only ever removes its own lock.
+pub struct Lock {
+    path: PathBuf,
+    token: String,
+}
+
+impl Lock {
+    /// Lock `path` for a read-modify-write, waiting for other runs holding it.
+    pub fn acquire(path: &Path) -> Result<Lock, String> {
+        Lock::acquire_within(path, WAIT)
+    }
+
+    fn acquire_within(path: &Path, wait: Duration) -> Result<Lock, String> {
+        let lock = atomic::sibling(path, ".", ".lock");
+        let token = format!("{} {:016x}", std::process::id(), fastrand::u64(..));
+        let started = Instant::now();
+        loop {
+            match File::create_new(&lock) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", token)
+                        .map_err(|e| format!("can't lock '{}': {}", path.display(), e))?;
+                    return Ok(Lock { path: lock, token });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    if is_stale(&lock) {
+                        take_over(&lock, owner(&lock).as_deref(), &token);
+                        continue;
+                    }
+                    if started.elapsed() >= wait {
+                        return Err(format!(
+                            "'{}' is locked by another run (remove '{}' if none is running)",
+                            path.display(),
+                            lock.display()
+                        ));
+                    }
+                    thread::sleep(RETRY);
+                }
+                Err(e) => return Err(format!("can't lock '{}': {}", path.display(), e)),
+            }
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        // A run that took the lock over as stale owns it now
+        if owner(&self.path).as_deref() == Some(self.token.as_str()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// The token in the lock file at `path`.
+fn owner(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|text| text.trim().to_string())
+}
+
+/// Remove the stale lock file at `lock`, owned by `stale`, for a run with
+/// `token`. It's moved aside first, and put back if another run replaced it
+/// in the meantime, so two runs finding the same stale lock can't remove
+/// each other's new one.
+fn take_over(lock: &Path, stale: Option<&str>, token: &str) {
+    let aside = atomic::sibling(lock, "", &format!(".{}", token.replace(' ', "-")));
+    if fs::rename(lock, &aside).is_err() {
+        return;
+    }
+    if owner(&aside).as_deref() != stale {
+        let _ = fs::hard_link(&aside, lock);
+    }
+    let _ = fs::remove_file(&aside);
+}
+
+/// Whether the lock file at `path` is older than `STALE`.
+fn is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|time| {
+            SystemTime::now()
+                .duration_since(time)
+                .is_ok_and(|age| age > STALE)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.path().join("log.md");
+        let lock_file = dir.path().join(".log.md.lock");
+
+        let lock = Lock::acquire(&note).unwrap();
+        assert!(lock_file.exists());
+        let err = Lock::acquire_within(&note, Duration::ZERO).err().unwrap();
+        assert!(err.contains("is locked by another run"));
+        drop(lock);
+        assert!(!lock_file.exists());
+
+        // Left over from a run that crashed
+        File::create(&lock_file)
+            .unwrap()
+            .set_modified(SystemTime::now() - STALE * 2)
+            .unwrap();
+        let lock = Lock::acquire_within(&note, Duration::ZERO).unwrap();
+
+        // A run that found the same stale lock leaves the new one alone
+        take_over(&lock_file, Some(""), "other");
+        assert!(lock_file.exists());
+
+        // Taken over after this run held it too long: not removed on drop
+        fs::write(&lock_file, "other\n").unwrap();
+        drop(lock);
+        assert!(lock_file.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
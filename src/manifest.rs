@@ -0,0 +1,178 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{i18n, relative_name};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Format {
+    #[default]
+    Json,
+    Bsd,
+}
+
+/// A manifest line: a file, relative to the root, and what it looked like.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: String,
+    pub size: u64,
+    pub mtime: String,
+    pub blake3: String,
+}
+
+/// Size and BLAKE3 hash (in hex) of the file at `path`.
+pub fn hash_file(path: &Path) -> io::Result<(u64, String)> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(File::open(path)?)?;
+    Ok((hasher.count(), hasher.finalize().to_hex().to_string()))
+}
+
+fn entry(root: &Path, file: &Path) -> io::Result<Entry> {
+    let modified: DateTime<Utc> = fs::metadata(file)?.modified()?.into();
+    let (size, blake3) = hash_file(file)?;
+    Ok(Entry {
+        path: relative_name(root, file),
+        size,
+        mtime: modified.to_rfc3339_opts(SecondsFormat::Secs, true),
+        blake3,
+    })
+}
+
+/// Manifest entries for `files`, with paths relative to `root`.
+pub fn entries(root: &Path, files: &[PathBuf]) -> Result<Vec<Entry>, String> {
+    files
+        .iter()
+        .map(|file| {
+            entry(root, file).map_err(|e| i18n::trf("can't read '{}': {}", &[&file.display(), &e]))
+        })
+        .collect()
+}
+
+/// Render `entries` as a JSON array, or as BSD-style checksum lines
+/// (`BLAKE3 (path) = hash`), which only record the hashes.
+pub fn render(entries: &[Entry], format: Format) -> String {
+    match format {
+        Format::Json => {
+            serde_json::to_string_pretty(entries).expect("manifest entries serialize") + "\n"
+        }
+        Format::Bsd => entries
+            .iter()
+            .map(|entry| format!("BLAKE3 ({}) = {}\n", entry.path, entry.blake3))
+            .collect(),
+    }
+}
+
+fn parse_bsd_line(line: &str) -> Option<(String, String)> {
+    let rest = line.strip_prefix("BLAKE3 (")?;
+    let (path, hash) = rest.rsplit_once(") = ")?;
+    Some((path.to_string(), hash.trim().to_string()))
+}
+
+/// Paths and hashes listed in a manifest, in either format.
+fn parse(text: &str) -> Result<Vec<(String, String)>, String> {
+    if text.trim_start().starts_with('[') {
+        let entries: Vec<Entry> = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        return Ok(entries
+            .into_iter()
+            .map(|entry| (entry.path, entry.blake3))
+            .collect());
+    }
+
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            parse_bsd_line(line)
+                .ok_or_else(|| format!("line {}: not a BLAKE3 checksum line", i + 1))
+        })
+        .collect()
+}
+
+/// Read the manifest at `path`, as written by `mdfiles manifest`.
+pub fn read(path: &str) -> Result<Vec<(String, String)>, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| i18n::trf("can't read '{}': {}", &[&path, &e]))?;
+    parse(&text).map_err(|e| format!("invalid manifest '{}': {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const EMPTY_HASH: &str = "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+
+    #[test]
+    fn test_hash_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("empty.md");
+        fs::write(&path, "").unwrap();
+        assert_eq!(hash_file(&path).unwrap(), (0, EMPTY_HASH.to_string()));
+    }
+
+    #[test]
+    fn test_entries() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let path = dir.path().join("sub").join("a.md");
+        fs::write(&path, "hello").unwrap();
+
+        let entries = entries(dir.path(), &[path]).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "sub/a.md");
+        assert_eq!(entries[0].size, 5);
+        assert_eq!(entries[0].blake3, blake3::hash(b"hello").to_hex().as_str());
+        assert!(entries[0].mtime.ends_with('Z'));
+    }
+
+    #[test]
+    fn test_entries_missing_file() {
+        let dir = TempDir::new().unwrap();
+        let err = entries(dir.path(), &[dir.path().join("gone.md")]).unwrap_err();
+        assert!(err.starts_with("can't read"));
+    }
+
+    #[test]
+    fn test_render() {
+        let entries = vec![Entry {
+            path: "a.md".to_string(),
+            size: 0,
+            mtime: "2025-03-14T10:00:00Z".to_string(),
+            blake3: EMPTY_HASH.to_string(),
+        }];
+
+        assert_eq!(
+            render(&entries, Format::Bsd),
+            format!("BLAKE3 (a.md) = {}\n", EMPTY_HASH)
+        );
+
+        let json: Vec<Entry> = serde_json::from_str(&render(&entries, Format::Json)).unwrap();
+        assert_eq!(json, entries);
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let entries = vec![Entry {
+            path: "a (1).md".to_string(),
+            size: 0,
+            mtime: "2025-03-14T10:00:00Z".to_string(),
+            blake3: EMPTY_HASH.to_string(),
+        }];
+        let expected = vec![("a (1).md".to_string(), EMPTY_HASH.to_string())];
+
+        assert_eq!(parse(&render(&entries, Format::Json)).unwrap(), expected);
+        assert_eq!(parse(&render(&entries, Format::Bsd)).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_bad_line() {
+        let text = format!("BLAKE3 (a.md) = {}\nsha256 b.md\n", EMPTY_HASH);
+        assert_eq!(
+            parse(&text).unwrap_err(),
+            "line 2: not a BLAKE3 checksum line"
+        );
+    }
+}
@@ -0,0 +1,312 @@
+/// Targets of the inline (`[text](target)`) and reference (`[id]: target`)
+/// links in a markdown document, in order of appearance. Links in code
+/// spans, fenced code blocks and the frontmatter are left out.
+pub fn link_targets(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+
+    for line in prose_lines(content) {
+        if let Some(target) = reference_target(line) {
+            targets.push(target);
+            continue;
+        }
+
+        let line = without_code_spans(line);
+        let mut rest = line.as_str();
+        while let Some(start) = rest.find("](") {
+            rest = &rest[start + 2..];
+            let Some(end) = closing_paren(rest) else {
+                break;
+            };
+            if let Some(target) = clean_target(&rest[..end]) {
+                targets.push(target);
+            }
+            rest = &rest[end + 1..];
+        }
+    }
+
+    targets
+}
+
+/// Targets of the wiki links (`[[target]]`, `[[target|alias]]`, and embeds
+/// `![[target]]`) in a markdown document, in order of appearance. Heading
+/// references (`#heading`) are kept.
+pub fn wiki_targets(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else {
+            break;
+        };
+        let inner = &rest[..end];
+        if !inner.contains('\n') {
+            let target = inner.split('|').next().unwrap_or(inner).trim();
+            if !target.is_empty() {
+                targets.push(target.to_string());
+            }
+        }
+        rest = &rest[end + 2..];
+    }
+    targets
+}
+
+/// Decode the `%XX` escapes of a link target.
+pub fn percent_decode(s: &str) -> String {
+    String::from_utf8_lossy(&percent_decode_bytes(s)).into_owned()
+}
+
+/// Whether `bytes` start with two hex digits, as after the `%` of an escape.
+pub fn is_escape(bytes: &[u8]) -> bool {
+    bytes.len() >= 2 && bytes[..2].iter().all(u8::is_ascii_hexdigit)
+}
+
+/// `s` with its `%XX` escapes decoded, as bytes that may not be UTF-8.
+pub fn percent_decode_bytes(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let decoded = (bytes[i] == b'%' && is_escape(&bytes[i + 1..]))
+            .then(|| std::str::from_utf8(&bytes[i + 1..i + 3]).ok())
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Lines of a markdown document outside its frontmatter and fenced code
+/// blocks.
+pub fn prose_lines(content: &str) -> Vec<&str> {
+    prose(content).into_iter().map(|(_, line)| line).collect()
+}
+
+/// `prose_lines`, each with its index among all the lines of the document.
+pub fn prose(content: &str) -> Vec<(usize, &str)> {
+    let mut lines = content.lines().enumerate().peekable();
+    if lines.peek().is_some_and(|(_, line)| *line == "---") {
+        lines.next();
+        for (_, line) in lines.by_ref() {
+            if matches!(line.trim_end(), "---" | "...") {
+                break;
+            }
+        }
+    }
+
+    let mut in_fence = false;
+    lines
+        .filter(|(_, line)| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return false;
+            }
+            !in_fence
+        })
+        .collect()
+}
+
+/// `line` with its code spans (`` `code` ``) blanked out. An unclosed
+/// backtick is kept as text.
+fn without_code_spans(line: &str) -> String {
+    let parts: Vec<&str> = line.split('`').collect();
+    if parts.len().is_multiple_of(2) {
+        return line.to_string();
+    }
+    parts
+        .iter()
+        .step_by(2)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `content` with its inline links (`[text](target)`) turned into
+/// reference links (`[text][1]`), numbered in order of appearance, and the
+/// `[1]: target` definitions at the end. Links to the same target share a
+/// number.
+pub fn reference_links(content: &str) -> String {
+    let mut targets: Vec<&str> = Vec::new();
+    let mut out = String::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("](") {
+        let after = &rest[start + 2..];
+        let Some(end) = closing_paren(after).filter(|end| !after[..*end].contains('\n')) else {
+            out.push_str(&rest[..start + 2]);
+            rest = after;
+            continue;
+        };
+        let target = &after[..end];
+        let number = match targets.iter().position(|known| *known == target) {
+            Some(index) => index + 1,
+            None => {
+                targets.push(target);
+                targets.len()
+            }
+        };
+        out.push_str(&format!("{}][{}]", &rest[..start], number));
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+
+    if !targets.is_empty() {
+        out.push('\n');
+        for (i, target) in targets.iter().enumerate() {
+            if target.contains(char::is_whitespace) {
+                out.push_str(&format!("[{}]: <{}>\n", i + 1, target));
+            } else {
+                out.push_str(&format!("[{}]: {}\n", i + 1, target));
+            }
+        }
+    }
+    out
+}
+
+/// Index of the `)` closing an inline link target, allowing balanced
+/// parentheses inside the target.
+fn closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn reference_target(line: &str) -> Option<String> {
+    let line = line.trim_start();
+    let rest = line.strip_prefix('[')?;
+    let end = rest.find("]:")?;
+    if end == 0 {
+        return None;
+    }
+    clean_target(&rest[end + 2..])
+}
+
+/// Strip an optional title and angle brackets from a raw link target.
+fn clean_target(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let target = match raw.strip_prefix('<') {
+        Some(rest) => rest.split('>').next().unwrap_or(rest),
+        None => without_title(raw),
+    };
+
+    if target.is_empty() {
+        None
+    } else {
+        Some(target.to_string())
+    }
+}
+
+/// `raw` without a trailing link title (`"title"`, `'title'` or `(title)`).
+/// Targets with spaces are kept whole, as the listings write them.
+fn without_title(raw: &str) -> &str {
+    for (open, close) in [('"', '"'), ('\'', '\''), ('(', ')')] {
+        if let Some(body) = raw.strip_suffix(close)
+            && let Some(start) = body.rfind(open)
+            && body[..start].ends_with(char::is_whitespace)
+        {
+            return body[..start].trim_end();
+        }
+    }
+    raw
+}
+
+/// Normalize a link target or path for comparison: forward slashes and no
+/// leading `./`.
+pub fn normalize_target(target: &str) -> String {
+    let target = target.replace('\\', "/");
+    let mut target = target.as_str();
+    while let Some(rest) = target.strip_prefix("./") {
+        target = rest;
+    }
+    target.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_targets_inline() {
+        let content = "- [a](a.md) and [b](./dir/b.md \"title\")\n- [c](<with space.md>)";
+        assert_eq!(
+            link_targets(content),
+            vec!["a.md", "./dir/b.md", "with space.md"]
+        );
+    }
+
+    #[test]
+    fn test_link_targets_reference_and_parens() {
+        let content = "[x](notes/(draft).md)\n\n[1]: docs/ref.md\n[]: nothing";
+        assert_eq!(
+            link_targets(content),
+            vec!["notes/(draft).md", "docs/ref.md"]
+        );
+    }
+
+    #[test]
+    fn test_link_targets_skips_code() {
+        let content = "[a](a.md) `[b](b.md)`\n```\n[c](c.md)\n```\n[d](my d.md 'title') `x";
+        assert_eq!(link_targets(content), vec!["a.md", "my d.md"]);
+    }
+
+    #[test]
+    fn test_link_targets_ignores_plain_text() {
+        assert!(link_targets("no links (here) [or] here").is_empty());
+    }
+
+    #[test]
+    fn test_wiki_targets() {
+        let content = "See [[Note]], [[dir/Other|alias]] and ![[image.png]].\n[[Plan#Goals]] [[ ]] [[broken\n]]";
+        assert_eq!(
+            wiki_targets(content),
+            vec!["Note", "dir/Other", "image.png", "Plan#Goals"]
+        );
+    }
+
+    #[test]
+    fn test_prose_lines() {
+        let content = "---\ntitle: x\n---\n# Title\n```\n# not a heading\n```\ntext\n";
+        assert_eq!(prose_lines(content), vec!["# Title", "text"]);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("with%20space.md"), "with space.md");
+        assert_eq!(percent_decode("a+b%2"), "a+b%2");
+        assert_eq!(percent_decode("%+1"), "%+1");
+        assert_eq!(percent_decode("%C3%A9t%C3%A9"), "été");
+    }
+
+    #[test]
+    fn test_reference_links() {
+        let content = "- [a.md](notes/a.md)\n  - [Intro](notes/a.md#intro)\n\
+                       - ![b.png](my pics/b.png)\n- [c (old).md](c (old).md)\n- [again](notes/a.md)\n";
+        assert_eq!(
+            reference_links(content),
+            "- [a.md][1]\n  - [Intro][2]\n- ![b.png][3]\n- [c (old).md][4]\n- [again][1]\n\n\
+             [1]: notes/a.md\n[2]: notes/a.md#intro\n[3]: <my pics/b.png>\n[4]: <c (old).md>\n"
+        );
+        assert_eq!(reference_links("no links\n"), "no links\n");
+    }
+
+    #[test]
+    fn test_normalize_target() {
+        assert_eq!(normalize_target("./src/main.rs"), "src/main.rs");
+        assert_eq!(normalize_target("src\\main.rs"), "src/main.rs");
+        assert_eq!(normalize_target("/abs/path.md"), "/abs/path.md");
+    }
+}
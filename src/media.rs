@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use crate::markdown;
+
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "avif", "bmp", "gif", "heic", "jpeg", "jpg", "png", "svg", "tif", "tiff", "webp",
+];
+const AUDIO_EXTENSIONS: &[&str] = &["aac", "flac", "m4a", "mp3", "oga", "ogg", "opus", "wav"];
+const VIDEO_EXTENSIONS: &[&str] = &["m4v", "mkv", "mov", "mp4", "ogv", "webm"];
+
+/// Kind of media of a file, going by its extension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Image,
+    Audio,
+    Video,
+    Document,
+}
+
+/// Lowercase extension of `path`, if it has one.
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+/// Kind of media of the file at `path`.
+pub fn kind(path: &Path) -> Kind {
+    let Some(ext) = extension(path) else {
+        return Kind::Document;
+    };
+    let ext = ext.as_str();
+    if IMAGE_EXTENSIONS.contains(&ext) {
+        Kind::Image
+    } else if AUDIO_EXTENSIONS.contains(&ext) {
+        Kind::Audio
+    } else if VIDEO_EXTENSIONS.contains(&ext) {
+        Kind::Video
+    } else {
+        Kind::Document
+    }
+}
+
+/// Whether `path` is an image, going by its extension.
+pub fn is_image(path: &Path) -> bool {
+    kind(path) == Kind::Image
+}
+
+/// HTML `<audio>` or `<video>` player for `target`, or `None` for other
+/// kinds of files.
+pub fn player(kind: Kind, target: &str) -> Option<String> {
+    let tag = match kind {
+        Kind::Audio => "audio",
+        Kind::Video => "video",
+        Kind::Image | Kind::Document => return None,
+    };
+    let src = target.replace('&', "&amp;").replace('"', "&quot;");
+    Some(format!("<{} controls src=\"{}\"></{}>", tag, src, tag))
+}
+
+/// Sources of the players made by `player` in `content`, unescaped.
+pub fn player_sources(content: &str) -> Vec<String> {
+    let mut sources = Vec::new();
+    for line in markdown::prose_lines(content) {
+        let mut rest = line;
+        while let Some(start) = rest.find(" controls src=\"") {
+            rest = &rest[start + " controls src=\"".len()..];
+            let end = rest.find('"').unwrap_or(rest.len());
+            sources.push(rest[..end].replace("&quot;", "\"").replace("&amp;", "&"));
+            rest = &rest[end..];
+        }
+    }
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(kind(Path::new("photos/IMG_0001.JPG")), Kind::Image);
+        assert_eq!(kind(Path::new("diagram.svg")), Kind::Image);
+        assert_eq!(kind(Path::new("memo.m4a")), Kind::Audio);
+        assert_eq!(kind(Path::new("clip.webm")), Kind::Video);
+        assert_eq!(kind(Path::new("notes.md")), Kind::Document);
+        assert_eq!(kind(Path::new("png")), Kind::Document);
+    }
+
+    #[test]
+    fn test_player() {
+        assert_eq!(
+            player(Kind::Video, "a \"b\".mp4").unwrap(),
+            "<video controls src=\"a &quot;b&quot;.mp4\"></video>"
+        );
+        assert_eq!(player(Kind::Image, "a.png"), None);
+
+        let audio = player(Kind::Audio, "memo & more.m4a").unwrap();
+        assert_eq!(player_sources(&audio), vec!["memo & more.m4a"]);
+    }
+}
@@ -0,0 +1,247 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{i18n, relative_path, status};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Copy,
+    Move,
+}
+
+impl Mode {
+    /// Message templates: the dry run line, the error and the summary.
+    fn templates(self) -> [&'static str; 3] {
+        match self {
+            Mode::Copy => [
+                "would copy {} -> {}",
+                "can't copy '{}' to '{}': {}",
+                "copied {} files to {}",
+            ],
+            Mode::Move => [
+                "would move {} -> {}",
+                "can't move '{}' to '{}': {}",
+                "moved {} files to {}",
+            ],
+        }
+    }
+}
+
+/// Copy `from` to `to`, keeping its modification time, which `fs::copy`
+/// sets to now.
+fn copy_file(from: &Path, to: &Path) -> io::Result<()> {
+    let modified = fs::metadata(from)?.modified()?;
+    fs::copy(from, to)?;
+    File::options().write(true).open(to)?.set_modified(modified)
+}
+
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    // rename fails across file systems, fall back to copy and delete
+    fs::rename(from, to).or_else(|_| {
+        copy_file(from, to)?;
+        fs::remove_file(from)
+    })
+}
+
+fn transfer(from: &Path, to: &Path, mode: Mode) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match mode {
+        Mode::Copy => copy_file(from, to),
+        Mode::Move => move_file(from, to),
+    }
+}
+
+/// `path` made absolute with symlinks resolved, for paths that may not exist
+/// yet: the longest existing ancestor is canonicalized and the rest appended.
+fn resolve(path: &Path) -> io::Result<PathBuf> {
+    let path = std::path::absolute(path)?;
+    let mut rest = Vec::new();
+    let mut base = path.as_path();
+    loop {
+        match base.canonicalize() {
+            Ok(real) => return Ok(rest.iter().rev().fold(real, |acc, name| acc.join(name))),
+            Err(_) => match (base.parent(), base.file_name()) {
+                (Some(parent), Some(name)) => {
+                    rest.push(name.to_owned());
+                    base = parent;
+                }
+                _ => return Ok(path),
+            },
+        }
+    }
+}
+
+/// Refuse a `dest` that is `root` or inside it, where files would be
+/// copied onto themselves or picked up by the next run.
+fn check_dest(root: &Path, dest: &Path) -> Result<(), String> {
+    let real_root =
+        resolve(root).map_err(|e| i18n::trf("can't read '{}': {}", &[&root.display(), &e]))?;
+    let real_dest =
+        resolve(dest).map_err(|e| i18n::trf("can't read '{}': {}", &[&dest.display(), &e]))?;
+    if real_dest.starts_with(&real_root) {
+        return Err(format!(
+            "destination '{}' is inside the root directory '{}'",
+            dest.display(),
+            root.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Copy or move `files` into `dest`, recreating their layout relative to
+/// `root`. Existing files in `dest` are only replaced with `force`. With
+/// `dry_run`, only print what would be done.
+pub fn mirror(
+    root: &Path,
+    files: &[PathBuf],
+    dest: &Path,
+    mode: Mode,
+    force: bool,
+    dry_run: bool,
+) -> Result<(), String> {
+    check_dest(root, dest)?;
+    if !force {
+        for file in files {
+            let target = dest.join(relative_path(root, file));
+            if target.symlink_metadata().is_ok() {
+                return Err(format!(
+                    "'{}' exists, use --force to overwrite it",
+                    target.display()
+                ));
+            }
+        }
+    }
+
+    let [would, failed, done] = mode.templates();
+    for file in files {
+        let target = dest.join(relative_path(root, file));
+
+        if dry_run {
+            println!(
+                "{}",
+                i18n::trf(would, &[&file.display(), &target.display()])
+            );
+            continue;
+        }
+
+        transfer(file, &target, mode)
+            .map_err(|e| i18n::trf(failed, &[&file.display(), &target.display(), &e]))?;
+    }
+
+    if !dry_run {
+        status::say(i18n::trn(
+            done,
+            files.len(),
+            &[&files.len(), &dest.display()],
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn sample_tree() -> (TempDir, Vec<PathBuf>) {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("sub")).unwrap();
+        fs::write(root.path().join("a.md"), "a").unwrap();
+        fs::write(root.path().join("sub").join("b.md"), "b").unwrap();
+        let files = vec![
+            root.path().join("a.md"),
+            root.path().join("sub").join("b.md"),
+        ];
+        (root, files)
+    }
+
+    #[test]
+    fn test_mirror_copy() {
+        let (root, files) = sample_tree();
+        let dest = TempDir::new().unwrap();
+        mirror(root.path(), &files, dest.path(), Mode::Copy, false, false).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dest.path().join("sub/b.md")).unwrap(),
+            "b"
+        );
+        assert!(root.path().join("sub/b.md").exists());
+    }
+
+    #[test]
+    fn test_copy_file_keeps_mtime() {
+        let (root, files) = sample_tree();
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        File::options()
+            .write(true)
+            .open(&files[0])
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let copy = root.path().join("copy.md");
+        copy_file(&files[0], &copy).unwrap();
+
+        assert_eq!(fs::metadata(&copy).unwrap().modified().unwrap(), old);
+    }
+
+    #[test]
+    fn test_mirror_move() {
+        let (root, files) = sample_tree();
+        let dest = TempDir::new().unwrap();
+        mirror(root.path(), &files, dest.path(), Mode::Move, false, false).unwrap();
+
+        assert_eq!(fs::read_to_string(dest.path().join("a.md")).unwrap(), "a");
+        assert!(!root.path().join("a.md").exists());
+    }
+
+    #[test]
+    fn test_mirror_dry_run() {
+        let (root, files) = sample_tree();
+        let dest = TempDir::new().unwrap();
+        mirror(root.path(), &files, dest.path(), Mode::Move, false, true).unwrap();
+
+        assert_eq!(fs::read_dir(dest.path()).unwrap().count(), 0);
+        assert!(root.path().join("a.md").exists());
+    }
+
+    #[test]
+    fn test_mirror_refuses_overwrite() {
+        let (root, files) = sample_tree();
+        let dest = TempDir::new().unwrap();
+        fs::write(dest.path().join("a.md"), "old").unwrap();
+
+        let err = mirror(root.path(), &files, dest.path(), Mode::Copy, false, false).unwrap_err();
+        assert!(err.contains("use --force"));
+        assert_eq!(fs::read_to_string(dest.path().join("a.md")).unwrap(), "old");
+        assert!(!dest.path().join("sub").exists());
+
+        mirror(root.path(), &files, dest.path(), Mode::Copy, true, false).unwrap();
+        assert_eq!(fs::read_to_string(dest.path().join("a.md")).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_mirror_refuses_dest_in_root() {
+        let (root, files) = sample_tree();
+        for dest in [root.path().to_path_buf(), root.path().join("new/copy")] {
+            let err = mirror(root.path(), &files, &dest, Mode::Copy, true, false).unwrap_err();
+            assert!(err.contains("inside the root directory"));
+        }
+        assert_eq!(fs::read_to_string(&files[0]).unwrap(), "a");
+        assert!(!root.path().join("new").exists());
+    }
+
+    #[test]
+    fn test_mirror_missing_source() {
+        let root = TempDir::new().unwrap();
+        let dest = TempDir::new().unwrap();
+        let files = vec![root.path().join("gone.md")];
+        let err = mirror(root.path(), &files, dest.path(), Mode::Copy, false, false).unwrap_err();
+        assert!(err.starts_with("can't copy"));
+    }
+}
@@ -0,0 +1,139 @@
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How much of a file is looked at to tell text from binary.
+const SNIFF_SIZE: u64 = 8192;
+
+/// Program and arguments of an editor setting such as `code --wait`, split
+/// as a shell would. None when it's empty or has unbalanced quotes.
+fn editor_command(value: &str) -> Option<(String, Vec<String>)> {
+    let mut words = shlex::split(value)?.into_iter();
+    let program = words.next()?;
+    Some((program, words.collect()))
+}
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`.
+fn editor() -> Option<(String, Vec<String>)> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find_map(|value| editor_command(&value))
+}
+
+/// Whether `path` looks like a text file: its start has no NUL byte and is
+/// valid UTF-8, save for a character cut at the end of the sample.
+fn is_text(path: &Path) -> bool {
+    let mut sample = Vec::new();
+    let read = File::open(path).and_then(|file| file.take(SNIFF_SIZE).read_to_end(&mut sample));
+    if read.is_err() || sample.contains(&0) {
+        return false;
+    }
+
+    match std::str::from_utf8(&sample) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// The platform's command for opening a file with its default application.
+fn opener() -> (&'static str, &'static [&'static str]) {
+    if cfg!(target_os = "macos") {
+        ("open", &[])
+    } else if cfg!(windows) {
+        ("cmd", &["/C", "start", ""])
+    } else {
+        ("xdg-open", &[])
+    }
+}
+
+fn run(program: &str, args: &[String], files: &[&PathBuf]) -> Result<(), String> {
+    let status = Command::new(program)
+        .args(args)
+        .args(files)
+        .status()
+        .map_err(|e| format!("can't run '{}': {}", program, e))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("'{}' failed: {}", program, status))
+    }
+}
+
+/// Open `files`: text files in the editor, all at once, and other files (or
+/// all of them when no editor is set) with the platform's opener.
+pub fn open_files(files: &[PathBuf]) -> Result<(), String> {
+    let editor = editor();
+    let (text, other): (Vec<_>, Vec<_>) = files
+        .iter()
+        .partition(|file| editor.is_some() && is_text(file));
+
+    if let Some((program, args)) = &editor
+        && !text.is_empty()
+    {
+        run(program, args, &text)?;
+    }
+
+    let (program, args) = opener();
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    for file in other {
+        run(program, &args, &[file])?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_editor_command() {
+        assert_eq!(
+            editor_command("code --wait"),
+            Some(("code".to_string(), vec!["--wait".to_string()]))
+        );
+        assert_eq!(editor_command("vim"), Some(("vim".to_string(), vec![])));
+        assert_eq!(editor_command("  "), None);
+        assert_eq!(
+            editor_command("'/Applications/Sublime Text.app/subl' -w"),
+            Some((
+                "/Applications/Sublime Text.app/subl".to_string(),
+                vec!["-w".to_string()]
+            ))
+        );
+        assert_eq!(editor_command("vim \"unclosed"), None);
+    }
+
+    #[test]
+    fn test_is_text() {
+        let dir = TempDir::new().unwrap();
+        let text = dir.path().join("a.md");
+        let binary = dir.path().join("a.png");
+        let latin1 = dir.path().join("a.txt");
+        fs::write(&text, "# Notes\n\nété\n").unwrap();
+        fs::write(&binary, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        fs::write(&latin1, b"caf\xe9 au lait").unwrap();
+
+        assert!(is_text(&text));
+        assert!(!is_text(&binary));
+        assert!(!is_text(&latin1));
+        assert!(!is_text(&dir.path().join("missing.md")));
+    }
+
+    #[test]
+    fn test_is_text_cut_character() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("long.md");
+        let mut content = "a".repeat(SNIFF_SIZE as usize - 1).into_bytes();
+        content.extend("é".as_bytes());
+        fs::write(&path, content).unwrap();
+
+        assert!(is_text(&path));
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::Locale;
+
+use crate::{DateRange, list_title};
+
+const GENERATOR: &str = concat!("mdfiles ", env!("CARGO_PKG_VERSION"));
+
+/// `text` as a double-quoted YAML string.
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Pandoc YAML metadata block (title, date, generator) for a list of the
+/// files of `range`.
+pub fn metadata(range: DateRange, locale: Option<Locale>) -> String {
+    format!(
+        "---\ntitle: {}\ndate: {}\ngenerator: {}\n---\n\n",
+        quoted(&list_title(range, locale)),
+        range.end.format("%Y-%m-%d"),
+        quoted(GENERATOR)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_quoted() {
+        assert_eq!(quoted(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+
+    #[test]
+    fn test_metadata_single_day() {
+        assert_eq!(
+            metadata(date(14).into(), None),
+            format!(
+                "---\ntitle: \"Files for 2025-03-14\"\ndate: 2025-03-14\ngenerator: \"{}\"\n---\n\n",
+                GENERATOR
+            )
+        );
+    }
+
+    #[test]
+    fn test_metadata_range() {
+        let range = DateRange {
+            start: date(1),
+            end: date(14),
+        };
+        let result = metadata(range, Some(Locale::fr_FR));
+        assert!(result.contains("title: \"Files from 1 mars 2025 to 14 mars 2025\"\n"));
+        assert!(result.contains("date: 2025-03-14\n"));
+    }
+}
@@ -0,0 +1,342 @@
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use std::io::{self, Stderr};
+
+const HELP: &str = "type to filter  ↑/↓ move  tab select  ctrl-a all  enter accept  esc cancel";
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Continue,
+    Accept,
+    Cancel,
+}
+
+/// Selection state of the picker, independent of the terminal.
+struct Picker {
+    labels: Vec<String>,
+    selected: Vec<bool>,
+    query: String,
+    /// Indices of the labels matching `query`, best match first.
+    visible: Vec<usize>,
+    state: ListState,
+    matcher: SkimMatcherV2,
+}
+
+impl Picker {
+    fn new(labels: Vec<String>) -> Self {
+        let mut picker = Picker {
+            selected: vec![false; labels.len()],
+            visible: Vec::new(),
+            labels,
+            query: String::new(),
+            state: ListState::default(),
+            matcher: SkimMatcherV2::default(),
+        };
+        picker.filter();
+        picker
+    }
+
+    /// Recompute the visible labels from the query and reset the cursor.
+    fn filter(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .labels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, label)| {
+                let score = self.matcher.fuzzy_match(label, &self.query)?;
+                Some((score, i))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        self.visible = scored.into_iter().map(|(_, i)| i).collect();
+        let cursor = if self.visible.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        self.state.select(cursor);
+    }
+
+    /// Index of the label under the cursor.
+    fn cursor(&self) -> Option<usize> {
+        self.state.selected().map(|row| self.visible[row])
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if let Some(current) = self.state.selected() {
+            let last = self.visible.len() - 1;
+            let next = current.saturating_add_signed(delta).min(last);
+            self.state.select(Some(next));
+        }
+    }
+
+    fn toggle(&mut self) {
+        if let Some(current) = self.cursor() {
+            self.selected[current] = !self.selected[current];
+            self.move_by(1);
+        }
+    }
+
+    /// Toggle all the visible labels.
+    fn toggle_all(&mut self) {
+        let all = self.visible.iter().all(|&i| self.selected[i]);
+        for &i in &self.visible {
+            self.selected[i] = !all;
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => Action::Cancel,
+            KeyCode::Char('c') if control => Action::Cancel,
+            KeyCode::Enter => Action::Accept,
+            KeyCode::Up => {
+                self.move_by(-1);
+                Action::Continue
+            }
+            KeyCode::Down => {
+                self.move_by(1);
+                Action::Continue
+            }
+            KeyCode::PageUp => {
+                self.move_by(-10);
+                Action::Continue
+            }
+            KeyCode::PageDown => {
+                self.move_by(10);
+                Action::Continue
+            }
+            KeyCode::Tab => {
+                self.toggle();
+                Action::Continue
+            }
+            KeyCode::Char('a') if control => {
+                self.toggle_all();
+                Action::Continue
+            }
+            KeyCode::Backspace => {
+                if self.query.pop().is_some() {
+                    self.filter();
+                }
+                Action::Continue
+            }
+            KeyCode::Char(c) if !control => {
+                self.query.push(c);
+                self.filter();
+                Action::Continue
+            }
+            _ => Action::Continue,
+        }
+    }
+
+    /// Indices of the chosen entries: the selected ones, or the one under the
+    /// cursor when nothing was selected.
+    fn chosen(&self) -> Vec<usize> {
+        let selected: Vec<usize> = (0..self.labels.len())
+            .filter(|&i| self.selected[i])
+            .collect();
+        if selected.is_empty() {
+            self.cursor().into_iter().collect()
+        } else {
+            selected
+        }
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame) {
+        let [query_area, list_area, help_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let count = self.selected.iter().filter(|s| **s).count();
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&i| {
+                let mark = if self.selected[i] { "[x] " } else { "[ ] " };
+                ListItem::new(Line::from(format!("{}{}", mark, self.labels[i])))
+            })
+            .collect();
+
+        let title = format!(
+            " {}/{} files, {} selected ",
+            self.visible.len(),
+            self.labels.len(),
+            count
+        );
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_widget(Paragraph::new(format!("> {}", self.query)), query_area);
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+        frame.render_widget(Paragraph::new(HELP), help_area);
+    }
+}
+
+fn run_picker(
+    terminal: &mut Terminal<CrosstermBackend<Stderr>>,
+    picker: &mut Picker,
+) -> io::Result<Action> {
+    loop {
+        terminal.draw(|frame| picker.draw(frame))?;
+
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match picker.handle_key(key) {
+                Action::Continue => {}
+                action => return Ok(action),
+            }
+        }
+    }
+}
+
+/// Let the user pick among `labels` interactively. The interface is drawn on
+/// stderr so stdout stays free for the result. Returns the indices of the
+/// chosen labels, or `None` when the user cancelled.
+pub fn pick(labels: Vec<String>) -> Result<Option<Vec<usize>>, String> {
+    let mut picker = Picker::new(labels);
+
+    let setup = || -> io::Result<Terminal<CrosstermBackend<Stderr>>> {
+        enable_raw_mode()?;
+        execute!(io::stderr(), EnterAlternateScreen)?;
+        Terminal::new(CrosstermBackend::new(io::stderr()))
+    };
+
+    let result = setup().and_then(|mut terminal| run_picker(&mut terminal, &mut picker));
+
+    let _ = execute!(io::stderr(), LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+
+    match result.map_err(|e| format!("can't run picker: {}", e))? {
+        Action::Accept => Ok(Some(picker.chosen())),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picker(n: usize) -> Picker {
+        Picker::new((0..n).map(|i| format!("file{}", i)).collect())
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_navigation_is_clamped() {
+        let mut p = picker(3);
+        p.handle_key(key(KeyCode::Up));
+        assert_eq!(p.cursor(), Some(0));
+        p.handle_key(key(KeyCode::PageDown));
+        assert_eq!(p.cursor(), Some(2));
+        p.handle_key(key(KeyCode::Up));
+        assert_eq!(p.cursor(), Some(1));
+    }
+
+    #[test]
+    fn test_chosen_defaults_to_cursor() {
+        let mut p = picker(3);
+        p.handle_key(key(KeyCode::Down));
+        assert_eq!(p.chosen(), vec![1]);
+    }
+
+    #[test]
+    fn test_multi_select() {
+        let mut p = picker(4);
+        p.handle_key(key(KeyCode::Tab));
+        p.handle_key(key(KeyCode::Down));
+        p.handle_key(key(KeyCode::Tab));
+        assert_eq!(p.chosen(), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_toggle_all() {
+        let mut p = picker(3);
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        p.handle_key(ctrl_a);
+        assert_eq!(p.chosen(), vec![0, 1, 2]);
+        p.handle_key(ctrl_a);
+        assert_eq!(p.chosen(), vec![0]);
+    }
+
+    #[test]
+    fn test_accept_and_cancel() {
+        let mut p = picker(1);
+        assert_eq!(p.handle_key(key(KeyCode::Enter)), Action::Accept);
+        assert_eq!(p.handle_key(key(KeyCode::Esc)), Action::Cancel);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(p.handle_key(ctrl_c), Action::Cancel);
+    }
+
+    #[test]
+    fn test_empty_picker() {
+        let mut p = picker(0);
+        p.handle_key(key(KeyCode::Down));
+        p.handle_key(key(KeyCode::Tab));
+        assert!(p.chosen().is_empty());
+    }
+
+    fn type_query(p: &mut Picker, query: &str) {
+        for c in query.chars() {
+            p.handle_key(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_filter() {
+        let mut p = Picker::new(vec![
+            "notes/meeting.md".to_string(),
+            "src/main.rs".to_string(),
+            "docs/manual.md".to_string(),
+        ]);
+        type_query(&mut p, "manmd");
+        assert_eq!(p.visible, vec![2]);
+        assert_eq!(p.chosen(), vec![2]);
+
+        p.handle_key(key(KeyCode::Backspace));
+        p.handle_key(key(KeyCode::Backspace));
+        assert_eq!(p.query, "man");
+        assert!(p.visible.contains(&1));
+    }
+
+    #[test]
+    fn test_filter_keeps_selection() {
+        let mut p = picker(12);
+        p.handle_key(key(KeyCode::Tab));
+        type_query(&mut p, "file11");
+        assert_eq!(p.visible, vec![11]);
+        p.handle_key(key(KeyCode::Tab));
+        type_query(&mut p, "x");
+        assert!(p.visible.is_empty());
+        assert_eq!(p.chosen(), vec![0, 11]);
+    }
+
+    #[test]
+    fn test_toggle_all_visible_only() {
+        let mut p = picker(12);
+        type_query(&mut p, "file1");
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        p.handle_key(ctrl_a);
+        assert_eq!(p.chosen(), vec![1, 10, 11]);
+    }
+}
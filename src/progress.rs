@@ -0,0 +1,96 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+use crate::{i18n, status};
+
+/// How long a walk runs before its progress is shown.
+const THRESHOLD: Duration = Duration::from_millis(500);
+
+/// Entries between updates of the progress message.
+const UPDATE_EVERY: usize = 256;
+
+/// Progress of a directory walk, shown on stderr once the walk has taken
+/// longer than `THRESHOLD`. Cleared when dropped, at the end of the walk.
+pub struct Scan {
+    bar: ProgressBar,
+    started: Instant,
+    shown: bool,
+    dirs: usize,
+    files: usize,
+}
+
+impl Scan {
+    /// A scan for a new walk, or `None` when stderr isn't a terminal or
+    /// output is quiet.
+    pub fn start() -> Option<Scan> {
+        if !std::io::stderr().is_terminal() || status::is_quiet() {
+            return None;
+        }
+        let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::hidden());
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} {elapsed} {msg}")
+                .expect("progress template is valid"),
+        );
+        Some(Scan {
+            bar,
+            started: Instant::now(),
+            shown: false,
+            dirs: 0,
+            files: 0,
+        })
+    }
+
+    /// Count an entry of the walk, which has had `matches` matching files
+    /// so far.
+    pub fn entry(&mut self, is_dir: bool, matches: usize) {
+        if is_dir {
+            self.dirs += 1;
+        } else {
+            self.files += 1;
+        }
+        if !(self.dirs + self.files).is_multiple_of(UPDATE_EVERY) {
+            return;
+        }
+
+        if !self.shown && self.started.elapsed() >= THRESHOLD {
+            self.bar.set_draw_target(ProgressDrawTarget::stderr());
+            self.bar.enable_steady_tick(Duration::from_millis(100));
+            self.shown = true;
+        }
+        if self.shown {
+            self.bar
+                .set_message(message(self.dirs, self.files, matches));
+        }
+    }
+}
+
+impl Drop for Scan {
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+fn message(dirs: usize, files: usize, matches: usize) -> String {
+    i18n::trf(
+        "{}, {} scanned, {}",
+        &[
+            &i18n::trn("{} directories", dirs, &[&dirs]),
+            &i18n::trn("{} files", files, &[&files]),
+            &i18n::trn("{} matches", matches, &[&matches]),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message() {
+        assert_eq!(
+            message(3, 120, 7),
+            "3 directories, 120 files scanned, 7 matches"
+        );
+    }
+}
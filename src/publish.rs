@@ -0,0 +1,152 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::{file_url, path_text, toc};
+
+/// `text` with the XML special characters escaped.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn timestamp(time: SystemTime) -> String {
+    let time: DateTime<Utc> = time.into();
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Title of a feed entry: the first top-level heading of a markdown file,
+/// or its file name.
+fn entry_title(path: &Path) -> String {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| {
+            toc::headings(&content)
+                .into_iter()
+                .find(|(level, _)| *level == 1)
+                .map(|(_, text)| text)
+        })
+        .unwrap_or_else(|| path.file_name().map(path_text).unwrap_or_default())
+}
+
+/// Atom feed with an entry per file, newest first, linking to the files
+/// published under `base_url`.
+pub fn atom(title: &str, root: &Path, files: &[PathBuf], base_url: &str) -> String {
+    let times: Vec<SystemTime> = files
+        .iter()
+        .map(|file| modified(file).unwrap_or(SystemTime::UNIX_EPOCH))
+        .collect();
+    let updated = times.iter().max().copied().unwrap_or_else(SystemTime::now);
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    out.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    out.push_str(&format!("  <id>{}</id>\n", escape(base_url)));
+    out.push_str(&format!("  <link href=\"{}\"/>\n", escape(base_url)));
+    out.push_str(&format!("  <updated>{}</updated>\n", timestamp(updated)));
+    out.push_str("  <author><name>mdfiles</name></author>\n");
+    out.push_str(&format!(
+        "  <generator version=\"{}\">mdfiles</generator>\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    for (file, time) in files.iter().zip(&times).rev() {
+        let url = escape(&file_url(base_url, root, file));
+        out.push_str("  <entry>\n");
+        out.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape(&entry_title(file))
+        ));
+        out.push_str(&format!("    <id>{}</id>\n", url));
+        out.push_str(&format!("    <link href=\"{}\"/>\n", url));
+        out.push_str(&format!("    <updated>{}</updated>\n", timestamp(*time)));
+        out.push_str("  </entry>\n");
+    }
+
+    out.push_str("</feed>\n");
+    out
+}
+
+/// Sitemap listing the files published under `base_url`, with their
+/// modification times.
+pub fn sitemap(root: &Path, files: &[PathBuf], base_url: &str) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+
+    for file in files {
+        out.push_str("  <url>\n");
+        out.push_str(&format!(
+            "    <loc>{}</loc>\n",
+            escape(&file_url(base_url, root, file))
+        ));
+        if let Some(time) = modified(file) {
+            out.push_str(&format!("    <lastmod>{}</lastmod>\n", timestamp(time)));
+        }
+        out.push_str("  </url>\n");
+    }
+
+    out.push_str("</urlset>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<a href=\"x\">R&D's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;R&amp;D&apos;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_entry_title() {
+        let dir = TempDir::new().unwrap();
+        let titled = dir.path().join("a.md");
+        let untitled = dir.path().join("b.md");
+        fs::write(&titled, "intro\n## Sub\n# Big & Bold\n").unwrap();
+        fs::write(&untitled, "## Only a subheading\n").unwrap();
+
+        assert_eq!(entry_title(&titled), "Big & Bold");
+        assert_eq!(entry_title(&untitled), "b.md");
+    }
+
+    #[test]
+    fn test_atom() {
+        let root = TempDir::new().unwrap();
+        let files = vec![root.path().join("old.md"), root.path().join("new.md")];
+        fs::write(&files[0], "# Old").unwrap();
+        fs::write(&files[1], "# New").unwrap();
+
+        let feed = atom("Files", root.path(), &files, "https://example.com/notes/");
+        assert!(feed.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed"));
+        assert!(feed.contains("<id>https://example.com/notes/</id>"));
+        assert!(feed.contains("<link href=\"https://example.com/notes/new.md\"/>"));
+        assert!(feed.find("<title>New</title>") < feed.find("<title>Old</title>"));
+        assert_eq!(feed.matches("<entry>").count(), 2);
+        assert!(feed.ends_with("</feed>\n"));
+    }
+
+    #[test]
+    fn test_sitemap() {
+        let root = TempDir::new().unwrap();
+        let file = root.path().join("R&D.md");
+        fs::write(&file, "").unwrap();
+
+        let map = sitemap(root.path(), &[file], "https://example.com");
+        assert!(map.contains("<loc>https://example.com/R%26D.md</loc>"));
+        assert!(map.contains("<lastmod>"));
+        assert!(map.ends_with("</url>\n</urlset>\n"));
+    }
+}
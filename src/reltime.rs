@@ -0,0 +1,68 @@
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use std::time::SystemTime;
+
+use crate::i18n;
+
+/// How file times are shown in entries.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ShowTime {
+    Iso,
+    Relative,
+}
+
+/// `time` relative to `now`, in the largest whole unit: "just now",
+/// "5 minutes ago", "3 hours ago", "2 weeks ago"...
+pub fn relative(time: SystemTime, now: SystemTime) -> String {
+    const UNITS: [(u64, &str, &str); 6] = [
+        (365 * 86400, "1 year ago", "{} years ago"),
+        (30 * 86400, "1 month ago", "{} months ago"),
+        (7 * 86400, "1 week ago", "{} weeks ago"),
+        (86400, "1 day ago", "{} days ago"),
+        (3600, "1 hour ago", "{} hours ago"),
+        (60, "1 minute ago", "{} minutes ago"),
+    ];
+
+    let seconds = now
+        .duration_since(time)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    UNITS
+        .iter()
+        .find(|(length, _, _)| seconds >= *length)
+        .map(|&(length, one, many)| match seconds / length {
+            1 => i18n::tr(one).to_string(),
+            count => i18n::trf(many, &[&count]),
+        })
+        .unwrap_or_else(|| i18n::tr("just now").to_string())
+}
+
+/// `time` as shown with `--show-time`.
+pub fn format(time: SystemTime, show: ShowTime) -> String {
+    match show {
+        ShowTime::Iso => {
+            let time: DateTime<Local> = time.into();
+            time.format("%Y-%m-%d %H:%M").to_string()
+        }
+        ShowTime::Relative => relative(time, SystemTime::now()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_relative() {
+        let now = SystemTime::now();
+        let ago = |seconds| relative(now - Duration::from_secs(seconds), now);
+        assert_eq!(ago(10), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(3 * 3600 + 59), "3 hours ago");
+        assert_eq!(ago(86400), "1 day ago");
+        assert_eq!(ago(15 * 86400), "2 weeks ago");
+        assert_eq!(ago(400 * 86400), "1 year ago");
+        assert_eq!(relative(now + Duration::from_secs(60), now), "just now");
+    }
+}
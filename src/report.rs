@@ -0,0 +1,568 @@
+use chrono::{Datelike, Days, NaiveDate};
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::forge::Commit;
+use crate::frontmatter::Generated;
+use crate::{LinkStyle, TimeField, atomic, dryrun, file_time, i18n, local_date, relative_name};
+
+pub const DEFAULT_NAME: &str = "{date}.md";
+
+/// Expand the `{date}`, `{year}`, `{month}` and `{day}` placeholders of a
+/// report file name template.
+fn report_name(template: &str, date: NaiveDate) -> String {
+    template
+        .replace("{date}", &date.format("%Y-%m-%d").to_string())
+        .replace("{year}", &format!("{:04}", date.year()))
+        .replace("{month}", &format!("{:02}", date.month()))
+        .replace("{day}", &format!("{:02}", date.day()))
+}
+
+/// How `--commits` lists the commits of a report.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum CommitLayout {
+    /// Along with the files, in the order they happened.
+    #[default]
+    Interleave,
+    /// Under each file, those that touched it.
+    PerFile,
+}
+
+/// What reports list: files, and for `--commits` the commits they're
+/// shown with.
+pub struct Activity<'a> {
+    pub files: &'a [PathBuf],
+    pub commits: &'a [Commit],
+    pub layout: CommitLayout,
+    /// The timestamp the files are dated and ordered by.
+    pub field: TimeField,
+}
+
+impl Activity<'_> {
+    fn is_empty(&self) -> bool {
+        self.files.is_empty() && (self.commits.is_empty() || self.layout == CommitLayout::PerFile)
+    }
+
+    /// The report's entries: the files in their order, or with commits, the
+    /// files and commits together in the order they happened, or each file
+    /// followed by the commits that touched it.
+    fn render(&self, style: &LinkStyle) -> String {
+        if self.commits.is_empty() || self.layout == CommitLayout::PerFile {
+            return self
+                .files
+                .iter()
+                .map(|file| {
+                    let mut entry = style.format(file) + "\n";
+                    for commit in self.commits.iter().filter(|commit| commit.touches(file)) {
+                        entry.push_str(&commit.provenance());
+                        entry.push('\n');
+                    }
+                    entry
+                })
+                .collect();
+        }
+        let mut entries: Vec<(Option<SystemTime>, String)> = self
+            .files
+            .iter()
+            .map(|file| (file_time(file, self.field), style.format(file)))
+            .chain(
+                self.commits
+                    .iter()
+                    .map(|commit| (Some(commit.time.into()), commit.entry())),
+            )
+            .collect();
+        // Stable, so files without a time keep their place at the start
+        entries.sort_by_key(|(time, _)| *time);
+        entries.into_iter().map(|(_, entry)| entry + "\n").collect()
+    }
+}
+
+/// Write the report of `activity` on `date` into `out_dir`, or with
+/// `options.dry_run` only print the change as a diff. The report starts with
+/// `frontmatter` when given. Nothing is written when there's no activity;
+/// returns the path of the report otherwise.
+pub fn write_report(
+    out_dir: &Path,
+    name: &str,
+    date: NaiveDate,
+    activity: &Activity,
+    style: &LinkStyle,
+    frontmatter: Option<&Generated>,
+    options: atomic::Options,
+) -> Result<Option<PathBuf>, String> {
+    if activity.is_empty() {
+        return Ok(None);
+    }
+
+    let path = out_dir.join(report_name(name, date));
+    let mut content = frontmatter.map_or_else(String::new, |f| f.render(activity.files.len()));
+    content.push_str(&activity.render(style));
+    if options.dry_run {
+        let old = fs::read_to_string(&path).ok();
+        print!("{}", dryrun::diff(&path, old.as_deref(), &content));
+        return Ok(Some(path));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| i18n::trf("can't create '{}': {}", &[&parent.display(), &e]))?;
+    }
+    atomic::write(&path, content, options.backup)
+        .map_err(|e| i18n::trf("can't write '{}': {}", &[&path.display(), &e]))?;
+
+    Ok(Some(path))
+}
+
+/// Write a report per day into `out_dir` for the days of `activity`, going
+/// by the files' timestamps and the commits' author times, like
+/// `write_report`. Returns the paths of the reports, oldest first.
+pub fn write_reports(
+    out_dir: &Path,
+    name: &str,
+    activity: &Activity,
+    style: &LinkStyle,
+    frontmatter: Option<&Generated>,
+    options: atomic::Options,
+) -> Result<Vec<PathBuf>, String> {
+    let mut days: BTreeMap<NaiveDate, (Vec<PathBuf>, Vec<Commit>)> = BTreeMap::new();
+    for file in activity.files {
+        if let Some(time) = file_time(file, activity.field) {
+            days.entry(local_date(time))
+                .or_default()
+                .0
+                .push(file.clone());
+        }
+    }
+    for commit in activity.commits {
+        days.entry(commit.time.date_naive())
+            .or_default()
+            .1
+            .push(commit.clone());
+    }
+    let mut names = BTreeMap::new();
+    for date in days.keys() {
+        if let Some(other) = names.insert(report_name(name, *date), date) {
+            return Err(format!(
+                "report name '{}' gives {} and {} the same file, use {{date}} or {{day}}",
+                name, other, date
+            ));
+        }
+    }
+
+    let mut paths = Vec::new();
+    for (date, (files, commits)) in &days {
+        let frontmatter = frontmatter.map(|f| f.on(*date));
+        let day = Activity {
+            files,
+            commits,
+            layout: activity.layout,
+            field: activity.field,
+        };
+        paths.extend(write_report(
+            out_dir,
+            name,
+            *date,
+            &day,
+            style,
+            frontmatter.as_ref(),
+            options,
+        )?);
+    }
+    Ok(paths)
+}
+
+/// Which old reports to keep, for `--keep`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Keep {
+    /// The newest reports.
+    Reports(usize),
+    /// The reports of the last days, up to the report date.
+    Days(u64),
+}
+
+/// Parse `--keep`: a number of reports (`30`) or days (`30d`).
+pub fn parse_keep(text: &str) -> Result<Keep, String> {
+    let invalid = || format!("invalid count '{}', expected N or Nd", text);
+    let keep = match text.strip_suffix('d') {
+        Some(days) => Keep::Days(days.parse().map_err(|_| invalid())?),
+        None => Keep::Reports(text.parse().map_err(|_| invalid())?),
+    };
+    if matches!(keep, Keep::Reports(0) | Keep::Days(0)) {
+        return Err("must keep at least one".to_string());
+    }
+    Ok(keep)
+}
+
+/// The date of the report at `name` (relative to the output directory, with
+/// forward slashes) when it fits the report name `template`. Parts of the
+/// date left out of the template are taken to be the first.
+fn name_date(template: &str, name: &str) -> Option<NaiveDate> {
+    let (mut year, mut month, mut day) = (None, 1, 1);
+    let mut rest = name;
+    let mut template = template;
+    while !template.is_empty() {
+        let number = |rest: &str, len: usize| -> Option<u32> {
+            let digits = rest.get(..len)?;
+            digits
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| digits.parse().ok())?
+        };
+        if let Some(after) = template.strip_prefix("{date}") {
+            let date = NaiveDate::parse_from_str(rest.get(..10)?, "%Y-%m-%d").ok()?;
+            (year, month, day) = (Some(date.year()), date.month(), date.day());
+            (template, rest) = (after, &rest[10..]);
+        } else if let Some(after) = template.strip_prefix("{year}") {
+            year = Some(number(rest, 4)? as i32);
+            (template, rest) = (after, &rest[4..]);
+        } else if let Some(after) = template.strip_prefix("{month}") {
+            month = number(rest, 2)?;
+            (template, rest) = (after, &rest[2..]);
+        } else if let Some(after) = template.strip_prefix("{day}") {
+            day = number(rest, 2)?;
+            (template, rest) = (after, &rest[2..]);
+        } else {
+            let c = template.chars().next()?;
+            rest = rest.strip_prefix(c)?;
+            template = &template[c.len_utf8()..];
+        }
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year?, month, day)
+}
+
+/// Remove the reports named after `name` in `out_dir` beyond those to
+/// `keep` as of `date`, or with `dry_run` only print which. Returns the
+/// removed reports.
+pub fn prune(
+    out_dir: &Path,
+    name: &str,
+    keep: Keep,
+    date: NaiveDate,
+    dry_run: bool,
+) -> Result<Vec<PathBuf>, String> {
+    let mut reports: Vec<(NaiveDate, PathBuf)> = WalkDir::new(out_dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let date = name_date(name, &relative_name(out_dir, entry.path()))?;
+            Some((date, entry.into_path()))
+        })
+        .collect();
+    // Newest first
+    reports.sort_by(|a, b| b.cmp(a));
+
+    let old: Vec<PathBuf> = match keep {
+        Keep::Reports(count) => reports
+            .into_iter()
+            .skip(count)
+            .map(|(_, path)| path)
+            .collect(),
+        Keep::Days(days) => {
+            let first = date.checked_sub_days(Days::new(days - 1)).unwrap_or(date);
+            reports
+                .into_iter()
+                .filter(|(date, _)| *date < first)
+                .map(|(_, path)| path)
+                .collect()
+        }
+    };
+    for path in &old {
+        if dry_run {
+            println!("{}", i18n::trf("would remove {}", &[&path.display()]));
+        } else {
+            fs::remove_file(path)
+                .map_err(|e| format!("can't remove '{}': {}", path.display(), e))?;
+        }
+    }
+    Ok(old)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 4).unwrap()
+    }
+
+    #[test]
+    fn test_report_name_default() {
+        assert_eq!(report_name(DEFAULT_NAME, date()), "2025-03-04.md");
+    }
+
+    #[test]
+    fn test_report_name_parts() {
+        assert_eq!(
+            report_name("{year}/{month}/activity-{day}.md", date()),
+            "2025/03/activity-04.md"
+        );
+    }
+
+    #[test]
+    fn test_write_report() {
+        let out_dir = TempDir::new().unwrap();
+        let files = vec![PathBuf::from("src/main.rs")];
+        let activity = Activity {
+            files: &files,
+            commits: &[],
+            layout: CommitLayout::Interleave,
+            field: TimeField::Modified,
+        };
+        let path = write_report(
+            out_dir.path(),
+            DEFAULT_NAME,
+            date(),
+            &activity,
+            &LinkStyle::default(),
+            None,
+            atomic::Options::default(),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(path, out_dir.path().join("2025-03-04.md"));
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "- [main.rs](src/main.rs)\n"
+        );
+    }
+
+    #[test]
+    fn test_write_report_skips_empty() {
+        let out_dir = TempDir::new().unwrap();
+        let activity = Activity {
+            files: &[],
+            commits: &[],
+            layout: CommitLayout::Interleave,
+            field: TimeField::Modified,
+        };
+        let result = write_report(
+            out_dir.path(),
+            DEFAULT_NAME,
+            date(),
+            &activity,
+            &LinkStyle::default(),
+            None,
+            atomic::Options::default(),
+        )
+        .unwrap();
+        assert!(result.is_none());
+        assert_eq!(fs::read_dir(out_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_write_reports() {
+        let dir = TempDir::new().unwrap();
+        let out_dir = dir.path().join("out");
+        let mut files = Vec::new();
+        for (name, day) in [("a.md", 4), ("b.md", 6), ("c.md", 4)] {
+            let path = dir.path().join(name);
+            let time = NaiveDate::from_ymd_opt(2025, 3, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_local_timezone(chrono::Local)
+                .unwrap();
+            fs::File::create(&path)
+                .unwrap()
+                .set_modified(time.into())
+                .unwrap();
+            files.push(path);
+        }
+
+        let style = LinkStyle::default();
+        let activity = Activity {
+            files: &files,
+            commits: &[],
+            layout: CommitLayout::Interleave,
+            field: TimeField::Modified,
+        };
+        let paths = write_reports(
+            &out_dir,
+            DEFAULT_NAME,
+            &activity,
+            &style,
+            None,
+            atomic::Options::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            paths,
+            [out_dir.join("2025-03-04.md"), out_dir.join("2025-03-06.md")]
+        );
+        assert_eq!(fs::read_to_string(&paths[0]).unwrap().lines().count(), 2);
+        assert_eq!(fs::read_dir(&out_dir).unwrap().count(), 2);
+
+        let err = write_reports(
+            &out_dir,
+            "{month}.md",
+            &activity,
+            &style,
+            None,
+            atomic::Options::default(),
+        )
+        .unwrap_err();
+        assert!(err.contains("the same file"));
+
+        // A commit on a day without files gets a report too
+        let commits = [commit("abc1234", "Add docs", 5, 9)];
+        let activity = Activity {
+            commits: &commits,
+            ..activity
+        };
+        let paths = write_reports(
+            &out_dir,
+            DEFAULT_NAME,
+            &activity,
+            &style,
+            None,
+            atomic::Options::default(),
+        )
+        .unwrap();
+        assert_eq!(paths.len(), 3);
+        assert_eq!(
+            fs::read_to_string(&paths[1]).unwrap(),
+            "- Add docs (`abc1234`)\n"
+        );
+    }
+
+    fn commit(short: &str, subject: &str, day: u32, hour: u32) -> Commit {
+        Commit {
+            short: short.to_string(),
+            subject: subject.to_string(),
+            time: NaiveDate::from_ymd_opt(2025, 3, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+                .and_local_timezone(chrono::Local)
+                .unwrap(),
+            files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_activity_render() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.path().join("a.md");
+        let noon = NaiveDate::from_ymd_opt(2025, 3, 4)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap();
+        fs::File::create(&note)
+            .unwrap()
+            .set_modified(noon.into())
+            .unwrap();
+        let files = [note];
+        let commits = [
+            commit("abc1234", "Start", 4, 9),
+            commit("def5678", "Finish", 4, 17),
+        ];
+        let style = LinkStyle::default();
+        let activity = Activity {
+            files: &files,
+            commits: &commits,
+            layout: CommitLayout::Interleave,
+            field: TimeField::Modified,
+        };
+        assert_eq!(
+            activity.render(&style),
+            format!(
+                "- Start (`abc1234`)\n- [a.md]({})\n- Finish (`def5678`)\n",
+                style.target(&files[0])
+            )
+        );
+    }
+
+    #[test]
+    fn test_activity_render_per_file() {
+        let dir = TempDir::new().unwrap();
+        let files = [dir.path().join("a.md"), dir.path().join("b.md")];
+        for file in &files {
+            fs::write(file, "").unwrap();
+        }
+        let mut fix = commit("abc1234", "Fix a", 4, 9);
+        fix.files = vec![fs::canonicalize(&files[0]).unwrap()];
+        let commits = [fix, commit("def5678", "Unrelated", 4, 17)];
+        let style = LinkStyle::default();
+        let activity = Activity {
+            files: &files,
+            commits: &commits,
+            layout: CommitLayout::PerFile,
+            field: TimeField::Modified,
+        };
+        assert_eq!(
+            activity.render(&style),
+            format!(
+                "- [a.md]({})\n  - abc1234 — Fix a\n- [b.md]({})\n",
+                style.target(&files[0]),
+                style.target(&files[1])
+            )
+        );
+
+        let activity = Activity {
+            files: &[],
+            ..activity
+        };
+        assert!(activity.is_empty());
+    }
+
+    #[test]
+    fn test_parse_keep() {
+        assert_eq!(parse_keep("30"), Ok(Keep::Reports(30)));
+        assert_eq!(parse_keep("7d"), Ok(Keep::Days(7)));
+        assert!(parse_keep("0").is_err());
+        assert!(parse_keep("week").is_err());
+    }
+
+    #[test]
+    fn test_name_date() {
+        let date = |month, day| NaiveDate::from_ymd_opt(2025, month, day);
+        assert_eq!(name_date(DEFAULT_NAME, "2025-03-04.md"), date(3, 4));
+        assert_eq!(
+            name_date("{year}/{month}/activity-{day}.md", "2025/03/activity-04.md"),
+            date(3, 4)
+        );
+        assert_eq!(name_date("{year}-{month}.md", "2025-03.md"), date(3, 1));
+        assert_eq!(name_date(DEFAULT_NAME, "2025-03-04.md.bak"), None);
+        assert_eq!(name_date(DEFAULT_NAME, "notes.md"), None);
+        assert_eq!(name_date(DEFAULT_NAME, "2025-02-30.md"), None);
+    }
+
+    #[test]
+    fn test_prune() {
+        let out_dir = TempDir::new().unwrap();
+        for day in 1..=5 {
+            fs::write(out_dir.path().join(format!("2025-03-0{}.md", day)), "").unwrap();
+        }
+        fs::write(out_dir.path().join("README.md"), "").unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 3, 5).unwrap();
+
+        let removed = prune(out_dir.path(), DEFAULT_NAME, Keep::Days(3), today, true).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(fs::read_dir(out_dir.path()).unwrap().count(), 6);
+
+        prune(out_dir.path(), DEFAULT_NAME, Keep::Days(3), today, false).unwrap();
+        assert!(!out_dir.path().join("2025-03-02.md").exists());
+        assert!(out_dir.path().join("2025-03-03.md").exists());
+
+        let removed = prune(out_dir.path(), DEFAULT_NAME, Keep::Reports(1), today, false).unwrap();
+        assert_eq!(
+            removed,
+            [
+                out_dir.path().join("2025-03-04.md"),
+                out_dir.path().join("2025-03-03.md")
+            ]
+        );
+        assert!(out_dir.path().join("README.md").exists());
+    }
+}
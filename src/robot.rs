@@ -0,0 +1,246 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::dirconfig::Scopes;
+use crate::traversal::{WalkContext, WalkOptions};
+use crate::{FileEntry, TimeField, file_times, get_date, i18n, local_date};
+
+/// One query, read as a single JSON line. Missing fields fall back to the
+/// command line options.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Query {
+    id: Option<serde_json::Value>,
+    date: Option<String>,
+    root: Option<String>,
+    suffix: Option<String>,
+    refresh: bool,
+}
+
+/// Files seen under each root, with their modification times. Kept for the
+/// life of the process so repeated queries don't walk the tree again.
+pub struct Cache<'a> {
+    field: TimeField,
+    counts: bool,
+    options: &'a WalkOptions,
+    roots: HashMap<PathBuf, Vec<(PathBuf, SystemTime)>>,
+}
+
+impl<'a> Cache<'a> {
+    /// A cache of files dated by `field`, listed with line and word
+    /// `counts` if set, found by walks with `options`.
+    pub fn new(field: TimeField, counts: bool, options: &'a WalkOptions) -> Self {
+        Cache {
+            field,
+            counts,
+            options,
+            roots: HashMap::new(),
+        }
+    }
+
+    fn files(&mut self, root: &Path, refresh: bool) -> &[(PathBuf, SystemTime)] {
+        if refresh {
+            self.roots.remove(root);
+        }
+
+        self.roots.entry(root.to_path_buf()).or_insert_with(|| {
+            let context = WalkContext::new(self.options);
+            file_times(root, self.field, &context).collect()
+        })
+    }
+}
+
+fn answer(
+    cache: &mut Cache,
+    query: &Query,
+    root: &str,
+    suffix: &str,
+    filter: &impl Fn(Vec<PathBuf>) -> Result<Vec<PathBuf>, String>,
+) -> Result<Vec<FileEntry>, String> {
+    let date = get_date(query.date.as_deref())?;
+    let root = query.root.as_deref().unwrap_or(root);
+    let suffix = query.suffix.as_deref().unwrap_or(suffix);
+
+    let counts = cache.counts;
+    let root_path = Path::new(root);
+    if !root_path.exists() {
+        return Err(i18n::trf("root directory '{}' does not exist", &[&root]));
+    }
+
+    // The configs are read again for each query, like the files on refresh
+    let scopes = Scopes::new(&cache.options.overridden);
+    let mut files: Vec<_> = cache
+        .files(root_path, query.refresh)
+        .iter()
+        .filter(|(path, _)| scopes.matches_suffix(path, suffix))
+        .filter(|(_, modified)| local_date(*modified) == date)
+        .collect();
+    files.sort_by_key(|(_, modified)| *modified);
+    let files = filter(files.into_iter().map(|(path, _)| path.clone()).collect())?;
+
+    Ok(files
+        .iter()
+        .map(|path| FileEntry::new(path, counts))
+        .collect())
+}
+
+/// Read JSON queries from `input`, one per line, and write one JSON result
+/// line per query to `output`, finding files through `cache`. Found files go
+/// through `filter` before they're listed.
+pub fn run(
+    root: &str,
+    suffix: &str,
+    mut cache: Cache,
+    filter: impl Fn(Vec<PathBuf>) -> Result<Vec<PathBuf>, String>,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<(), String> {
+    for line in input.lines() {
+        let line = line.map_err(|e| format!("can't read query: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match serde_json::from_str::<Query>(&line) {
+            Ok(query) => match answer(&mut cache, &query, root, suffix, &filter) {
+                Ok(files) => serde_json::json!({ "id": query.id, "files": files }),
+                Err(e) => serde_json::json!({ "id": query.id, "error": e }),
+            },
+            Err(e) => serde_json::json!({ "id": null, "error": format!("bad query: {}", e) }),
+        };
+
+        writeln!(output, "{}", reply)
+            .and_then(|_| output.flush())
+            .map_err(|e| format!("can't write result: {}", e))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    fn replies(root: &Path, input: &str) -> Vec<serde_json::Value> {
+        let mut output = Vec::new();
+        let options = WalkOptions::default();
+        run(
+            root.to_str().unwrap(),
+            ".go",
+            Cache::new(TimeField::Modified, false, &options),
+            Ok,
+            input.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_robot_defaults_from_options() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("a.go")).unwrap();
+        File::create(temp_dir.path().join("b.txt")).unwrap();
+
+        let result = replies(temp_dir.path(), "{\"id\": 1}\n");
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0]["id"], 1);
+        assert_eq!(result[0]["files"][0]["name"], "a.go");
+        assert_eq!(result[0]["files"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_robot_query_overrides() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("b.txt")).unwrap();
+
+        let input = "{\"suffix\": \".txt\"}\n\n{\"suffix\": \".txt\", \"date\": \"2020-01-01\"}\n";
+        let result = replies(temp_dir.path(), input);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0]["files"].as_array().unwrap().len(), 1);
+        assert_eq!(result[1]["files"].as_array().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_robot_errors_keep_going() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = "not json\n{\"date\": \"bad\"}\n{\"root\": \"/nonexistent/path\"}\n{}\n";
+        let result = replies(temp_dir.path(), input);
+        assert_eq!(result.len(), 4);
+        assert!(result[0]["error"].as_str().unwrap().contains("bad query"));
+        assert!(
+            result[1]["error"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid date")
+        );
+        assert!(
+            result[2]["error"]
+                .as_str()
+                .unwrap()
+                .contains("does not exist")
+        );
+        assert!(result[3]["files"].is_array());
+    }
+
+    #[test]
+    fn test_robot_cache_and_refresh() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("a.go")).unwrap();
+
+        let options = WalkOptions::default();
+        let mut cache = Cache::new(TimeField::Modified, false, &options);
+        let root = temp_dir.path().to_str().unwrap();
+        let query = Query::default();
+        assert_eq!(
+            answer(&mut cache, &query, root, ".go", &Ok).unwrap().len(),
+            1
+        );
+
+        File::create(temp_dir.path().join("b.go")).unwrap();
+        assert_eq!(
+            answer(&mut cache, &query, root, ".go", &Ok).unwrap().len(),
+            1
+        );
+
+        let query = Query {
+            refresh: true,
+            ..Query::default()
+        };
+        assert_eq!(
+            answer(&mut cache, &query, root, ".go", &Ok).unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_robot_filters_files() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("a.go")).unwrap();
+        File::create(temp_dir.path().join("b.go")).unwrap();
+
+        let options = WalkOptions::default();
+        let mut cache = Cache::new(TimeField::Modified, false, &options);
+        let root = temp_dir.path().to_str().unwrap();
+        let query = Query::default();
+        let last = |mut files: Vec<PathBuf>| -> Result<Vec<PathBuf>, String> {
+            files.sort();
+            Ok(files.split_off(1))
+        };
+        let files = answer(&mut cache, &query, root, ".go", &last).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "b.go");
+
+        let failing = |_| Err("can't read 'x': gone".to_string());
+        assert!(answer(&mut cache, &query, root, ".go", &failing).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use fastrand::Rng;
+
+/// Up to `count` of `items` picked uniformly at random in a single pass
+/// (reservoir sampling), in no particular order.
+pub fn reservoir<T>(items: impl IntoIterator<Item = T>, count: usize, rng: &mut Rng) -> Vec<T> {
+    let mut sample = Vec::with_capacity(count);
+    for (seen, item) in items.into_iter().enumerate() {
+        if seen < count {
+            sample.push(item);
+        } else {
+            let slot = rng.usize(..=seen);
+            if slot < count {
+                sample[slot] = item;
+            }
+        }
+    }
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservoir() {
+        let mut rng = Rng::with_seed(7);
+        assert_eq!(reservoir(0..3, 5, &mut rng).len(), 3);
+        assert!(reservoir(0..3, 0, &mut rng).is_empty());
+
+        let mut sample = reservoir(0..100, 10, &mut rng);
+        sample.sort_unstable();
+        sample.dedup();
+        assert_eq!(sample.len(), 10);
+
+        // Every item is about as likely to be picked
+        let mut picked = [0; 10];
+        for _ in 0..10_000 {
+            for item in reservoir(0..10, 3, &mut rng) {
+                picked[item] += 1;
+            }
+        }
+        assert!(picked.iter().all(|&count| (2_700..3_300).contains(&count)));
+    }
+}
@@ -0,0 +1,326 @@
+use std::path::{Path, PathBuf};
+use tiny_http::{Header, Server};
+
+use crate::index::Period;
+use crate::traversal::WalkOptions;
+use crate::{
+    FileEntry, LinkStyle, TimeField, errors, find_files, get_date, i18n, markdown, status,
+};
+
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Reply {
+    fn error(status: u16, message: &str) -> Self {
+        let body = serde_json::json!({ "error": message }).to_string();
+        Reply {
+            status,
+            content_type: "application/json",
+            body,
+        }
+    }
+}
+
+fn percent_decode(s: &str) -> String {
+    markdown::percent_decode(&s.replace('+', " "))
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .find(|(key, _)| percent_decode(key) == name)
+        .map(|(_, value)| percent_decode(value))
+}
+
+/// Search options shared by all requests.
+pub struct Defaults<'a> {
+    pub root: &'a Path,
+    pub suffix: &'a str,
+    pub field: TimeField,
+    /// Times to match in requests without a `date` (for `--after` and
+    /// `--before`), instead of today.
+    pub between: Option<Period>,
+    pub options: WalkOptions,
+    pub style: &'a LinkStyle,
+    /// Filters and orders the found files, as for a listing.
+    pub filter: &'a dyn Fn(Vec<PathBuf>) -> Result<Vec<PathBuf>, String>,
+}
+
+/// Answer a request for `url` (path and query string). Errors skipped over
+/// while answering (for `--strict`) fail it, as they fail a run.
+fn handle(url: &str, defaults: &Defaults) -> Reply {
+    let reply = list(url, defaults);
+    let errors = errors::take();
+    if reply.status != 200 || errors.is_empty() {
+        return reply;
+    }
+    Reply::error(500, &errors.join("\n"))
+}
+
+fn list(url: &str, defaults: &Defaults) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    if path != "/files" {
+        return Reply::error(404, "not found");
+    }
+
+    let date = query_param(query, "date");
+    let period = match (date, defaults.between) {
+        (None, Some(between)) => between,
+        (date, _) => match get_date(date.as_deref()) {
+            Ok(d) => d.into(),
+            Err(e) => return Reply::error(400, &e),
+        },
+    };
+    let suffix = query_param(query, "suffix").unwrap_or_else(|| defaults.suffix.to_string());
+    let files = find_files(
+        defaults.root,
+        &suffix,
+        period,
+        defaults.field,
+        &defaults.options,
+    );
+    let files = match (defaults.filter)(files) {
+        Ok(files) => files,
+        Err(e) => return Reply::error(500, &e),
+    };
+
+    match query_param(query, "format").as_deref() {
+        None | Some("json") => {
+            let entries: Vec<FileEntry> = files
+                .iter()
+                .map(|file| FileEntry::new(file, defaults.style.show_wc))
+                .collect();
+            Reply {
+                status: 200,
+                content_type: "application/json",
+                body: serde_json::to_string(&entries).unwrap_or_default(),
+            }
+        }
+        Some("markdown") => {
+            let body: String = files
+                .iter()
+                .map(|file| defaults.style.format(file) + "\n")
+                .collect();
+            Reply {
+                status: 200,
+                content_type: "text/markdown; charset=utf-8",
+                body,
+            }
+        }
+        Some(other) => Reply::error(400, &format!("unknown format '{}'", other)),
+    }
+}
+
+/// Serve `GET /files` on `host:port` until the process is killed, searching
+/// with `defaults`. Found files go through its `filter` before they're
+/// listed.
+pub fn serve(host: &str, port: u16, defaults: &Defaults) -> Result<(), String> {
+    let server = Server::http((host, port))
+        .map_err(|e| format!("can't listen on {}:{}: {}", host, port, e))?;
+    status::say(i18n::trf("listening on http://{}:{}", &[&host, &port]));
+
+    for request in server.incoming_requests() {
+        let reply = if request.method() == &tiny_http::Method::Get {
+            handle(request.url(), defaults)
+        } else {
+            Reply::error(405, "method not allowed")
+        };
+
+        let header = Header::from_bytes("Content-Type", reply.content_type)
+            .expect("valid content type header");
+        let response = tiny_http::Response::from_string(reply.body)
+            .with_status_code(reply.status)
+            .with_header(header);
+        if let Err(e) = request.respond(response) {
+            let message = i18n::trf("can't send response: {}", &[&e]);
+            status::say(i18n::trf("error: {}", &[&message]));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn unfiltered(files: Vec<PathBuf>) -> Result<Vec<PathBuf>, String> {
+        Ok(files)
+    }
+
+    fn defaults<'a>(root: &'a Path, style: &'a LinkStyle) -> Defaults<'a> {
+        Defaults {
+            root,
+            suffix: ".go",
+            field: TimeField::Modified,
+            between: None,
+            options: WalkOptions::default(),
+            style,
+            filter: &unfiltered,
+        }
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b+c"), "a b c");
+        assert_eq!(percent_decode("%2Emd"), ".md");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn test_query_param() {
+        let query = "date=2025-03-14&suffix=%2Ers&flag";
+        assert_eq!(query_param(query, "date").as_deref(), Some("2025-03-14"));
+        assert_eq!(query_param(query, "suffix").as_deref(), Some(".rs"));
+        assert_eq!(query_param(query, "flag").as_deref(), Some(""));
+        assert_eq!(query_param(query, "missing"), None);
+    }
+
+    #[test]
+    fn test_handle_unknown_path() {
+        let reply = handle("/other", &defaults(Path::new("."), &LinkStyle::default()));
+        assert_eq!(reply.status, 404);
+    }
+
+    #[test]
+    fn test_handle_invalid_date() {
+        let reply = handle(
+            "/files?date=bad",
+            &defaults(Path::new("."), &LinkStyle::default()),
+        );
+        assert_eq!(reply.status, 400);
+        assert!(reply.body.contains("Invalid date format"));
+    }
+
+    #[test]
+    fn test_handle_json() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("a.txt")).unwrap();
+        File::create(temp_dir.path().join("b.go")).unwrap();
+        let style = LinkStyle::default();
+
+        let reply = handle("/files?suffix=.txt", &defaults(temp_dir.path(), &style));
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.content_type, "application/json");
+
+        let entries: serde_json::Value = serde_json::from_str(&reply.body).unwrap();
+        assert_eq!(entries.as_array().unwrap().len(), 1);
+        assert_eq!(entries[0]["name"], "a.txt");
+    }
+
+    #[test]
+    fn test_handle_markdown() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("b.go")).unwrap();
+        let style = LinkStyle {
+            show_size: true,
+            ..LinkStyle::default()
+        };
+
+        let reply = handle("/files?format=markdown", &defaults(temp_dir.path(), &style));
+        assert_eq!(reply.status, 200);
+        assert!(reply.body.starts_with("- [b.go]("));
+        assert!(reply.body.ends_with(") (0 B)\n"));
+    }
+
+    #[test]
+    fn test_handle_filters_files() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("a.go")).unwrap();
+        File::create(temp_dir.path().join("b.go")).unwrap();
+        let style = LinkStyle::default();
+
+        let last = |mut files: Vec<PathBuf>| -> Result<Vec<PathBuf>, String> {
+            files.sort();
+            files.reverse();
+            files.truncate(1);
+            Ok(files)
+        };
+        let filtered = Defaults {
+            filter: &last,
+            ..defaults(temp_dir.path(), &style)
+        };
+        let reply = handle("/files?format=markdown", &filtered);
+        assert!(reply.body.starts_with("- [b.go]("));
+        assert_eq!(reply.body.lines().count(), 1);
+
+        let failing = |_| Err("can't read 'x': gone".to_string());
+        let failed = Defaults {
+            filter: &failing,
+            ..defaults(temp_dir.path(), &style)
+        };
+        assert_eq!(handle("/files", &failed).status, 500);
+    }
+
+    #[test]
+    fn test_handle_between() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("a.go")).unwrap();
+        let style = LinkStyle::default();
+        let hour_ago = SystemTime::now() - Duration::from_secs(3600);
+
+        let since = Defaults {
+            between: Some(Period::Between {
+                after: Some(hour_ago),
+                before: None,
+            }),
+            ..defaults(temp_dir.path(), &style)
+        };
+        let reply = handle("/files?format=markdown", &since);
+        assert!(reply.body.starts_with("- [a.go]("));
+        // A date in the request wins
+        let reply = handle("/files?format=markdown&date=2020-01-01", &since);
+        assert_eq!(reply.body, "");
+
+        let until = Defaults {
+            between: Some(Period::Between {
+                after: None,
+                before: Some(hour_ago),
+            }),
+            ..defaults(temp_dir.path(), &style)
+        };
+        assert_eq!(handle("/files?format=markdown", &until).body, "");
+    }
+
+    #[test]
+    fn test_handle_strict_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("a.go")).unwrap();
+        let style = LinkStyle::default();
+
+        let first = Cell::new(true);
+        let recording = |files| {
+            if first.replace(false) {
+                errors::record("x: gone".to_string());
+            }
+            Ok(files)
+        };
+        let strict = Defaults {
+            filter: &recording,
+            ..defaults(temp_dir.path(), &style)
+        };
+        errors::collect();
+        let reply = handle("/files", &strict);
+        assert_eq!(reply.status, 500);
+        assert!(reply.body.contains("x: gone"));
+        // Each request reports only its own errors
+        assert_eq!(handle("/files", &strict).status, 200);
+    }
+
+    #[test]
+    fn test_handle_unknown_format() {
+        let reply = handle(
+            "/files?format=xml",
+            &defaults(Path::new("."), &LinkStyle::default()),
+        );
+        assert_eq!(reply.status, 400);
+    }
+}
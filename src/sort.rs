@@ -0,0 +1,197 @@
+use clap::ValueEnum;
+use icu_collator::options::CollatorOptions;
+use icu_collator::preferences::CollationNumericOrdering;
+use icu_collator::{Collator, CollatorPreferences};
+use icu_locale_core::Locale;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use crate::path_text;
+
+/// Order of the listed files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum SortKey {
+    #[default]
+    Time,
+    Name,
+    Natural,
+}
+
+/// Split `text` into runs of ASCII digits and runs of other characters.
+fn chunks(text: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut in_digits = None;
+    for (i, c) in text.char_indices() {
+        let digit = c.is_ascii_digit();
+        if in_digits.is_some_and(|in_digits| in_digits != digit) {
+            chunks.push(&text[start..i]);
+            start = i;
+        }
+        in_digits = Some(digit);
+    }
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+    chunks
+}
+
+/// Compare `a` and `b` with runs of digits compared by their numeric value,
+/// so `note2` comes before `note10`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    for (x, y) in chunks(a).into_iter().zip(chunks(b)) {
+        let digits = |chunk: &str| chunk.starts_with(|c: char| c.is_ascii_digit());
+        let order = if digits(x) && digits(y) {
+            let (x_value, y_value) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+            x_value
+                .len()
+                .cmp(&y_value.len())
+                .then_with(|| x_value.cmp(y_value))
+                // Equal values: fewer leading zeros first
+                .then_with(|| x.len().cmp(&y.len()))
+        } else {
+            x.cmp(y)
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    chunks(a).len().cmp(&chunks(b).len())
+}
+
+/// Parse a `--collate` locale name such as `de_DE`, `sv-SE` or `fr`.
+pub fn parse_collation_locale(name: &str) -> Result<Locale, String> {
+    let name = name.split('.').next().unwrap_or(name).replace('_', "-");
+    Locale::try_from_str(&name).map_err(|_| format!("unknown locale '{}'", name))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(path_text).unwrap_or_default()
+}
+
+/// Sort `files` by `key`. Files are found in time order, so sorting by time
+/// leaves them as they are; the other keys sort by file name, then by path.
+/// Names are compared with the collation rules of `collation` when given,
+/// which also sorts by name when `key` is time.
+pub fn sort(files: &mut [PathBuf], key: SortKey, collation: Option<&Locale>) -> Result<(), String> {
+    let Some(locale) = collation else {
+        match key {
+            SortKey::Time => {}
+            SortKey::Name => files.sort_by_cached_key(|path| (file_name(path), path.clone())),
+            SortKey::Natural => files
+                .sort_by(|a, b| natural_cmp(&file_name(a), &file_name(b)).then_with(|| a.cmp(b))),
+        }
+        return Ok(());
+    };
+
+    let mut preferences = CollatorPreferences::from(locale);
+    if key == SortKey::Natural {
+        preferences.numeric_ordering = Some(CollationNumericOrdering::True);
+    }
+    let collator = Collator::try_new(preferences, CollatorOptions::default())
+        .map_err(|e| format!("can't collate for '{}': {}", locale, e))?;
+    files.sort_by(|a, b| {
+        collator
+            .compare(&file_name(a), &file_name(b))
+            .then_with(|| a.cmp(b))
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks() {
+        assert_eq!(chunks("note10b.md"), vec!["note", "10", "b.md"]);
+        assert_eq!(chunks("2025-03"), vec!["2025", "-", "03"]);
+        assert_eq!(chunks(""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("note2.md", "note10.md"), Ordering::Less);
+        assert_eq!(natural_cmp("note10.md", "note10.md"), Ordering::Equal);
+        assert_eq!(natural_cmp("note02.md", "note2.md"), Ordering::Greater);
+        assert_eq!(natural_cmp("note.md", "note1.md"), Ordering::Greater);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+        assert_eq!(
+            natural_cmp("v99999999999999999999999", "v100000000000000000000000"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut files: Vec<PathBuf> = ["b/note10.md", "a/note2.md", "note1.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        sort(&mut files, SortKey::Name, None).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("note1.md"),
+                PathBuf::from("b/note10.md"),
+                PathBuf::from("a/note2.md")
+            ]
+        );
+
+        sort(&mut files, SortKey::Natural, None).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("note1.md"),
+                PathBuf::from("a/note2.md"),
+                PathBuf::from("b/note10.md")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_collation_locale() {
+        assert_eq!(
+            parse_collation_locale("de_DE.UTF-8").unwrap().to_string(),
+            "de-DE"
+        );
+        assert_eq!(parse_collation_locale("sv").unwrap().to_string(), "sv");
+        assert!(parse_collation_locale("not a locale").is_err());
+    }
+
+    #[test]
+    fn test_sort_collated() {
+        let names =
+            |files: &[PathBuf]| -> Vec<String> { files.iter().map(|f| file_name(f)).collect() };
+        let mut files: Vec<PathBuf> = ["zebra.md", "Äpfel.md", "apple.md", "Ösen.md", "ost.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        sort(&mut files, SortKey::Name, None).unwrap();
+        assert_eq!(names(&files)[4], "Ösen.md");
+
+        let german = parse_collation_locale("de").unwrap();
+        sort(&mut files, SortKey::Name, Some(&german)).unwrap();
+        assert_eq!(
+            names(&files),
+            vec!["Äpfel.md", "apple.md", "Ösen.md", "ost.md", "zebra.md"]
+        );
+
+        // Swedish sorts Ä and Ö after Z
+        let swedish = parse_collation_locale("sv_SE").unwrap();
+        sort(&mut files, SortKey::Time, Some(&swedish)).unwrap();
+        assert_eq!(
+            names(&files),
+            vec!["apple.md", "ost.md", "zebra.md", "Äpfel.md", "Ösen.md"]
+        );
+
+        let mut numbered: Vec<PathBuf> = ["Note10.md", "note2.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        sort(&mut numbered, SortKey::Natural, Some(&german)).unwrap();
+        assert_eq!(names(&numbered), vec!["note2.md", "Note10.md"]);
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::diff::section;
+use crate::traversal::{WalkContext, WalkOptions};
+use crate::{LinkStyle, atomic, file_iterator, i18n, relative_file, relative_name};
+
+/// What a run saw: the size of every file with the searched suffix, by path
+/// relative to the root.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    pub files: BTreeMap<String, u64>,
+}
+
+/// Files that disappeared or changed size between two states, as paths
+/// under the root.
+#[derive(Debug, Default, PartialEq)]
+pub struct Changes {
+    pub disappeared: Vec<PathBuf>,
+    pub resized: Vec<(PathBuf, u64, u64)>,
+}
+
+impl State {
+    /// State of the files under `root` ending with `suffix`, whatever their
+    /// date.
+    pub fn scan(root: &Path, suffix: &str, options: &WalkOptions) -> State {
+        let context = WalkContext::new(options);
+        let files = file_iterator(root, &context)
+            .filter(|path| context.scopes.matches_suffix(path, suffix))
+            .filter_map(|path| {
+                let size = fs::metadata(&path).ok()?.len();
+                Some((relative_name(root, &path), size))
+            })
+            .collect();
+        State { files }
+    }
+
+    /// Load a state saved by an earlier run. A missing file is an empty
+    /// state, so the first run of a compare-and-save loop works.
+    pub fn load(path: &str) -> Result<State, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| format!("invalid state file '{}': {}", path, e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(State::default()),
+            Err(e) => Err(i18n::trf("can't read '{}': {}", &[&path, &e])),
+        }
+    }
+
+    pub fn save(&self, path: &str, backup: bool) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).expect("state serializes");
+        atomic::write(Path::new(path), json + "\n", backup)
+            .map_err(|e| i18n::trf("can't write '{}': {}", &[&path, &e]))
+    }
+
+    /// What changed from `old` to this state of the files under `root`.
+    pub fn changes_since(&self, old: &State, root: &Path) -> Changes {
+        let mut changes = Changes::default();
+        for (name, &old_size) in &old.files {
+            match self.files.get(name) {
+                None => changes.disappeared.push(relative_file(root, name)),
+                Some(&size) if size != old_size => {
+                    changes
+                        .resized
+                        .push((relative_file(root, name), old_size, size))
+                }
+                Some(_) => {}
+            }
+        }
+        changes
+    }
+}
+
+/// Markdown sections for `changes`.
+pub fn render(changes: &Changes, style: &LinkStyle) -> String {
+    let mut out = section(i18n::tr("Disappeared"), &changes.disappeared, style);
+    out.push_str(&format!("\n## {}\n\n", i18n::tr("Changed Size")));
+    for (file, old, new) in &changes.resized {
+        out.push_str(&format!(
+            "{} ({} -> {})\n",
+            style.format(file),
+            style.size(*old),
+            style.size(*new)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn state(files: &[(&str, u64)]) -> State {
+        State {
+            files: files.iter().map(|(p, s)| (p.to_string(), *s)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_scan() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("sub")).unwrap();
+        fs::write(root.path().join("sub").join("a.md"), "abc").unwrap();
+        fs::write(root.path().join("b.txt"), "").unwrap();
+
+        assert_eq!(
+            State::scan(root.path(), ".md", &WalkOptions::default()),
+            state(&[("sub/a.md", 3)])
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+        let path = path.to_str().unwrap();
+
+        assert_eq!(State::load(path).unwrap(), State::default());
+        let saved = state(&[("a.md", 1)]);
+        saved.save(path, false).unwrap();
+        assert_eq!(State::load(path).unwrap(), saved);
+
+        fs::write(path, "not json").unwrap();
+        assert!(
+            State::load(path)
+                .unwrap_err()
+                .starts_with("invalid state file")
+        );
+    }
+
+    #[test]
+    fn test_changes_since() {
+        let old = state(&[("a.md", 1), ("b.md", 2), ("c.md", 3)]);
+        let new = state(&[("a.md", 1), ("c.md", 30), ("d.md", 4)]);
+
+        assert_eq!(
+            new.changes_since(&old, Path::new("notes")),
+            Changes {
+                disappeared: vec![PathBuf::from("notes/b.md")],
+                resized: vec![(PathBuf::from("notes/c.md"), 3, 30)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let changes = Changes {
+            disappeared: vec![PathBuf::from("notes/b.md")],
+            resized: vec![(PathBuf::from("notes/c.md"), 3, 30)],
+        };
+        assert_eq!(
+            render(&changes, &LinkStyle::default()),
+            "## Disappeared\n\n- [b.md](notes/b.md)\n\n## Changed Size\n\n- [c.md](notes/c.md) (3 B -> 30 B)\n"
+        );
+    }
+}
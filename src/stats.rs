@@ -0,0 +1,169 @@
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::{DateRange, TimeField, file_time, i18n, local_date, path_text, relative_path};
+
+const BAR_WIDTH: usize = 20;
+
+/// The extension of `path` with its dot, or "(none)".
+pub fn extension(path: &Path) -> String {
+    match path.extension() {
+        Some(ext) => format!(".{}", path_text(ext)),
+        None => i18n::tr("(none)").to_string(),
+    }
+}
+
+fn directory(root: &Path, path: &Path) -> String {
+    let relative = relative_path(root, path);
+    let parts: Vec<String> = relative
+        .parent()
+        .map(|parent| parent.iter().map(path_text).collect())
+        .unwrap_or_default();
+
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// Counts sorted by decreasing count, then by key.
+pub fn by_count(counts: BTreeMap<String, usize>) -> Vec<(String, usize)> {
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// A bar `count` long on a scale where `max` fills `BAR_WIDTH`.
+pub fn bar(count: usize, max: usize) -> String {
+    if count == 0 || max == 0 {
+        return String::new();
+    }
+    let width = (count * BAR_WIDTH).div_ceil(max);
+    "█".repeat(width)
+}
+
+/// A markdown table with `headers` over `rows`.
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = format!("| {} |\n", headers.join(" | "));
+    out.push_str(&format!(
+        "|{}\n",
+        headers.iter().map(|_| "---|").collect::<String>()
+    ));
+    for row in rows {
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    out
+}
+
+/// Markdown summary of `files`: counts per extension, per directory and per
+/// day of `range`.
+pub fn stats(root: &Path, files: &[PathBuf], range: DateRange, field: TimeField) -> String {
+    let mut extensions = BTreeMap::new();
+    let mut directories = BTreeMap::new();
+    let mut days: BTreeMap<NaiveDate, usize> = range.days().map(|day| (day, 0)).collect();
+
+    for file in files {
+        *extensions.entry(extension(file)).or_insert(0) += 1;
+        *directories.entry(directory(root, file)).or_insert(0) += 1;
+        if let Some(time) = file_time(file, field) {
+            *days.entry(local_date(time)).or_insert(0) += 1;
+        }
+    }
+
+    let count_rows = |counts| -> Vec<Vec<String>> {
+        by_count(counts)
+            .into_iter()
+            .map(|(key, count)| vec![format!("`{}`", key), count.to_string()])
+            .collect()
+    };
+
+    let max = days.values().copied().max().unwrap_or(0);
+    let day_rows: Vec<Vec<String>> = days
+        .iter()
+        .map(|(day, count)| vec![day.to_string(), count.to_string(), bar(*count, max)])
+        .collect();
+
+    let files_header = i18n::tr("Files");
+    let mut out = i18n::trn("Total: {} files", files.len(), &[&files.len()]) + "\n\n";
+    out.push_str(&format!("## {}\n\n", i18n::tr("Extensions")));
+    out.push_str(&table(
+        &[i18n::tr("Extension"), files_header],
+        &count_rows(extensions),
+    ));
+    out.push_str(&format!("\n## {}\n\n", i18n::tr("Directories")));
+    out.push_str(&table(
+        &[i18n::tr("Directory"), files_header],
+        &count_rows(directories),
+    ));
+    out.push_str(&format!("\n## {}\n\n", i18n::tr("Days")));
+    out.push_str(&table(&[i18n::tr("Date"), files_header, ""], &day_rows));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+    use std::fs::{self, File};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extension() {
+        assert_eq!(extension(Path::new("a/b.md")), ".md");
+        assert_eq!(extension(Path::new("Makefile")), "(none)");
+    }
+
+    #[test]
+    fn test_directory() {
+        let root = Path::new("root");
+        assert_eq!(directory(root, Path::new("root/a.md")), ".");
+        assert_eq!(directory(root, Path::new("root/x/y/a.md")), "x/y");
+    }
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(bar(0, 10), "");
+        assert_eq!(bar(10, 10).chars().count(), BAR_WIDTH);
+        assert_eq!(bar(1, 10).chars().count(), 2);
+    }
+
+    #[test]
+    fn test_by_count() {
+        let counts = BTreeMap::from([
+            ("b".to_string(), 1),
+            ("a".to_string(), 1),
+            ("c".to_string(), 3),
+        ]);
+        let keys: Vec<_> = by_count(counts).into_iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_stats() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("docs")).unwrap();
+        let files = vec![
+            root.path().join("a.md"),
+            root.path().join("docs").join("b.md"),
+            root.path().join("docs").join("c.txt"),
+        ];
+        for file in &files {
+            File::create(file).unwrap();
+        }
+
+        let today = Local::now().date_naive();
+        let range = DateRange {
+            start: today.pred_opt().unwrap(),
+            end: today,
+        };
+        let result = stats(root.path(), &files, range, TimeField::Modified);
+
+        assert!(result.starts_with("Total: 3 files\n"));
+        assert!(result.contains("| `.md` | 2 |\n| `.txt` | 1 |\n"));
+        assert!(result.contains("| `docs` | 2 |\n| `.` | 1 |\n"));
+        assert!(result.contains(&format!("| {} | 0 |  |\n", today.pred_opt().unwrap())));
+        assert!(result.contains(&format!("| {} | 3 | {} |\n", today, "█".repeat(BAR_WIDTH))));
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::i18n;
+
+/// Shared with the threads of `--exec`, unlike the other settings.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Leave stderr to the error that ends a failed run (for `--quiet`).
+pub fn set_quiet(on: bool) {
+    QUIET.store(on, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Print a status message on stderr, unless quiet. Stdout is kept for the
+/// output itself.
+pub fn say(message: impl Display) {
+    if !is_quiet() {
+        eprintln!("{}", message);
+    }
+}
+
+/// Print a warning about something skipped over on stderr, unless quiet.
+pub fn warn(message: impl Display) {
+    say(i18n::trf("warning: {}", &[&message]));
+}
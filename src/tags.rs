@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::Path;
+use yaml_rust2::Yaml;
+
+use crate::{frontmatter, markdown};
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// `#tag` tokens of a line, outside inline code. A tag follows whitespace or
+/// the start of the line and isn't all digits, so `#1` and `a#b` aren't tags.
+fn line_tags(line: &str, tags: &mut Vec<String>) {
+    let mut in_code = false;
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        if c == '`' {
+            in_code = !in_code;
+        } else if c == '#' && !in_code && previous.is_whitespace() {
+            let tag: String = line[i + 1..]
+                .chars()
+                .take_while(|&c| is_tag_char(c))
+                .collect();
+            if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+                tags.push(tag);
+            }
+        }
+        previous = c;
+    }
+}
+
+/// Tags written as `#tag` in the body of a markdown document, skipping
+/// frontmatter and fenced code blocks.
+fn inline_tags(content: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    for line in markdown::prose_lines(content) {
+        line_tags(line, &mut tags);
+    }
+    tags
+}
+
+/// Tags of the `tags` (or `tag`) frontmatter key: a list, or text separated
+/// by commas or spaces.
+fn frontmatter_tags(frontmatter: &Yaml) -> Vec<String> {
+    let value = match &frontmatter["tags"] {
+        Yaml::BadValue => &frontmatter["tag"],
+        value => value,
+    };
+
+    let words: Vec<String> = match value {
+        Yaml::Array(items) => items.iter().filter_map(frontmatter::scalar_text).collect(),
+        _ => frontmatter::scalar_text(value)
+            .map(|text| {
+                text.split(|c: char| c == ',' || c.is_whitespace())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    words
+        .iter()
+        .map(|word| word.trim().trim_start_matches('#').to_string())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Tags of a markdown document: frontmatter tags first, then inline ones,
+/// without duplicates.
+pub fn tags(content: &str) -> Vec<String> {
+    let mut all = frontmatter::parse(content)
+        .map(|frontmatter| frontmatter_tags(&frontmatter))
+        .unwrap_or_default();
+    all.extend(inline_tags(content));
+
+    let mut tags: Vec<String> = Vec::new();
+    for tag in all {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Tags of the file at `path`. Unreadable files have none.
+pub fn file_tags(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|content| tags(&content))
+        .unwrap_or_default()
+}
+
+/// Whether `tags` has `name` (with or without `#`), or a nested tag under it:
+/// `project` matches `project/x`. Case is ignored.
+pub fn has_tag(tags: &[String], name: &str) -> bool {
+    let name = name.trim_start_matches('#').to_lowercase();
+    tags.iter().any(|tag| {
+        let tag = tag.to_lowercase();
+        tag == name || tag.starts_with(&format!("{}/", name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_tags() {
+        let content = "# Title\n\nWork on #project-x and #meeting/weekly.\nIssue #12, not a#tag.\n`#code` stays out\n```\n#fenced\n```\n";
+        assert_eq!(inline_tags(content), vec!["project-x", "meeting/weekly"]);
+    }
+
+    #[test]
+    fn test_tags_merges_frontmatter() {
+        let content = "---\ntags: [project-x, '#draft']\n---\n#draft notes on #rust\n";
+        assert_eq!(tags(content), vec!["project-x", "draft", "rust"]);
+    }
+
+    #[test]
+    fn test_frontmatter_tag_text() {
+        let content = "---\ntag: a, b c\n---\n";
+        assert_eq!(tags(content), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_has_tag() {
+        let tags = vec!["Project/X".to_string(), "rust".to_string()];
+        assert!(has_tag(&tags, "#rust"));
+        assert!(has_tag(&tags, "project"));
+        assert!(has_tag(&tags, "project/x"));
+        assert!(!has_tag(&tags, "proj"));
+    }
+}
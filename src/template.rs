@@ -0,0 +1,537 @@
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tera::{Context, Tera};
+
+use crate::reltime::{self, ShowTime};
+use crate::{
+    DateRange, LinkStyle, TimeField, errors, file_time, forge, i18n, manifest, path_text, wc,
+};
+
+/// A timestamp in the formats templates most often want.
+#[derive(Clone, Debug, Serialize)]
+pub struct Times {
+    pub rfc3339: String,
+    /// Local date and time of day.
+    pub date: String,
+    pub time: String,
+    /// Seconds since the Unix epoch.
+    pub unix: i64,
+    /// How long ago, as in `3 days ago`.
+    pub relative: String,
+}
+
+impl Times {
+    fn new(time: SystemTime) -> Self {
+        let local = DateTime::<Local>::from(time);
+        Times {
+            rfc3339: local.to_rfc3339_opts(SecondsFormat::Secs, true),
+            date: local.format("%Y-%m-%d").to_string(),
+            time: local.format("%H:%M:%S").to_string(),
+            unix: local.timestamp(),
+            relative: reltime::format(time, ShowTime::Relative),
+        }
+    }
+}
+
+/// Which of the file fields that take reading the file or running git to
+/// fill in a template needs: those named in its tags, as an attribute
+/// (`file.git`, also through a variable or macro argument) or a string
+/// (`file["git"]`). Text outside the tags doesn't count.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Needs {
+    blake3: bool,
+    lines: bool,
+    git: bool,
+}
+
+impl Needs {
+    fn of(text: &str) -> Self {
+        let names = tag_names(text);
+        let named = |field: &str| names.contains(&field);
+        Needs {
+            blake3: named("blake3"),
+            lines: named("lines"),
+            git: named("git"),
+        }
+    }
+}
+
+/// The identifiers and string literals inside the `{{ }}` and `{% %}` tags
+/// of template `text`.
+fn tag_names(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(['{']) {
+        rest = &rest[start + 1..];
+        let close = match rest.chars().next() {
+            Some('{') => "}}",
+            Some('%') => "%}",
+            Some('#') => {
+                rest = rest.find("#}").map_or("", |end| &rest[end + 2..]);
+                continue;
+            }
+            _ => continue,
+        };
+        rest = &rest[1..];
+        let mut tag = &rest[..rest.find(close).unwrap_or(rest.len())];
+        rest = &rest[tag.len()..];
+        while let Some(c) = tag.chars().next() {
+            if c == '"' || c == '\'' || c == '`' {
+                let end = tag[1..].find(c).map_or(tag.len(), |end| end + 1);
+                names.push(&tag[1..end]);
+                tag = tag.get(end + 1..).unwrap_or("");
+            } else if c.is_alphanumeric() || c == '_' {
+                let end = tag
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(tag.len());
+                names.push(&tag[..end]);
+                tag = &tag[end..];
+            } else {
+                tag = &tag[c.len_utf8()..];
+            }
+        }
+    }
+    names
+}
+
+/// A matched file, as seen by templates.
+#[derive(Clone, Debug, Serialize)]
+pub struct TemplateFile {
+    pub name: String,
+    pub path: String,
+    /// The link target, as in the markdown listing.
+    pub link: String,
+    /// The file's markdown list entry.
+    pub entry: String,
+    pub extension: String,
+    pub size: u64,
+    /// Local date and RFC 3339 time of the timestamp dates are matched on.
+    pub date: String,
+    pub time: String,
+    pub mtime: Option<Times>,
+    /// Directories between the root and the file, 0 for files in the root.
+    pub depth: usize,
+    /// Content hash, line count and last commit, when the template uses
+    /// them and they're available.
+    pub blake3: Option<String>,
+    pub lines: Option<usize>,
+    pub git: Option<forge::LastCommit>,
+}
+
+impl TemplateFile {
+    pub fn new(
+        path: &Path,
+        root: &Path,
+        style: &LinkStyle,
+        field: TimeField,
+        needs: Needs,
+    ) -> Self {
+        TemplateFile::with_entry(path, root, style, field, needs, style.format(path))
+    }
+
+    /// The file at `path` with `entry` as its list entry.
+    fn with_entry(
+        path: &Path,
+        root: &Path,
+        style: &LinkStyle,
+        field: TimeField,
+        needs: Needs,
+        entry: String,
+    ) -> Self {
+        let time = file_time(path, field).map(DateTime::<Local>::from);
+        let metadata = fs::metadata(path).ok();
+        TemplateFile {
+            name: path.file_name().map(path_text).unwrap_or_default(),
+            path: path_text(path),
+            link: style.target(path),
+            entry,
+            extension: path.extension().map(path_text).unwrap_or_default(),
+            size: metadata.as_ref().map_or(0, |metadata| metadata.len()),
+            date: time.map_or_else(String::new, |time| time.format("%Y-%m-%d").to_string()),
+            time: time.map_or_else(String::new, |time| {
+                time.to_rfc3339_opts(SecondsFormat::Secs, true)
+            }),
+            mtime: metadata
+                .and_then(|metadata| metadata.modified().ok())
+                .map(Times::new),
+            depth: path
+                .strip_prefix(root)
+                .map_or(0, |path| path.components().count().saturating_sub(1)),
+            blake3: needs
+                .blake3
+                .then(|| manifest::hash_file(path).ok().map(|(_, hash)| hash))
+                .flatten(),
+            lines: needs
+                .lines
+                .then(|| wc::count(path).map(|counts| counts.lines))
+                .flatten(),
+            git: needs.git.then(|| forge::last_commit(path)).flatten(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Root {
+    path: String,
+    files: Vec<TemplateFile>,
+}
+
+#[derive(Serialize)]
+struct Day {
+    date: String,
+    files: Vec<TemplateFile>,
+}
+
+/// Text of a Tera error, which comes with its own "error: " label.
+fn message(e: tera::Error) -> String {
+    let text = e.to_string();
+    text.strip_prefix("error: ").unwrap_or(&text).to_string()
+}
+
+/// Name to register the template at `path` under: its file name without a
+/// `.tera` extension, so `page.html.tera` is escaped as HTML.
+fn template_name(path: &Path) -> String {
+    let name = path.file_name().map(path_text).unwrap_or_default();
+    name.strip_suffix(".tera").unwrap_or(&name).to_string()
+}
+
+/// Extension and template of an `--entry-template EXT=TEMPLATE`. The
+/// extension is matched without its dot and case, and the template is
+/// checked against a sample file so mistakes show up before the walk.
+pub fn parse_entry_template(spec: &str) -> Result<(String, String), String> {
+    let (extension, template) = spec
+        .split_once('=')
+        .ok_or("expected EXT=TEMPLATE, as in 'png=![{{ name }}]({{ link }})'")?;
+    let extension = extension.trim_start_matches('.').to_lowercase();
+    if extension.is_empty() {
+        return Err("missing extension before '='".into());
+    }
+    let sample = TemplateFile {
+        name: format!("sample.{}", extension),
+        path: format!("sample.{}", extension),
+        link: format!("sample.{}", extension),
+        entry: format!("[sample.{0}](sample.{0})", extension),
+        extension: extension.clone(),
+        size: 0,
+        date: String::new(),
+        time: String::new(),
+        mtime: Some(Times::new(SystemTime::now())),
+        depth: 0,
+        blake3: Some(String::new()),
+        lines: Some(0),
+        git: Some(forge::LastCommit {
+            hash: String::new(),
+            short: String::new(),
+            author: String::new(),
+            date: String::new(),
+        }),
+    };
+    let mut tera = Tera::new();
+    tera.add_raw_template(&extension, template)
+        .and_then(|_| tera.render(&extension, &Context::from_serialize(&sample)?))
+        .map_err(message)?;
+    Ok((extension, template.to_string()))
+}
+
+/// Templates for the list entries of files by extension, from
+/// `--entry-template`. They see the fields of a file in listing templates,
+/// with `entry` being the entry they replace.
+#[derive(Clone)]
+pub struct EntryTemplates {
+    tera: Tera,
+    /// Roots depths are counted from.
+    roots: Vec<PathBuf>,
+    field: TimeField,
+    needs: Needs,
+}
+
+impl fmt::Debug for EntryTemplates {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let extensions: Vec<&str> = self.tera.get_template_names().collect();
+        f.debug_struct("EntryTemplates")
+            .field("extensions", &extensions)
+            .field("roots", &self.roots)
+            .field("field", &self.field)
+            .field("needs", &self.needs)
+            .finish()
+    }
+}
+
+impl EntryTemplates {
+    /// Templates from parsed `--entry-template` values, later ones winning,
+    /// for files under `roots`.
+    pub fn new(
+        specs: &[(String, String)],
+        roots: &[String],
+        field: TimeField,
+    ) -> Result<Self, String> {
+        let mut tera = Tera::new();
+        for (extension, template) in specs {
+            tera.add_raw_template(extension, template).map_err(|e| {
+                format!("invalid entry template for '{}': {}", extension, message(e))
+            })?;
+        }
+        let text: Vec<&str> = specs
+            .iter()
+            .map(|(_, template)| template.as_str())
+            .collect();
+        Ok(EntryTemplates {
+            tera,
+            roots: roots.iter().map(PathBuf::from).collect(),
+            field,
+            needs: Needs::of(&text.concat()),
+        })
+    }
+
+    /// `entry` for `path` passed through the template of its extension, if
+    /// it has one. Falls back to `entry` when the template fails.
+    pub fn apply(&self, path: &Path, style: &LinkStyle, entry: String) -> String {
+        let Some(extension) = path
+            .extension()
+            .map(|extension| path_text(extension).to_lowercase())
+            .filter(|extension| self.tera.contains_template(extension))
+        else {
+            return entry;
+        };
+        let root = self
+            .roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map_or(Path::new(""), PathBuf::as_path);
+        let file = TemplateFile::with_entry(path, root, style, self.field, self.needs, entry);
+        let rendered = Context::from_serialize(&file)
+            .and_then(|context| self.tera.render(&extension, &context));
+        match rendered {
+            Ok(text) => text,
+            Err(e) => {
+                errors::record(format!(
+                    "can't render entry of '{}': {}",
+                    path.display(),
+                    message(e)
+                ));
+                file.entry
+            }
+        }
+    }
+}
+
+/// Render the files of each root in `groups` through the Tera template at
+/// `path`. Templates get the `title`, the date `range` (`start` and `end`),
+/// the `generated` time, all `files`, the files of each of the `roots`, and
+/// the files of each of the `days`, oldest first.
+pub fn render(
+    path: &Path,
+    title: &str,
+    range: DateRange,
+    groups: &[(&Path, Vec<PathBuf>)],
+    style: &LinkStyle,
+    field: TimeField,
+) -> Result<String, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| i18n::trf("can't read '{}': {}", &[&path.display(), &e]))?;
+    let name = template_name(path);
+    let mut tera = Tera::new();
+    tera.add_raw_template(&name, &text)
+        .map_err(|e| format!("invalid template '{}': {}", path.display(), message(e)))?;
+    let needs = Needs::of(&text);
+
+    let roots: Vec<Root> = groups
+        .iter()
+        .map(|(root, files)| Root {
+            path: path_text(root),
+            files: files
+                .iter()
+                .map(|file| TemplateFile::new(file, root, style, field, needs))
+                .collect(),
+        })
+        .collect();
+    let files: Vec<&TemplateFile> = roots.iter().flat_map(|root| &root.files).collect();
+    let mut days: BTreeMap<&str, Vec<TemplateFile>> = BTreeMap::new();
+    for file in &files {
+        days.entry(&file.date).or_default().push((*file).clone());
+    }
+    let days: Vec<Day> = days
+        .into_iter()
+        .map(|(date, files)| Day {
+            date: date.to_string(),
+            files,
+        })
+        .collect();
+
+    let mut context = Context::new();
+    context.insert("title", title);
+    context.insert(
+        "range",
+        &BTreeMap::from([
+            ("start", range.start.to_string()),
+            ("end", range.end.to_string()),
+        ]),
+    );
+    context.insert(
+        "generated",
+        &Local::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+    context.insert("files", &files);
+    context.insert("roots", &roots);
+    context.insert("days", &days);
+    tera.render(&name, &context)
+        .map_err(|e| format!("can't render '{}': {}", path.display(), message(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.md");
+        let b = dir.path().join("b.rs");
+        fs::write(&a, "alpha").unwrap();
+        fs::write(&b, "").unwrap();
+        let template = dir.path().join("page.html.tera");
+        fs::write(
+            &template,
+            "<h1>{{ title }}</h1>\n\
+             {% for file in files %}{% if file.extension == \"md\" %}\
+             <a href=\"{{ file.link }}\">{{ file.name }}</a> {{ file.size }}\n\
+             {% endif %}{% endfor %}{{ days | length }} {{ range.end }}\n",
+        )
+        .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let range = DateRange {
+            start: date,
+            end: date,
+        };
+        let style = LinkStyle::default();
+        let groups = [(dir.path(), vec![a.clone(), b])];
+        let out = render(
+            &template,
+            "A & B",
+            range,
+            &groups,
+            &style,
+            TimeField::Modified,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            format!(
+                "<h1>A &amp; B</h1>\n<a href=\"{}\">a.md</a> 5\n1 2025-03-14\n",
+                a.display()
+            )
+        );
+
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let c = dir.path().join("sub").join("c.md");
+        fs::write(&c, "one\ntwo\n").unwrap();
+        fs::write(
+            &template,
+            "{% for file in files %}{{ file.depth }} {{ file.lines }} {{ file.blake3 }} \
+             {{ file.mtime.unix > 0 }} {{ file.mtime.relative }}{% endfor %}",
+        )
+        .unwrap();
+        let groups = [(dir.path(), vec![c])];
+        assert_eq!(
+            render(&template, "", range, &groups, &style, TimeField::Modified).unwrap(),
+            format!("1 2 {} true just now", blake3::hash(b"one\ntwo\n").to_hex())
+        );
+
+        fs::write(&template, "{% for %}").unwrap();
+        assert!(
+            render(&template, "", range, &groups, &style, TimeField::Modified)
+                .unwrap_err()
+                .starts_with("invalid template")
+        );
+    }
+
+    #[test]
+    fn test_needs() {
+        let none = Needs::default();
+        assert_eq!(Needs::of("digital outlines {{ file.name }}"), none);
+        assert_eq!(Needs::of("{{ file.outlines }} {# file.git #}"), none);
+        assert_eq!(
+            Needs::of("{% set f = file %}{{ f.git.short }}"),
+            Needs { git: true, ..none }
+        );
+        assert_eq!(
+            Needs::of("{%- if file[\"lines\"] -%}{{ file.blake3 }}{% endif %}"),
+            Needs {
+                blake3: true,
+                lines: true,
+                git: false
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_fields_through_variables() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.md");
+        fs::write(&a, "one\ntwo\n").unwrap();
+        let template = dir.path().join("page.tera");
+        let date = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let range = DateRange {
+            start: date,
+            end: date,
+        };
+        let groups = [(dir.path(), vec![a])];
+        let style = LinkStyle::default();
+        let render = |text: &str| {
+            fs::write(&template, text).unwrap();
+            render(&template, "", range, &groups, &style, TimeField::Modified).unwrap()
+        };
+
+        assert_eq!(
+            render("{% for file in files %}{% set f = file %}{{ f[\"lines\"] }}{% endfor %}"),
+            "2"
+        );
+        assert_eq!(
+            render("{% for file in files %}{{ file.name }}: digital outlines{% endfor %}"),
+            "a.md: digital outlines"
+        );
+    }
+
+    #[test]
+    fn test_entry_templates() {
+        assert_eq!(
+            parse_entry_template(".PNG=![{{ name }}]({{ link }})").unwrap(),
+            ("png".to_string(), "![{{ name }}]({{ link }})".to_string())
+        );
+        assert!(parse_entry_template("png").is_err());
+        assert!(parse_entry_template("={{ entry }}").is_err());
+        assert!(parse_entry_template("rs={{ entry").is_err());
+        assert!(parse_entry_template("rs={{ nope }}").is_err());
+
+        let dir = TempDir::new().unwrap();
+        let image = dir.path().join("Cat.PNG");
+        let code = dir.path().join("main.rs");
+        let specs = [
+            parse_entry_template("png=![{{ name }}]({{ link }})").unwrap(),
+            parse_entry_template("rs={{ entry }} `rust`").unwrap(),
+        ];
+        let templates = EntryTemplates::new(&specs, &[], TimeField::Modified).unwrap();
+        let style = LinkStyle::default();
+        assert_eq!(
+            templates.apply(&image, &style, "[Cat.PNG](x)".into()),
+            format!("![Cat.PNG]({})", image.display())
+        );
+        assert_eq!(
+            templates.apply(&code, &style, "[main.rs](x)".into()),
+            "[main.rs](x) `rust`"
+        );
+        assert_eq!(
+            templates.apply(&dir.path().join("a.md"), &style, "[a.md](x)".into()),
+            "[a.md](x)"
+        );
+    }
+}
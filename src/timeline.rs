@@ -0,0 +1,108 @@
+use chrono::{DateTime, Local, Locale};
+use std::path::PathBuf;
+
+use crate::{DateRange, LinkStyle, TimeField, date_heading, file_time};
+
+/// `files` in the exact order of their `field` timestamps, each entry with
+/// its time of day. Over a range of dates, the entries are under a heading
+/// per day.
+pub fn timeline(
+    files: &[PathBuf],
+    range: DateRange,
+    field: TimeField,
+    locale: Option<Locale>,
+    style: &LinkStyle,
+) -> String {
+    let mut entries: Vec<(DateTime<Local>, &PathBuf)> = files
+        .iter()
+        .filter_map(|file| Some((file_time(file, field)?.into(), file)))
+        .collect();
+    entries.sort();
+
+    let mut out = String::new();
+    let mut day = None;
+    for (time, file) in entries {
+        let date = time.date_naive();
+        if range.start != range.end && day != Some(date) {
+            if day.is_some() {
+                out.push('\n');
+            }
+            out.push_str(&format!("## {}\n\n", date_heading(date, locale)));
+            day = Some(date);
+        }
+        out.push_str(&format!(
+            "- {} — {}\n",
+            time.format("%H:%M"),
+            style.entry(file)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::fs::File;
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_timeline() {
+        let dir = TempDir::new().unwrap();
+        let mut files = Vec::new();
+        for (name, day, hour, minute) in [
+            ("late.md", 14, 17, 5),
+            ("early.md", 14, 9, 30),
+            ("next.md", 15, 8, 0),
+        ] {
+            let path = dir.path().join(name);
+            let time = date(day)
+                .and_hms_opt(hour, minute, 0)
+                .unwrap()
+                .and_local_timezone(Local)
+                .unwrap();
+            File::create(&path)
+                .unwrap()
+                .set_modified(SystemTime::from(time))
+                .unwrap();
+            files.push(path);
+        }
+        let style = LinkStyle::default();
+        let target = |name: &str| style.target(&dir.path().join(name));
+
+        assert_eq!(
+            timeline(
+                &files[..2],
+                date(14).into(),
+                TimeField::Modified,
+                None,
+                &style
+            ),
+            format!(
+                "- 09:30 — [early.md]({})\n- 17:05 — [late.md]({})\n",
+                target("early.md"),
+                target("late.md")
+            )
+        );
+
+        let range = DateRange {
+            start: date(14),
+            end: date(15),
+        };
+        assert_eq!(
+            timeline(&files, range, TimeField::Modified, None, &style),
+            format!(
+                "## 2025-03-14\n\n- 09:30 — [early.md]({})\n- 17:05 — [late.md]({})\n\n\
+                 ## 2025-03-15\n\n- 08:00 — [next.md]({})\n",
+                target("early.md"),
+                target("late.md"),
+                target("next.md")
+            )
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::markdown;
+
+/// ATX headings (`## Title`) of a markdown document, as (level, text).
+pub fn headings(content: &str) -> Vec<(usize, String)> {
+    markdown::prose_lines(content)
+        .into_iter()
+        .filter_map(heading)
+        .collect()
+}
+
+/// The ATX heading on `line`, if it is one, as (level, text).
+pub fn heading(line: &str) -> Option<(usize, String)> {
+    let line = line.trim_start();
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+
+    let text = rest.trim().trim_end_matches('#').trim_end();
+    (!text.is_empty()).then(|| (level, text.to_string()))
+}
+
+/// GitHub-style anchor of a heading: lowercase, punctuation dropped, spaces
+/// turned into dashes.
+pub fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Anchors of `headings`, numbering repeated ones (`notes`, `notes-1`...).
+fn anchors(headings: &[(usize, String)]) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    headings
+        .iter()
+        .map(|(_, text)| {
+            let slug = slug(text);
+            let count = seen.entry(slug.clone()).or_insert(0);
+            let anchor = match *count {
+                0 => slug,
+                n => format!("{}-{}", slug, n),
+            };
+            *count += 1;
+            anchor
+        })
+        .collect()
+}
+
+/// Sub-bullets linking to the headings of `content` (the first `limit` of
+/// them, if given), nested by level under the file's entry. `target` is the
+/// file's link target.
+pub fn toc(content: &str, target: &str, limit: Option<usize>) -> Vec<String> {
+    let mut headings = headings(content);
+    headings.truncate(limit.unwrap_or(usize::MAX));
+    let top = headings.iter().map(|(level, _)| *level).min().unwrap_or(1);
+
+    headings
+        .iter()
+        .zip(anchors(&headings))
+        .map(|((level, text), anchor)| {
+            let indent = "  ".repeat(level - top + 1);
+            format!("{}- [{}]({}#{})", indent, text, target, anchor)
+        })
+        .collect()
+}
+
+/// Table of contents of the markdown file at `path`. Unreadable files have
+/// none.
+pub fn file_toc(path: &Path, target: &str, limit: Option<usize>) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|content| toc(&content, target, limit))
+        .unwrap_or_default()
+}
+
+/// Anchor of the first heading of the markdown file at `path`, if it has
+/// one and can be read.
+pub fn first_anchor(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    headings(&content).first().map(|(_, text)| slug(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings() {
+        let content =
+            "# Title #\n\ntext\n##Not a heading\n### Deep\n####### Too deep\n```\n# code\n```\n";
+        assert_eq!(
+            headings(content),
+            vec![(1, "Title".to_string()), (3, "Deep".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Hello, World!"), "hello-world");
+        assert_eq!(slug("API v2 (draft)"), "api-v2-draft");
+        assert_eq!(slug("snake_case & more"), "snake_case--more");
+        assert_eq!(slug("Été"), "été");
+    }
+
+    #[test]
+    fn test_anchors_numbers_duplicates() {
+        let headings = vec![
+            (2, "Notes".to_string()),
+            (2, "Notes".to_string()),
+            (2, "Notes".to_string()),
+        ];
+        assert_eq!(anchors(&headings), vec!["notes", "notes-1", "notes-2"]);
+    }
+
+    #[test]
+    fn test_toc() {
+        let content = "## Plan\n### Step one\n## Done\n";
+        assert_eq!(
+            toc(content, "a.md", None),
+            vec![
+                "  - [Plan](a.md#plan)",
+                "    - [Step one](a.md#step-one)",
+                "  - [Done](a.md#done)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_first_anchor() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let titled = dir.path().join("a.md");
+        let untitled = dir.path().join("b.md");
+        fs::write(&titled, "intro\n\n## My Heading\n# Later\n").unwrap();
+        fs::write(&untitled, "no headings\n").unwrap();
+
+        assert_eq!(first_anchor(&titled).as_deref(), Some("my-heading"));
+        assert_eq!(first_anchor(&untitled), None);
+        assert_eq!(first_anchor(&dir.path().join("missing.md")), None);
+    }
+
+    #[test]
+    fn test_toc_limit() {
+        let content = "# Title\n## Plan\n### Step one\n## Done\n";
+        assert_eq!(
+            toc(content, "a.md", Some(2)),
+            vec!["  - [Title](a.md#title)", "    - [Plan](a.md#plan)"]
+        );
+        assert!(toc(content, "a.md", Some(0)).is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+use clap::ValueEnum;
+use std::cell::Cell;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dirconfig::{Overridden, Scopes};
+
+/// Order directories are walked in. Both sort the entries of each directory
+/// by name.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum WalkOrder {
+    Dfs,
+    Bfs,
+}
+
+/// How directories are walked, set from the command line and passed to
+/// every walk.
+#[derive(Clone, Debug)]
+pub struct WalkOptions {
+    /// Directories left out with everything under them (for
+    /// `--exclude-root`), canonicalized.
+    excluded: Vec<PathBuf>,
+    /// Order to walk in, or `None` for the order the file system lists
+    /// entries in (for `--unordered`).
+    pub order: Option<WalkOrder>,
+    /// Keys of the working directory's `mdfiles.toml` the settings override.
+    pub overridden: Overridden,
+}
+
+impl Default for WalkOptions {
+    /// Depth-first and sorted, excluding nothing.
+    fn default() -> Self {
+        WalkOptions {
+            excluded: Vec::new(),
+            order: Some(WalkOrder::Dfs),
+            overridden: Overridden::default(),
+        }
+    }
+}
+
+impl WalkOptions {
+    /// Options leaving out the directories at `paths` and walking in `order`.
+    pub fn new(paths: &[String], order: Option<WalkOrder>) -> Result<Self, String> {
+        let excluded = paths
+            .iter()
+            .map(|path| {
+                fs::canonicalize(path).map_err(|e| format!("can't exclude '{}': {}", path, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(WalkOptions {
+            excluded,
+            order,
+            overridden: Overridden::default(),
+        })
+    }
+
+    /// Whether the directory at `path` was excluded.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        !self.excluded.is_empty()
+            && fs::canonicalize(path).is_ok_and(|path| self.excluded.contains(&path))
+    }
+}
+
+/// A walk under way: its options, the `mdfiles.toml` overrides of the
+/// directories it went through, and how many of its files matched, which
+/// the code taking files from the walk needs as well.
+pub struct WalkContext<'a> {
+    pub options: &'a WalkOptions,
+    pub scopes: Scopes,
+    matches: Cell<usize>,
+}
+
+impl<'a> WalkContext<'a> {
+    pub fn new(options: &'a WalkOptions) -> Self {
+        WalkContext {
+            options,
+            scopes: Scopes::new(&options.overridden),
+            matches: Cell::new(0),
+        }
+    }
+
+    /// Count a file of the walk that matched the search, for its progress.
+    pub fn matched(&self) {
+        self.matches.set(self.matches.get() + 1);
+    }
+
+    /// Files of the walk that matched so far.
+    pub fn matches(&self) -> usize {
+        self.matches.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_exclude() {
+        let root = TempDir::new().unwrap();
+        let data = root.path().join("data");
+        fs::create_dir(&data).unwrap();
+        assert!(!WalkOptions::default().is_excluded(&data));
+
+        let options = WalkOptions::new(&[data.to_string_lossy().into_owned()], None).unwrap();
+        assert!(options.is_excluded(&root.path().join(".").join("data")));
+        assert!(!options.is_excluded(root.path()));
+
+        let missing = root.path().join("missing").to_string_lossy().into_owned();
+        assert!(
+            WalkOptions::new(&[missing], None)
+                .unwrap_err()
+                .starts_with("can't exclude")
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::diff::section;
+use crate::manifest::hash_file;
+use crate::{LinkStyle, i18n, relative_file, relative_name};
+
+/// Differences between a manifest and the files on disk, as paths relative
+/// to the root.
+#[derive(Debug, Default, PartialEq)]
+pub struct Report {
+    pub changed: Vec<String>,
+    pub missing: Vec<String>,
+    pub new: Vec<String>,
+}
+
+impl Report {
+    /// Whether files listed in the manifest were changed or lost.
+    pub fn failed(&self) -> bool {
+        !self.changed.is_empty() || !self.missing.is_empty()
+    }
+}
+
+/// Re-hash the files of `manifest` under `root`. Files of `current` (the
+/// files matching now) that the manifest doesn't list are reported as new.
+pub fn verify(
+    root: &Path,
+    manifest: &[(String, String)],
+    current: &[PathBuf],
+) -> Result<Report, String> {
+    let mut report = Report::default();
+
+    for (name, hash) in manifest {
+        let path = relative_file(root, name);
+        if !path.exists() {
+            report.missing.push(name.clone());
+            continue;
+        }
+
+        let (_, actual) = hash_file(&path)
+            .map_err(|e| i18n::trf("can't read '{}': {}", &[&path.display(), &e]))?;
+        if actual != *hash {
+            report.changed.push(name.clone());
+        }
+    }
+
+    let listed: HashSet<&str> = manifest.iter().map(|(name, _)| name.as_str()).collect();
+    report.new = current
+        .iter()
+        .map(|file| relative_name(root, file))
+        .filter(|name| !listed.contains(name.as_str()))
+        .collect();
+
+    Ok(report)
+}
+
+/// Markdown report with a section each for changed, missing and new files.
+pub fn render(report: &Report, root: &Path, style: &LinkStyle) -> String {
+    let paths = |names: &[String]| -> Vec<PathBuf> {
+        names.iter().map(|name| relative_file(root, name)).collect()
+    };
+
+    [
+        section(i18n::tr("Changed"), &paths(&report.changed), style),
+        section(i18n::tr("Missing"), &paths(&report.missing), style),
+        section(i18n::tr("New"), &paths(&report.new), style),
+    ]
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn listed(root: &Path, names: &[&str]) -> Vec<(String, String)> {
+        names
+            .iter()
+            .map(|name| {
+                let (_, hash) = hash_file(&root.join(name)).unwrap();
+                (name.to_string(), hash)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify() {
+        let root = TempDir::new().unwrap();
+        for name in ["same.md", "changed.md", "gone.md"] {
+            fs::write(root.path().join(name), name).unwrap();
+        }
+        let manifest = listed(root.path(), &["same.md", "changed.md", "gone.md"]);
+
+        fs::write(root.path().join("changed.md"), "edited").unwrap();
+        fs::remove_file(root.path().join("gone.md")).unwrap();
+        fs::write(root.path().join("new.md"), "").unwrap();
+        let current = vec![
+            root.path().join("same.md"),
+            root.path().join("changed.md"),
+            root.path().join("new.md"),
+        ];
+
+        let report = verify(root.path(), &manifest, &current).unwrap();
+        assert_eq!(
+            report,
+            Report {
+                changed: vec!["changed.md".to_string()],
+                missing: vec!["gone.md".to_string()],
+                new: vec!["new.md".to_string()],
+            }
+        );
+        assert!(report.failed());
+    }
+
+    #[test]
+    fn test_verify_clean() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join("a.md"), "a").unwrap();
+        let manifest = listed(root.path(), &["a.md"]);
+
+        let report = verify(root.path(), &manifest, &[root.path().join("a.md")]).unwrap();
+        assert_eq!(report, Report::default());
+        assert!(!report.failed());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_verify_non_utf8_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let root = TempDir::new().unwrap();
+        let file = root.path().join(OsStr::from_bytes(b"caf\xe9.md"));
+        fs::write(&file, "a").unwrap();
+        let (_, hash) = hash_file(&file).unwrap();
+        let manifest = vec![(relative_name(root.path(), &file), hash)];
+
+        let report = verify(root.path(), &manifest, &[file]).unwrap();
+        assert_eq!(report, Report::default());
+    }
+
+    #[test]
+    fn test_render() {
+        let report = Report {
+            changed: vec!["a.md".to_string()],
+            missing: vec![],
+            new: vec!["sub/b.md".to_string()],
+        };
+        assert_eq!(
+            render(&report, Path::new("notes"), &LinkStyle::default()),
+            "## Changed\n\n- [a.md](notes/a.md)\n\n## Missing\n\n\n## New\n\n- [b.md](notes/sub/b.md)\n"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Lines and words of a text file, as counted by `wc`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Counts {
+    pub lines: usize,
+    pub words: usize,
+}
+
+impl Counts {
+    pub fn of(text: &str) -> Self {
+        Counts {
+            lines: text.lines().count(),
+            words: text.split_whitespace().count(),
+        }
+    }
+}
+
+/// Counts of the file at `path`, or `None` if it can't be read or isn't
+/// UTF-8 text.
+pub fn count(path: &Path) -> Option<Counts> {
+    fs::read_to_string(path).ok().map(|text| Counts::of(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        assert_eq!(
+            Counts::of("# Title\n\nsome  words here\nlast line"),
+            Counts { lines: 4, words: 7 }
+        );
+        assert_eq!(Counts::of(""), Counts { lines: 0, words: 0 });
+    }
+
+    #[test]
+    fn test_count_binary() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("a.bin");
+        fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+        assert_eq!(count(&path), None);
+    }
+}
@@ -0,0 +1,103 @@
+use serde_json::json;
+
+/// Most characters Discord takes in a message's `content`.
+const DISCORD_LIMIT: usize = 2000;
+
+/// `markdown` cut at a line end to fit in `limit` characters, with the
+/// lines left out counted in a last `… and N more` line.
+fn truncate(markdown: &str, limit: usize) -> String {
+    if markdown.chars().count() <= limit {
+        return markdown.to_string();
+    }
+    let lines: Vec<&str> = markdown.lines().filter(|line| !line.is_empty()).collect();
+    let mut kept = String::new();
+    let mut used = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let more = format!("… and {} more", lines.len() - i);
+        let size = line.chars().count() + 1;
+        if used + size + more.chars().count() > limit {
+            return kept + &more;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+        used += size;
+    }
+    kept
+}
+
+/// JSON body for an incoming webhook: the markdown as `text`, which Slack
+/// and Matrix bridges show, and as `content`, which Discord shows, cut to
+/// the length Discord takes, with the number of files listed.
+fn payload(markdown: &str, count: usize) -> String {
+    json!({
+        "text": markdown,
+        "content": truncate(markdown, DISCORD_LIMIT),
+        "count": count,
+    })
+    .to_string()
+}
+
+/// POST the listing `markdown` of `count` files to the webhook at `url`.
+pub fn post(url: &str, markdown: &str, count: usize) -> Result<(), String> {
+    ureq::post(url)
+        .header("Content-Type", "application/json")
+        .send(payload(markdown, count))
+        .map_err(|e| format!("can't post to '{}': {}", url, e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use tiny_http::{Response, Server};
+
+    #[test]
+    fn test_post() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.server_addr().to_ip().unwrap());
+        let receiver = thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let path = request.url().to_string();
+            request.respond(Response::empty(200)).unwrap();
+            let request = server.recv().unwrap();
+            request.respond(Response::empty(404)).unwrap();
+            (path, body)
+        });
+
+        post(&url, "- [a.md](a.md)\n", 1).unwrap();
+        let err = post(&url, "", 0).unwrap_err();
+        assert!(err.contains("404"));
+
+        let (path, body) = receiver.join().unwrap();
+        assert_eq!(path, "/hook");
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["text"], "- [a.md](a.md)\n");
+        assert_eq!(body["content"], body["text"]);
+        assert_eq!(body["count"], 1);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("- a\n- b\n", 8), "- a\n- b\n");
+        assert_eq!(
+            truncate("- aaaa\n\n- bbbb\n- cccc\n", 20),
+            "- aaaa\n… and 2 more"
+        );
+        assert_eq!(truncate("- a\n- b\n", 3), "… and 2 more");
+    }
+
+    #[test]
+    fn test_payload_fits_discord() {
+        let markdown: String = (0..500)
+            .map(|i| format!("- [{0}.md]({0}.md)\n", i))
+            .collect();
+        let body: serde_json::Value = serde_json::from_str(&payload(&markdown, 500)).unwrap();
+        let content = body["content"].as_str().unwrap();
+        assert!(content.chars().count() <= DISCORD_LIMIT);
+        assert!(content.ends_with(" more"));
+        assert_eq!(body["text"], markdown);
+    }
+}
@@ -138,3 +138,2744 @@ fn test_all_options_together() {
         .assert()
         .success();
 }
+
+#[test]
+fn test_daily_writes_note_section() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("notes.md"), "hello").unwrap();
+    let vault = tempfile::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("daily")
+        .arg("--vault")
+        .arg(vault.path())
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success();
+
+    let notes: Vec<_> = std::fs::read_dir(vault.path())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(notes.len(), 1);
+
+    let note = std::fs::read_to_string(&notes[0]).unwrap();
+    assert!(note.starts_with("## Files\n\n- [notes.md]("));
+}
+
+#[test]
+fn test_daily_links_from_note_folder() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(root.path().join("a")).unwrap();
+    std::fs::write(root.path().join("a").join("note.md"), "hello").unwrap();
+    let settings = root.path().join("vault").join(".obsidian");
+    std::fs::create_dir_all(&settings).unwrap();
+    std::fs::write(
+        settings.join("daily-notes.json"),
+        r#"{"folder": "journal"}"#,
+    )
+    .unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let journal = root.path().join("vault").join("journal");
+    std::fs::create_dir(&journal).unwrap();
+    let note = journal.join(format!("{}.md", today));
+    std::fs::write(&note, "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(root.path())
+        .args(["daily", "--vault", "vault", "-s", ".md"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        std::fs::read_to_string(&note).unwrap(),
+        "## Files\n\n- [note.md](../../a/note.md)\n"
+    );
+}
+
+/// Run `daily` twice over `root` with `args` and return the note.
+fn daily_twice(root: &std::path::Path, args: &[&str]) -> String {
+    let vault = tempfile::TempDir::new().unwrap();
+    for _ in 0..2 {
+        let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+        cmd.arg("daily")
+            .arg("--vault")
+            .arg(vault.path())
+            .args(args)
+            .arg("-r")
+            .arg(root)
+            .assert()
+            .success();
+    }
+
+    let note = std::fs::read_dir(vault.path())
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    std::fs::read_to_string(note).unwrap()
+}
+
+#[test]
+fn test_daily_rerun_with_anchor() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("notes.md"), "# Intro\n\nhello\n").unwrap();
+
+    let note = daily_twice(root.path(), &["--anchor", "-s", ".md"]);
+    assert_eq!(note.matches("notes.md#intro)").count(), 1);
+}
+
+#[test]
+fn test_daily_rerun_with_media() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("memo.m4a"), "").unwrap();
+
+    let note = daily_twice(root.path(), &["--smart-embed", "-s", ".m4a"]);
+    assert_eq!(note.matches("<audio controls src=").count(), 1);
+}
+
+#[test]
+fn test_daily_missing_vault() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("daily")
+        .arg("--vault")
+        .arg("/nonexistent/vault")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not a directory"));
+}
+
+#[test]
+fn test_robot_mode() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--robot")
+        .arg("-r")
+        .arg("./src")
+        .arg("-s")
+        .arg(".rs")
+        .write_stdin("{\"id\": 7, \"date\": \"2020-01-01\"}\n")
+        .assert()
+        .success()
+        .stdout("{\"files\":[],\"id\":7}\n");
+}
+
+#[test]
+fn test_dates_from_stdin() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--dates-from")
+        .arg("-")
+        .arg("-r")
+        .arg("./src")
+        .write_stdin("2020-01-01\n2020-01-02\n")
+        .assert()
+        .success()
+        .stdout("## 2020-01-01\n\n\n## 2020-01-02\n\n");
+}
+
+#[test]
+fn test_dates_from_filters_and_sorts() {
+    let root = tempfile::TempDir::new().unwrap();
+    // c.md is older than b.md, and a.md is already listed
+    for (name, hour) in [("c.md", 9), ("b.md", 12), ("a.md", 10)] {
+        let time = chrono::NaiveDate::from_ymd_opt(2025, 3, 14)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap();
+        let file = std::fs::File::create(root.path().join(name)).unwrap();
+        file.set_modified(time.into()).unwrap();
+    }
+    let existing = root.path().join("existing.txt");
+    std::fs::write(
+        &existing,
+        format!("[a.md]({})\n", root.path().join("a.md").display()),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--dates-from", "-", "--sort", "name", "-s", ".md"])
+        .arg("--skip-existing")
+        .arg(&existing)
+        .arg("-r")
+        .arg(root.path())
+        .write_stdin("2025-03-14\n")
+        .assert()
+        .success()
+        .stdout(format!(
+            "## 2025-03-14\n\n- [b.md]({})\n- [c.md]({})\n",
+            root.path().join("b.md").display(),
+            root.path().join("c.md").display()
+        ));
+}
+
+#[test]
+fn test_dates_from_conflicts_with_after() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--dates-from", "-", "--after", "2025-03-14T00:00"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_dates_from_invalid_date() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--dates-from")
+        .arg("-")
+        .write_stdin("2020-01-01\nyesterday\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("line 2: Invalid date format"));
+}
+
+#[test]
+fn test_report_skips_empty_run() {
+    let out_dir = tempfile::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("report")
+        .arg("--out-dir")
+        .arg(out_dir.path())
+        .arg("-d")
+        .arg("2020-01-01")
+        .arg("-r")
+        .arg("./src")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("nothing written"));
+
+    assert_eq!(std::fs::read_dir(out_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_skip_existing_links() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("old.md"), "").unwrap();
+    std::fs::write(root.path().join("new.md"), "").unwrap();
+
+    let index = root.path().join("index.txt");
+    let old = root.path().join("old.md");
+    std::fs::write(&index, format!("- [old.md]({})\n", old.display())).unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .arg("--skip-existing")
+        .arg(&index)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("new.md"))
+        .stdout(predicate::str::contains("old.md").not());
+}
+
+#[test]
+fn test_skip_existing_missing_file() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--skip-existing")
+        .arg("/nonexistent/index.md")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("can't read"));
+}
+
+#[test]
+fn test_archive_unknown_format() {
+    let out = tempfile::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("archive")
+        .arg(out.path().join("files.rar"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("can't guess archive format"));
+}
+
+#[test]
+fn test_archive_zip() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "a").unwrap();
+    let out = tempfile::TempDir::new().unwrap();
+    let archive = out.path().join("files.zip");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("archive")
+        .arg(&archive)
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("archived 1 file into"));
+
+    assert!(archive.exists());
+}
+
+#[test]
+fn test_copy_dry_run() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "a").unwrap();
+    let dest = tempfile::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("copy")
+        .arg(dest.path())
+        .arg("--dry-run")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("would copy "));
+
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_copy_onto_root() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "a").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(root.path())
+        .arg("copy")
+        .arg(".")
+        .arg("--force")
+        .arg("-s")
+        .arg(".md")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("inside the root directory"));
+
+    assert_eq!(
+        std::fs::read_to_string(root.path().join("a.md")).unwrap(),
+        "a"
+    );
+}
+
+#[test]
+fn test_move_files() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(root.path().join("sub")).unwrap();
+    std::fs::write(root.path().join("sub").join("a.md"), "a").unwrap();
+    let dest = tempfile::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("move")
+        .arg(dest.path())
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success();
+
+    assert!(dest.path().join("sub").join("a.md").exists());
+    assert!(!root.path().join("sub").join("a.md").exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_non_utf8_file_name_is_listed() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let root = tempfile::TempDir::new().unwrap();
+    let name = std::ffi::OsStr::from_bytes(b"caf\xe9.md");
+    std::fs::write(root.path().join(name), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- [caf%E9.md]("));
+}
+
+#[test]
+fn test_time_field_created() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+
+    // Whether or not creation times are supported here, the run succeeds
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--time-field")
+        .arg("created")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_time_field_invalid() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--time-field")
+        .arg("accessed")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_strict_fails_on_traversal_errors() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::os::unix::fs::symlink(root.path().join("missing"), root.path().join("dangling")).unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("-r").arg(root.path()).assert().success();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--strict")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("dangling"));
+}
+
+#[test]
+fn test_strict_succeeds_without_errors() {
+    let root = tempfile::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--strict")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_dates_from_with_locale() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--dates-from")
+        .arg("-")
+        .arg("--locale")
+        .arg("fr_FR")
+        .arg("-r")
+        .arg("./src")
+        .write_stdin("2025-03-14\n")
+        .assert()
+        .success()
+        .stdout("## 14 mars 2025\n\n");
+}
+
+#[test]
+fn test_unknown_locale() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--locale")
+        .arg("xx_YY")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown locale"));
+}
+
+#[test]
+fn test_stats_over_range() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("stats")
+        .arg("--from")
+        .arg("2020-01-01")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Total: 1 file\n"))
+        .stdout(predicate::str::contains("| 2020-01-01 | 0 |  |"));
+}
+
+#[test]
+fn test_to_requires_from() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--to").arg("2025-01-01").assert().failure();
+}
+
+#[test]
+fn test_range_conflicts_with_date() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--date")
+        .arg("2025-01-01")
+        .arg("--from")
+        .arg("2024-12-01")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_diff_snapshots() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let old = dir.path().join("old.md");
+    let new = dir.path().join("new.md");
+    std::fs::write(&old, "- [a.md](a.md)\n- [b.md](b.md)\n").unwrap();
+    std::fs::write(&new, "- [b.md](b.md)\n- [c.md](c.md)\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("diff")
+        .arg("--snapshot")
+        .arg(&old)
+        .arg(&new)
+        .assert()
+        .success()
+        .stdout("## Added\n\n- [c.md](c.md)\n\n## Removed\n\n- [a.md](a.md)\n");
+}
+
+#[test]
+fn test_diff_dates() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("diff")
+        .arg("2020-01-01")
+        .arg("2020-01-02")
+        .arg("-r")
+        .arg("./src")
+        .assert()
+        .success()
+        .stdout("## Added\n\n\n## Removed\n\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_open_runs_editor_with_matches() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "# A\n").unwrap();
+    std::fs::write(root.path().join("b.txt"), "b").unwrap();
+    let file = root.path().join("a.md");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.env_remove("VISUAL")
+        .env("EDITOR", "echo edit")
+        .arg("--open")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "edit {}\n",
+            file.display()
+        )))
+        .stdout(predicate::str::contains("b.txt").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_runs_per_file() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    std::fs::write(root.path().join("b.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--exec")
+        .arg("echo 'name: {/.}'")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("name: a\n"))
+        .stdout(predicate::str::contains("name: b\n"))
+        .stdout(predicate::str::contains("- [").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_batch_runs_once() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    std::fs::write(root.path().join("b.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    let output = cmd
+        .arg("--exec-batch")
+        .arg("echo batch")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stdout.starts_with("batch "));
+    assert!(stdout.contains("a.md") && stdout.contains("b.md"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_reports_failures() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--exec")
+        .arg("false")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("1 of 1 commands failed"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_jobs() {
+    let root = tempfile::TempDir::new().unwrap();
+    for i in 0..8 {
+        std::fs::write(root.path().join(format!("{}.md", i)), "").unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    let output = cmd
+        .arg("--exec")
+        .arg("echo done {/}")
+        .arg("--jobs")
+        .arg("4")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines: Vec<_> = stdout.lines().collect();
+    lines.sort();
+    let expected: Vec<_> = (0..8).map(|i| format!("done {}.md", i)).collect();
+    assert_eq!(lines, expected);
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--exec")
+        .arg("sh -c 'test {/.} -lt 3'")
+        .arg("-j")
+        .arg("3")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("5 of 8 commands failed"));
+}
+
+#[test]
+fn test_jobs_requires_exec() {
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--jobs")
+        .arg("2")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--exec"));
+}
+
+#[test]
+fn test_manifest() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(root.path().join("sub")).unwrap();
+    std::fs::write(root.path().join("sub").join("a.md"), "").unwrap();
+    let empty_hash = "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("manifest")
+        .arg("--format")
+        .arg("bsd")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(format!("BLAKE3 (sub/a.md) = {}\n", empty_hash));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    let output = cmd
+        .arg("manifest")
+        .arg("-s")
+        .arg(".md")
+        .arg("-r")
+        .arg(root.path())
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["path"], "sub/a.md");
+    assert_eq!(json[0]["size"], 0);
+    assert_eq!(json[0]["blake3"], empty_hash);
+}
+
+#[test]
+fn test_verify_manifest() {
+    let root = tempfile::TempDir::new().unwrap();
+    let out = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "a").unwrap();
+    std::fs::write(root.path().join("b.md"), "b").unwrap();
+    let manifest = out.path().join("manifest.txt");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    let output = cmd
+        .args(["manifest", "--format", "bsd", "-s", ".md", "-r"])
+        .arg(root.path())
+        .output()
+        .unwrap();
+    std::fs::write(&manifest, output.stdout).unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("verify")
+        .arg(&manifest)
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success();
+
+    std::fs::write(root.path().join("a.md"), "edited").unwrap();
+    std::fs::remove_file(root.path().join("b.md")).unwrap();
+    std::fs::write(root.path().join("c.md"), "c").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("verify")
+        .arg(&manifest)
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("## Changed\n\n- [a.md]"))
+        .stdout(predicate::str::contains("## Missing\n\n- [b.md]"))
+        .stdout(predicate::str::contains("## New\n\n- [c.md]"))
+        .stderr(predicate::str::contains("1 changed and 1 missing files"));
+}
+
+#[test]
+fn test_save_and_compare_state() {
+    let root = tempfile::TempDir::new().unwrap();
+    let out = tempfile::TempDir::new().unwrap();
+    let state = out.path().join("state.json");
+    std::fs::write(root.path().join("a.md"), "a").unwrap();
+    std::fs::write(root.path().join("b.md"), "b").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--save-state")
+        .arg(&state)
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success();
+
+    std::fs::write(root.path().join("a.md"), "longer").unwrap();
+    std::fs::remove_file(root.path().join("b.md")).unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--compare-state")
+        .arg(&state)
+        .args(["-d", "2000-01-01", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("## Disappeared\n\n- [b.md]"))
+        .stdout(predicate::str::contains("a.md) (1 B -> 6 B)"));
+}
+
+#[test]
+fn test_checkpoint_reports_only_new_files() {
+    let root = tempfile::TempDir::new().unwrap();
+    let out = tempfile::TempDir::new().unwrap();
+    let checkpoint = out.path().join("checkpoint");
+    std::fs::write(root.path().join("old.md"), "").unwrap();
+
+    // Without a checkpoint yet, today's files are reported
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--checkpoint")
+        .arg(&checkpoint)
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old.md"));
+    assert!(checkpoint.exists());
+
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    std::fs::write(root.path().join("new.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--checkpoint")
+        .arg(&checkpoint)
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("new.md"))
+        .stdout(predicate::str::contains("old.md").not());
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--checkpoint")
+        .arg(&checkpoint)
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn test_frontmatter_filter() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        root.path().join("x.md"),
+        "---\ntags: [project-x]\nstatus: active\n---\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.path().join("y.md"),
+        "---\ntags: [project-y]\nstatus: active\n---\n",
+    )
+    .unwrap();
+    std::fs::write(root.path().join("plain.md"), "# No frontmatter\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--frontmatter")
+        .arg("tags contains project-x")
+        .arg("--frontmatter")
+        .arg("status = active")
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("x.md"))
+        .stdout(predicate::str::contains("y.md").not())
+        .stdout(predicate::str::contains("plain.md").not());
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--frontmatter")
+        .arg("bad key = 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid condition"));
+}
+
+#[test]
+fn test_show_tags_and_tag_filter() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        root.path().join("a.md"),
+        "---\ntags: [project-x]\n---\nNotes for #meeting\n",
+    )
+    .unwrap();
+    std::fs::write(root.path().join("b.md"), "Just #rust\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--show-tags")
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("a.md) #project-x #meeting\n"))
+        .stdout(predicate::str::contains("b.md) #rust\n"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--tag")
+        .arg("meeting")
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("a.md"))
+        .stdout(predicate::str::contains("b.md").not());
+}
+
+#[test]
+fn test_links_graph() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "See [[b]]\n").unwrap();
+    std::fs::write(root.path().join("b.md"), "Back to [a](a.md)\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("links")
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("## a.md\n\n### Links\n\n- [b.md]"))
+        .stdout(predicate::str::contains("## b.md\n\n### Links\n\n- [a.md]"))
+        .stdout(predicate::str::contains("### Backlinks\n\n- [a.md]"));
+}
+
+#[test]
+fn test_check_links() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        root.path().join("a.md"),
+        "[ok](b.md) [[nowhere]] [x](gone.md)\n",
+    )
+    .unwrap();
+    std::fs::write(root.path().join("b.md"), "[web](https://example.com)\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("check-links")
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- `gone.md`\n- `[[nowhere]]`\n"))
+        .stdout(predicate::str::contains("b.md](").not())
+        .stderr(predicate::str::contains("2 broken links in 1 file"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("check-links")
+        .arg("--fail")
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("found 2 broken links"));
+}
+
+#[test]
+fn test_toc() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        root.path().join("a.md"),
+        "# Plan\n\n## First Step\n\ntext\n",
+    )
+    .unwrap();
+    let target = root.path().join("a.md").display().to_string();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--toc")
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(format!(
+            "- [a.md]({0})\n  - [Plan]({0}#plan)\n    - [First Step]({0}#first-step)\n",
+            target
+        ));
+}
+
+#[test]
+fn test_bundle() {
+    let root = tempfile::TempDir::new().unwrap();
+    let out = tempfile::TempDir::new().unwrap();
+    let digest = out.path().join("digest.md");
+    std::fs::write(root.path().join("a.md"), "alpha\n").unwrap();
+    std::fs::write(root.path().join("b.txt"), "skipped\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("bundle")
+        .arg("-o")
+        .arg(&digest)
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("bundled 1 file into"));
+
+    assert_eq!(
+        std::fs::read_to_string(&digest).unwrap(),
+        "# a.md\n\nalpha\n"
+    );
+}
+
+#[test]
+fn test_pandoc_format() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--format", "pandoc", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("---\ntitle: \"Files for "))
+        .stdout(predicate::str::contains("generator: \"mdfiles "))
+        .stdout(predicate::str::contains("---\n\n- [a.md]("));
+}
+
+#[test]
+fn test_atom_format() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a b.md"), "# Hello\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args([
+        "--format",
+        "atom",
+        "--base-url",
+        "https://example.com/notes",
+    ])
+    .args(["-s", ".md", "-r"])
+    .arg(root.path())
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("<title>Hello</title>"))
+    .stdout(predicate::str::contains(
+        "<link href=\"https://example.com/notes/a%20b.md\"/>",
+    ));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--format", "atom", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("needs --base-url"));
+}
+
+#[test]
+fn test_sitemap_format() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(root.path().join("posts")).unwrap();
+    std::fs::write(root.path().join("posts").join("a.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--format", "sitemap", "--base-url", "https://example.com/"])
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "<loc>https://example.com/posts/a.md</loc>",
+        ))
+        .stdout(predicate::str::contains("<lastmod>"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--format", "sitemap"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--format sitemap needs --base-url",
+        ));
+}
+
+#[test]
+fn test_index_build() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(root.path().join("sub")).unwrap();
+    std::fs::write(root.path().join("a.md"), "alpha\n").unwrap();
+    std::fs::write(root.path().join("sub").join("b.txt"), "beta\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "build", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("indexed 2 files into"));
+    assert!(root.path().join(".mdfiles.db").exists());
+
+    // The index in the root isn't indexed itself on a rebuild
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "build", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("indexed 2 files into"));
+}
+
+#[test]
+fn test_index_query() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "---\nstatus: done\n---\n").unwrap();
+    std::fs::write(root.path().join("b.md"), "").unwrap();
+    std::fs::write(root.path().join("c.txt"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "query", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("run mdfiles index build"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "build", "-r"])
+        .arg(root.path())
+        .assert()
+        .success();
+    // Files added after the build aren't seen until the next one
+    std::fs::write(root.path().join("d.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "query", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[a.md]"))
+        .stdout(predicate::str::contains("[b.md]"))
+        .stdout(predicate::str::contains("c.txt").not())
+        .stdout(predicate::str::contains("d.md").not());
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "query", "--format", "pandoc", "-s", ".md"])
+        .args(["--frontmatter", "status = done", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("---\ntitle: \"Files for "))
+        .stdout(predicate::str::contains("[a.md]"))
+        .stdout(predicate::str::contains("b.md").not());
+}
+
+#[test]
+fn test_index_query_dir_config() {
+    let root = tempfile::TempDir::new().unwrap();
+    let photos = root.path().join("photos");
+    std::fs::create_dir(&photos).unwrap();
+    std::fs::write(photos.join("mdfiles.toml"), "suffixes = [\".jpg\"]\n").unwrap();
+    for path in ["notes.md", "photos/a.jpg", "photos/b.md"] {
+        std::fs::write(root.path().join(path), "").unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "build", "-r"])
+        .arg(root.path())
+        .assert()
+        .success();
+
+    // The same files as a plain run
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "query", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[notes.md]"))
+        .stdout(predicate::str::contains("[a.jpg]"))
+        .stdout(predicate::str::contains("b.md").not());
+}
+
+#[test]
+fn test_index_update() {
+    let root = tempfile::TempDir::new().unwrap();
+    let db = tempfile::TempDir::new().unwrap();
+    let db = db.path().join("index.db");
+    std::fs::create_dir(root.path().join("sub")).unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "build", "--db"])
+        .arg(&db)
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success();
+
+    std::fs::write(root.path().join("sub").join("b.md"), "").unwrap();
+    std::fs::write(root.path().join("a.md"), "edited").unwrap();
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "update", "--db"])
+        .arg(&db)
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "rescanned 1 of 2 directories and 1 edited file, 2 files",
+        ));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "query", "-s", ".md", "--db"])
+        .arg(&db)
+        .arg("-r")
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[b.md]"));
+}
+
+#[test]
+fn test_index_search() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        root.path().join("a.md"),
+        "# Launch\n\nThe rocket is ready.\n",
+    )
+    .unwrap();
+    std::fs::write(root.path().join("b.txt"), "rocket notes").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "build", "--content", "-r"])
+        .arg(root.path())
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["index", "search", "rocket", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[a.md]("))
+        .stdout(predicate::str::contains(
+            "  > # Launch The **rocket** is ready.\n",
+        ))
+        .stdout(predicate::str::contains("b.txt").not());
+}
+
+#[test]
+fn test_multiple_roots() {
+    let work = tempfile::TempDir::new().unwrap();
+    let notes = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(work.path().join("sub")).unwrap();
+    std::fs::write(work.path().join("a.md"), "").unwrap();
+    std::fs::write(work.path().join("sub").join("b.md"), "").unwrap();
+    std::fs::write(notes.path().join("c.md"), "").unwrap();
+
+    // Files under nested roots are listed once
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    let output = cmd
+        .args(["-s", ".md", "-r"])
+        .arg(work.path())
+        .arg("-r")
+        .arg(work.path().join("sub"))
+        .arg("-r")
+        .arg(notes.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 3);
+    assert_eq!(stdout.matches("[b.md]").count(), 1);
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--root-headings", "-s", ".md", "-r"])
+        .arg(work.path())
+        .arg("-r")
+        .arg(notes.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "## {}\n\n- [",
+            work.path().display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "\n\n## {}\n\n- [c.md](",
+            notes.path().display()
+        )));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["stats", "-r"])
+        .arg(work.path())
+        .arg("-r")
+        .arg(notes.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("several --root"));
+}
+
+#[test]
+fn test_exclude_root() {
+    let root = tempfile::TempDir::new().unwrap();
+    let data = root.path().join("data");
+    std::fs::create_dir_all(data.join("deep")).unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    std::fs::write(data.join("b.md"), "").unwrap();
+    std::fs::write(data.join("deep").join("c.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(root.path())
+        .args(["-s", ".md", "--exclude-root", "data"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[a.md]"))
+        .stdout(predicate::str::contains("b.md").not())
+        .stdout(predicate::str::contains("c.md").not());
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(root.path())
+        .args(["-s", ".md", "--exclude-root", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("can't exclude 'nope'"));
+}
+
+#[test]
+fn test_default_order() {
+    let root = tempfile::TempDir::new().unwrap();
+    // Created out of name order, all with the same time
+    let time = std::time::SystemTime::now();
+    let mut names: Vec<String> = (0..30).map(|i| format!("{:02}.md", (i * 7) % 30)).collect();
+    for name in &names {
+        let file = std::fs::File::create(root.path().join(name)).unwrap();
+        file.set_modified(time).unwrap();
+    }
+    names.sort();
+
+    let list = |extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+        let output = cmd
+            .args(["-s", ".md", "-r"])
+            .arg(root.path())
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let listing = list(&[]);
+    let listed: Vec<&str> = listing
+        .lines()
+        .map(|line| &line[3..line.find(']').unwrap()])
+        .collect();
+    assert_eq!(listed, names);
+    assert_eq!(list(&[]), listing);
+
+    let mut unordered: Vec<String> = list(&["--unordered"]).lines().map(String::from).collect();
+    unordered.sort();
+    let mut sorted: Vec<String> = listing.lines().map(String::from).collect();
+    sorted.sort();
+    assert_eq!(unordered, sorted);
+}
+
+#[test]
+fn test_walk_order() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(root.path().join("a")).unwrap();
+    // Files with the same time are listed in walk order
+    let time = std::time::SystemTime::now();
+    for name in ["z.md", "a/y.md", "b.md"] {
+        let file = std::fs::File::create(root.path().join(name)).unwrap();
+        file.set_modified(time).unwrap();
+    }
+
+    let names = |order: &[&str]| -> Vec<String> {
+        let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+        let output = cmd
+            .args(order)
+            .args(["-s", ".md", "-r"])
+            .arg(root.path())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        stdout
+            .lines()
+            .map(|line| line[3..line.find(']').unwrap()].to_string())
+            .collect()
+    };
+    assert_eq!(names(&[]), vec!["y.md", "b.md", "z.md"]);
+    assert_eq!(
+        names(&["--walk-order", "bfs"]),
+        vec!["b.md", "z.md", "y.md"]
+    );
+    assert_eq!(names(&["--unordered"]).len(), 3);
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--unordered", "--walk-order", "bfs"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_sort_natural() {
+    let root = tempfile::TempDir::new().unwrap();
+    for name in ["note10.md", "note2.md", "note1.md"] {
+        std::fs::write(root.path().join(name), "").unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    let output = cmd
+        .args(["--sort", "natural", "-s", ".md", "-r"])
+        .arg(root.path())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let first = stdout.find("[note1.md]").unwrap();
+    let second = stdout.find("[note2.md]").unwrap();
+    let tenth = stdout.find("[note10.md]").unwrap();
+    assert!(first < second && second < tenth);
+}
+
+#[test]
+fn test_collate() {
+    let root = tempfile::TempDir::new().unwrap();
+    for name in ["Öl.md", "zoo.md", "oak.md"] {
+        std::fs::write(root.path().join(name), "").unwrap();
+    }
+
+    let names = |locale: &str| -> Vec<String> {
+        let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+        let output = cmd
+            .args(["--collate", locale, "-s", ".md", "-r"])
+            .arg(root.path())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        stdout
+            .lines()
+            .map(|line| line[3..line.find(']').unwrap()].to_string())
+            .collect()
+    };
+    assert_eq!(names("de_DE"), vec!["oak.md", "Öl.md", "zoo.md"]);
+    assert_eq!(names("sv_SE"), vec!["oak.md", "zoo.md", "Öl.md"]);
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--collate", "not a locale"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown locale"));
+}
+
+#[test]
+fn test_group_by_week() {
+    let root = tempfile::TempDir::new().unwrap();
+    for (name, day) in [("a.md", 10), ("b.md", 14), ("c.md", 17)] {
+        let noon = chrono::NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap();
+        let file = std::fs::File::create(root.path().join(name)).unwrap();
+        file.set_modified(noon.into()).unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--group-by", "week", "-s", ".md"])
+        .args(["--from", "2025-03-01", "--to", "2025-03-31", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "## 2025-W11\n\n### 2025-03-10\n\n- [a.md](",
+        ))
+        .stdout(predicate::str::contains("\n\n### 2025-03-14\n\n- [b.md]("))
+        .stdout(predicate::str::contains(
+            "\n\n## 2025-W12\n\n### 2025-03-17\n\n- [c.md](",
+        ));
+}
+
+#[test]
+fn test_calendar_format() {
+    let root = tempfile::TempDir::new().unwrap();
+    let noon = chrono::NaiveDate::from_ymd_opt(2025, 2, 14)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+        .and_local_timezone(chrono::Local)
+        .unwrap();
+    for name in ["a.md", "b.md"] {
+        let file = std::fs::File::create(root.path().join(name)).unwrap();
+        file.set_modified(noon.into()).unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args([
+        "--format",
+        "calendar",
+        "-s",
+        ".md",
+        "-d",
+        "2025-02-14",
+        "-r",
+    ])
+    .arg(root.path())
+    .assert()
+    .success()
+    .stdout(predicate::str::starts_with(
+        "## February 2025\n\n| Mon | Tue |",
+    ))
+    .stdout(predicate::str::contains("| 14 [2](#2025-02-14) |"))
+    .stdout(predicate::str::contains("\n## 2025-02-14\n\n- [a.md]("));
+}
+
+#[test]
+fn test_timeline_format() {
+    let root = tempfile::TempDir::new().unwrap();
+    for (name, hour) in [("late.md", 16), ("early.md", 9)] {
+        let time = chrono::NaiveDate::from_ymd_opt(2025, 3, 14)
+            .unwrap()
+            .and_hms_opt(hour, 45, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap();
+        let file = std::fs::File::create(root.path().join(name)).unwrap();
+        file.set_modified(time.into()).unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--format", "timeline", "--sort", "name", "-s", ".md"])
+        .args(["-d", "2025-03-14", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("- 09:45 — [early.md]("))
+        .stdout(predicate::str::contains(")\n- 16:45 — [late.md]("));
+}
+
+#[test]
+fn test_repo_url_permalinks() {
+    let repo = tempfile::TempDir::new().unwrap();
+    std::fs::write(repo.path().join("a.md"), "").unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo.path())
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args([
+        "--repo-url",
+        "https://github.com/org/repo",
+        "-s",
+        ".md",
+        "-r",
+    ])
+    .arg(repo.path())
+    .assert()
+    .success()
+    .stdout(
+        predicate::str::is_match(
+            r"^- \[a\.md\]\(https://github\.com/org/repo/blob/[0-9a-f]{40}/a\.md\)\n$",
+        )
+        .unwrap(),
+    );
+}
+
+#[test]
+fn test_url_template_permalinks() {
+    let repo = tempfile::TempDir::new().unwrap();
+    std::fs::write(repo.path().join("a.md"), "").unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo.path())
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+    git(&[
+        "remote",
+        "add",
+        "origin",
+        "git@gitlab.example.com:group/repo.git",
+    ]);
+
+    let template = "https://gitlab.example.com/{repo}/-/blob/{ref}/{path}";
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--url-template", template, "-s", ".md", "-r"])
+        .arg(repo.path())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                r"^- \[a\.md\]\(https://gitlab\.example\.com/group/repo/-/blob/[0-9a-f]{40}/a\.md\)\n$",
+            )
+            .unwrap(),
+        );
+}
+
+#[test]
+fn test_base_url_links() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(root.path().join("daily")).unwrap();
+    std::fs::write(root.path().join("daily").join("a b.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--base-url", "https://notes.example.com", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout("- [a b.md](https://notes.example.com/daily/a%20b.md)\n");
+}
+
+#[test]
+fn test_anchor_links() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "---\ntitle: x\n---\n## My Plan\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--anchor", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("/a.md#my-plan)\n"));
+}
+
+#[test]
+fn test_headings_limit() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "# Title\n## Plan\n## Done\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--headings", "2", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#title)\n    - [Plan]("))
+        .stdout(predicate::str::contains("Done").not());
+}
+
+#[test]
+fn test_embed_images_gallery() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("import.log"), "").unwrap();
+    std::fs::write(root.path().join("IMG_1.JPG"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--embed-images", "--gallery", "Photos", "-s", "", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                r"^- \[import\.log\]\(.*\)\n\n## Photos\n\n- !\[IMG_1\.JPG\]\(.*\)\n$",
+            )
+            .unwrap(),
+        );
+}
+
+#[test]
+fn test_smart_embed() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.png"), "").unwrap();
+    std::fs::write(root.path().join("b.mp3"), "").unwrap();
+    std::fs::write(root.path().join("c.pdf"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--smart-embed", "--sort", "name", "-s", "", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                r#"^- !\[a\.png\]\(.*\)\n- <audio controls src=".*b\.mp3"></audio>\n- \[c\.pdf\]\(.*\)\n$"#,
+            )
+            .unwrap(),
+        );
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--gallery", "Photos"]).assert().failure();
+}
+
+#[test]
+fn test_show_size() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), vec![b'a'; 1536]).unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--show-size", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("a.md) (1.5 KiB)\n"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--show-size", "--bytes", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("a.md) (1536)\n"));
+}
+
+#[test]
+fn test_show_wc() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "# Draft\n\nthree more words\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--show-wc", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("a.md) (3 lines, 5 words)\n"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--robot", "--show-wc", "-s", ".md", "-r"])
+        .arg(root.path())
+        .write_stdin("{}\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"lines\":3"))
+        .stdout(predicate::str::contains("\"words\":5"));
+}
+
+#[test]
+fn test_show_time() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    let modified = chrono::NaiveDate::from_ymd_opt(2025, 3, 14)
+        .unwrap()
+        .and_hms_opt(9, 45, 0)
+        .unwrap()
+        .and_local_timezone(chrono::Local)
+        .unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(root.path().join("a.md"))
+        .unwrap()
+        .set_modified(modified.into())
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--show-time", "iso", "-d", "2025-03-14", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("a.md) (2025-03-14 09:45)\n"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args([
+        "--show-time",
+        "relative",
+        "-d",
+        "2025-03-14",
+        "-s",
+        ".md",
+        "-r",
+    ])
+    .arg(root.path())
+    .assert()
+    .success()
+    .stdout(predicate::str::is_match(r"a\.md\) \(\d+ (months?|years?) ago\)\n$").unwrap());
+}
+
+#[test]
+fn test_lang() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["stats", "--lang", "de", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Gesamt: 1 Datei\n"))
+        .stdout(predicate::str::contains("## Verzeichnisse"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--lang", "he", "-d", "not-a-date"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("שגיאה: "));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--lang", "de", "-d", "not-a-date"])
+        .assert()
+        .failure()
+        .stderr("Fehler: Ungültiges Datumsformat (erwartet JJJJ-MM-TT)\n");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--lang", "de", "-r", "/nonexistent/root"])
+        .assert()
+        .failure()
+        .stderr("Fehler: Wurzelverzeichnis '/nonexistent/root' existiert nicht\n");
+
+    let dest = tempfile::TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--lang", "de", "--dry-run", "copy"])
+        .arg(dest.path())
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("würde "))
+        .stdout(predicate::str::ends_with(" kopieren\n"));
+
+    // Commands run on worker threads report in the language too
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args([
+        "--lang", "de", "--exec", "false", "--jobs", "2", "-s", ".md", "-r",
+    ])
+    .arg(root.path())
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("Fehler: 'false' fehlgeschlagen: "));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_executable_filter() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = tempfile::TempDir::new().unwrap();
+    let script = root.path().join("build.sh");
+    std::fs::write(&script, "#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    std::fs::write(root.path().join("notes.sh"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--executable", "-s", ".sh", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("build.sh"))
+        .stdout(predicate::str::contains("notes.sh").not());
+}
+
+#[test]
+fn test_same_date_as() {
+    let root = tempfile::TempDir::new().unwrap();
+    let day = |d| {
+        chrono::NaiveDate::from_ymd_opt(2025, 3, d)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap()
+    };
+    for (name, d) in [("design.md", 14), ("same.md", 14), ("other.md", 15)] {
+        let path = root.path().join(name);
+        std::fs::write(&path, "").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(day(d).into())
+            .unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--same-date-as")
+        .arg(root.path().join("design.md"))
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"^- \[same\.md\]\(.*\)\n$").unwrap());
+}
+
+#[test]
+fn test_after_before() {
+    let root = tempfile::TempDir::new().unwrap();
+    let at = |h, m| {
+        chrono::NaiveDate::from_ymd_opt(2025, 3, 14)
+            .unwrap()
+            .and_hms_opt(h, m, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap()
+    };
+    for (name, time) in [
+        ("early.log", at(8, 59)),
+        ("during.log", at(12, 0)),
+        ("late.log", at(18, 31)),
+    ] {
+        let path = root.path().join(name);
+        std::fs::write(&path, "").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(time.into())
+            .unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args([
+        "--after",
+        "2025-03-14T09:00",
+        "--before",
+        "2025-03-14T18:30",
+    ])
+    .args(["-s", ".log", "-r"])
+    .arg(root.path())
+    .assert()
+    .success()
+    .stdout(predicate::str::is_match(r"^- \[during\.log\]\(.*\)\n$").unwrap());
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--after", "yesterday"]).assert().failure();
+}
+
+#[test]
+fn test_dir_config() {
+    let root = tempfile::TempDir::new().unwrap();
+    let photos = root.path().join("photos");
+    std::fs::create_dir_all(photos.join("raw")).unwrap();
+    std::fs::write(
+        photos.join("mdfiles.toml"),
+        "suffixes = [\".jpg\"]\nexclude = [\"raw/\"]\n",
+    )
+    .unwrap();
+    for path in [
+        "notes.md",
+        "photos/a.jpg",
+        "photos/b.md",
+        "photos/raw/c.jpg",
+    ] {
+        std::fs::write(root.path().join(path), "").unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("notes.md"))
+        .stdout(predicate::str::contains("a.jpg"))
+        .stdout(predicate::str::contains("b.md").not())
+        .stdout(predicate::str::contains("c.jpg").not());
+}
+
+#[test]
+fn test_dir_config_precedence() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("mdfiles.toml"),
+        "suffixes = [\".txt\"]\nexclude = [\"skip/\"]\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(dir.path().join("skip")).unwrap();
+    std::fs::create_dir_all(dir.path().join("other")).unwrap();
+    for path in ["a.md", "b.txt", "skip/c.md", "other/d.md"] {
+        std::fs::write(dir.path().join(path), "").unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .assert()
+        .success()
+        .stdout("- [b.txt](./b.txt)\n");
+
+    // --suffix from the command line or the environment wins over suffixes
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args(["-s", ".md"])
+        .assert()
+        .success()
+        .stdout("- [a.md](./a.md)\n- [d.md](./other/d.md)\n");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("MDFILES_SUFFIX", ".md")
+        .assert()
+        .success()
+        .stdout("- [a.md](./a.md)\n- [d.md](./other/d.md)\n");
+
+    // and --exclude-root over exclude
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args(["-s", ".md", "--exclude-root", "other"])
+        .assert()
+        .success()
+        .stdout("- [a.md](./a.md)\n- [c.md](./skip/c.md)\n");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "suffixes = [\".txt\"]  # mdfiles.toml\n",
+        ))
+        .stdout(predicate::str::contains(
+            "exclude = [\"skip/\"]  # mdfiles.toml\n",
+        ));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("MDFILES_SUFFIX", ".md")
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "suffix = \".md\"  # $MDFILES_SUFFIX\n",
+        ))
+        .stdout(predicate::str::contains("suffixes").not())
+        .stdout(predicate::str::contains("exclude = [\"skip/\"]"));
+}
+
+#[test]
+fn test_config_precedence() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let xdg = dir.path().join("xdg");
+    std::fs::create_dir_all(xdg.join("mdfiles")).unwrap();
+    std::fs::write(
+        xdg.join("mdfiles/config.toml"),
+        "suffix = \".txt\"\nshow-size = true\ndate = 2000-01-01\n",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("mdfiles.toml"), "suffix = \".md\"\n").unwrap();
+    std::fs::write(dir.path().join("a.md"), "abc").unwrap();
+    std::fs::write(dir.path().join("b.txt"), "").unwrap();
+    std::fs::write(dir.path().join("c.log"), "").unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    // ./mdfiles.toml wins over the user's config, --date over its date
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", &xdg)
+        .args(["--date", &today])
+        .assert()
+        .success()
+        .stdout("- [a.md](./a.md) (3 B)\n");
+
+    // Environment variables win over both files
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", &xdg)
+        .env("MDFILES_SUFFIX", ".log")
+        .env("MDFILES_SHOW_SIZE", "false")
+        .args(["--from", &today])
+        .assert()
+        .success()
+        .stdout("- [c.log](./c.log)\n");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", &xdg)
+        .args(["--suffix", ".rs", "config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "suffix = \".rs\"  # command line\n",
+        ))
+        .stdout(predicate::str::contains(format!(
+            "show-size = true  # {}\n",
+            xdg.join("mdfiles/config.toml").display()
+        )))
+        .stdout(predicate::str::contains("date = \"2000-01-01\""))
+        .stdout(predicate::str::contains("sort = \"time\"  # default\n"));
+
+    std::fs::write(dir.path().join("mdfiles.toml"), "colour = true\n").unwrap();
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", &xdg)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown setting 'colour' in mdfiles.toml",
+        ));
+}
+
+#[test]
+fn test_config_init_validate() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let xdg = dir.path().join("xdg");
+    let path = xdg.join("mdfiles/config.toml");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.env("XDG_CONFIG_HOME", &xdg)
+        .args(["config", "init"])
+        .assert()
+        .success();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("# File suffix to match\n#suffix = \".go\"\n"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.env("XDG_CONFIG_HOME", &xdg)
+        .args(["config", "init"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("use --force"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", &xdg)
+        .args(["config", "validate"])
+        .assert()
+        .success()
+        .stdout("");
+
+    std::fs::write(
+        dir.path().join("mdfiles.toml"),
+        "suffix = \".md\"\nwalk-order = \"random\"\nsuffixes = [\".jpg\"]\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", &xdg)
+        .args(["config", "validate"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::starts_with(
+            "mdfiles.toml:2:14: invalid value 'random' for '--walk-order <ORDER>'",
+        ))
+        .stderr(predicate::str::contains("found 1 problems"));
+}
+
+#[test]
+fn test_dry_run() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("notes.md"), "hello").unwrap();
+    let vault = tempfile::TempDir::new().unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let note = vault.path().join(format!("{}.md", today));
+    std::fs::write(&note, "# Today\n\n## Files\n\n- [old.md](old.md)\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["daily", "--dry-run", "--vault"])
+        .arg(vault.path())
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "--- {0}\n+++ {0}\n@@ -3,3 +3,4 @@\n ## Files\n \n - [old.md](old.md)\n+- [notes.md](",
+            note.display()
+        )));
+    assert_eq!(
+        std::fs::read_to_string(&note).unwrap(),
+        "# Today\n\n## Files\n\n- [old.md](old.md)\n"
+    );
+
+    let out = root.path().join("out.zip");
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--dry-run", "archive"])
+        .arg(&out)
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with(" as notes.md\n"));
+    assert!(!out.exists());
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--dry-run", "stats"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--dry-run only applies to"));
+}
+
+#[test]
+fn test_backup() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("notes.md"), "hello").unwrap();
+    let vault = tempfile::TempDir::new().unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let note = vault.path().join(format!("{}.md", today));
+    std::fs::write(&note, "# Today\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["daily", "--backup", "--vault"])
+        .arg(vault.path())
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success();
+
+    let mut names: Vec<_> = std::fs::read_dir(vault.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [format!("{}.md", today), format!("{}.md.bak", today)]
+    );
+    assert_eq!(
+        std::fs::read_to_string(vault.path().join(&names[1])).unwrap(),
+        "# Today\n"
+    );
+    assert!(
+        std::fs::read_to_string(&note)
+            .unwrap()
+            .contains("[notes.md]")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_daily_note_symlink() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("notes.md"), "hello").unwrap();
+    let vault = tempfile::TempDir::new().unwrap();
+    let real = tempfile::TempDir::new().unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let target = real.path().join("today.md");
+    std::fs::write(&target, "# Today\n").unwrap();
+    let note = vault.path().join(format!("{}.md", today));
+    std::os::unix::fs::symlink(&target, &note).unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["daily", "--backup", "--vault"])
+        .arg(vault.path())
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success();
+
+    assert!(std::fs::symlink_metadata(&note).unwrap().is_symlink());
+    assert!(
+        std::fs::read_to_string(&target)
+            .unwrap()
+            .contains("[notes.md]")
+    );
+    assert_eq!(
+        std::fs::read_to_string(real.path().join("today.md.bak")).unwrap(),
+        "# Today\n"
+    );
+}
+
+#[test]
+fn test_template_file() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    std::fs::write(root.path().join("b.md"), "").unwrap();
+    let template = root.path().join("page.tera");
+    std::fs::write(
+        &template,
+        "# {{ title }}\n{% for day in days %}\n## {{ day.date }}\n\n\
+         {% for file in day.files %}{{ file.entry }}\n{% endfor %}{% endfor %}",
+    )
+    .unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--template-file")
+        .arg(&template)
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(format!(
+                r"^# Files for {0}\n\n## {0}\n\n- \[a\.md\]\(.*\)\n- \[b\.md\]\(.*\)\n$",
+                today
+            ))
+            .unwrap(),
+        );
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("--template-file")
+        .arg(&template)
+        .args(["--format", "atom"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_entry_template() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    std::fs::write(root.path().join("b.png"), "").unwrap();
+    std::fs::write(
+        root.path().join("mdfiles.toml"),
+        "[entry-template]\npng = \"![{{ name }}]({{ link }})\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(root.path())
+        .args(["-s", "", "-r", "."])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- [a.md](./a.md)\n"))
+        .stdout(predicate::str::contains("- ![b.png](./b.png)\n"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.current_dir(root.path())
+        .args(["-s", ".md", "-r", ".", "--entry-template", "md={{ entry"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_frontmatter_out() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    let out_dir = tempfile::TempDir::new().unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--frontmatter-out", "-s", ".md", "-r"])
+        .arg(root.path())
+        .arg("report")
+        .arg("--out-dir")
+        .arg(out_dir.path())
+        .assert()
+        .success();
+    let report = std::fs::read_to_string(out_dir.path().join(format!("{}.md", today))).unwrap();
+    assert!(report.starts_with(&format!("---\ndate: {}\ngenerated: ", today)));
+    assert!(report.contains("\nfiles: 1\nquery:\n  suffix: \".md\"\n"));
+    assert!(report.contains("\n---\n\n- [a.md]("));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--frontmatter-out", "-s", ".md", "stats"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only applies to"));
+}
+
+#[test]
+fn test_quiet() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    let out = root.path().join("bundle.md");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .args(["bundle", "--out"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("bundled 1 file into"));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["-q", "-s", ".md", "-r"])
+        .arg(root.path())
+        .args(["bundle", "--out"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["-q", "-r", "no-such-dir"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not exist"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_daemon() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    let pid_file = root.path().join(".mdfiles.db.pid");
+
+    let mut daemon = std::process::Command::new(assert_cmd::cargo::cargo_bin("mdfiles"))
+        .arg("-r")
+        .arg(root.path())
+        .args(["daemon", "--foreground", "--interval", "1"])
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    for _ in 0..100 {
+        if pid_file.exists() && root.path().join(".mdfiles.db").exists() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("-r")
+        .arg(root.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "daemon: running (pid {})",
+            daemon.id()
+        )));
+
+    let status = std::process::Command::new("kill")
+        .arg(daemon.id().to_string())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(daemon.wait().unwrap().success());
+    assert!(!pid_file.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_daemon_background() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    let pid_file = root.path().join(".mdfiles.db.pid");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("-r")
+        .arg(root.path())
+        .args(["daemon", "--interval", "1"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("started the daemon (pid "));
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("-r")
+        .arg(root.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "daemon: running (pid {})",
+            pid.trim()
+        )));
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("-r")
+        .arg(root.path())
+        .args(["daemon", "--stop"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(format!(
+            "stopped the daemon (pid {})",
+            pid.trim()
+        )));
+    assert!(!pid_file.exists());
+    assert!(root.path().join(".mdfiles.db.log").exists());
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg("-r")
+        .arg(root.path())
+        .args(["daemon", "--stop"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no daemon is updating"));
+}
+
+#[test]
+fn test_weekly() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    let today = chrono::Local::now().date_naive();
+    let start = (today - chrono::Days::new(6))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--frontmatter-out", "-s", ".md", "-r"])
+        .arg(root.path())
+        .arg("weekly")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "---\nfrom: {}\nto: {}\n",
+            start,
+            today.format("%Y-%m-%d")
+        )))
+        .stdout(predicate::str::contains(format!(
+            "# Week of {}\n\nTotal: 1 file\n",
+            start
+        )))
+        .stdout(predicate::str::contains(format!(
+            "\n## {}\n\n- [a.md](",
+            today.format("%Y-%m-%d")
+        )));
+}
+
+#[test]
+fn test_monthly() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    let today = chrono::Local::now().date_naive();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .arg("monthly")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "# Month of {}\n\nTotal: 1 file\n\n## Weeks\n\n",
+            today.format("%Y-%m")
+        )))
+        .stdout(predicate::str::contains("| `.md` | 1 |\n"))
+        .stdout(predicate::str::contains(format!(
+            "\n#### {}\n\n- [a.md](",
+            today.format("%Y-%m-%d")
+        )));
+}
+
+#[test]
+fn test_changelog() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(root.path().join("docs")).unwrap();
+    std::fs::write(root.path().join("docs").join("a.md"), "").unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .arg("changelog")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "# Changes for {}\n\n## {}\n\n### docs\n\n- [a.md](",
+            today, today
+        )));
+
+    // Not a git checkout
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .args(["changelog", "--commits"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("git"));
+}
+
+#[test]
+fn test_report_commits_needs_git() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    let out_dir = tempfile::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .args(["report", "--commits", "--out-dir"])
+        .arg(out_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("git"));
+    assert_eq!(std::fs::read_dir(out_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_report_commits_per_file() {
+    let repo = tempfile::TempDir::new().unwrap();
+    std::fs::write(repo.path().join("a.md"), "").unwrap();
+    std::fs::write(repo.path().join("b.md"), "").unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo.path())
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    git(&["add", "a.md"]);
+    git(&["commit", "-q", "-m", "fix flaky test"]);
+    let out_dir = tempfile::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["-s", ".md", "--sort", "name", "-r"])
+        .arg(repo.path())
+        .args(["report", "--commits=per-file", "--out-dir"])
+        .arg(out_dir.path())
+        .assert()
+        .success();
+
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let report = std::fs::read_to_string(out_dir.path().join(format!("{}.md", today))).unwrap();
+    assert!(
+        predicate::str::is_match(
+            r"^- \[a\.md\]\(.*\)\n  - [0-9a-f]{7,} — fix flaky test\n- \[b\.md\]\(.*\)\n$"
+        )
+        .unwrap()
+        .eval(&report),
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn test_sample() {
+    let root = tempfile::TempDir::new().unwrap();
+    for i in 0..20 {
+        std::fs::write(root.path().join(format!("{}.md", i)), "").unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    let output = cmd
+        .args(["--sample", "5", "-s", ".md", "-r"])
+        .arg(root.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let listing = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(listing.lines().filter(|l| l.starts_with("- ")).count(), 5);
+}
+
+#[test]
+fn test_sample_keeps_time_order() {
+    let root = tempfile::TempDir::new().unwrap();
+    for i in 0..20 {
+        let time = chrono::NaiveDate::from_ymd_opt(2025, 3, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 19 - i)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap();
+        let file = std::fs::File::create(root.path().join(format!("{}.md", i))).unwrap();
+        file.set_modified(time.into()).unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    let output = cmd
+        .args([
+            "--sample",
+            "8",
+            "--from",
+            "2025-03-01",
+            "--to",
+            "2025-03-01",
+        ])
+        .args(["-s", ".md", "-r"])
+        .arg(root.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let numbers: Vec<u32> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.strip_prefix("- ["))
+        .map(|line| line.split('.').next().unwrap().parse().unwrap())
+        .collect();
+    assert_eq!(numbers.len(), 8);
+    // Oldest first, which is the highest numbers first
+    assert!(numbers.windows(2).all(|pair| pair[0] > pair[1]));
+}
+
+#[test]
+fn test_shuffle() {
+    let root = tempfile::TempDir::new().unwrap();
+    for i in 0..20 {
+        std::fs::write(root.path().join(format!("{}.md", i)), "").unwrap();
+    }
+    let list = |extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+        let output = cmd
+            .args(["--sort", "name", "-s", ".md", "-r"])
+            .arg(root.path())
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let sorted = list(&[]);
+    let shuffled = list(&["--shuffle=42"]);
+    assert_ne!(shuffled, sorted);
+    assert_eq!(list(&["--shuffle=42"]), shuffled);
+    let mut lines: Vec<&str> = shuffled.lines().collect();
+    lines.sort_unstable();
+    let mut expected: Vec<&str> = sorted.lines().collect();
+    expected.sort_unstable();
+    assert_eq!(lines, expected);
+
+    // A seed makes the sample repeatable too
+    assert_eq!(
+        list(&["--shuffle=7", "--sample", "3"]),
+        list(&["--shuffle=7", "--sample", "3"])
+    );
+    assert_eq!(
+        list(&["--shuffle"])
+            .lines()
+            .filter(|l| l.starts_with("- "))
+            .count(),
+        20
+    );
+}
+
+#[test]
+fn test_collapse_names() {
+    let root = tempfile::TempDir::new().unwrap();
+    for dir in ["a", "b"] {
+        std::fs::create_dir(root.path().join(dir)).unwrap();
+        std::fs::write(root.path().join(dir).join("index.md"), "").unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    let output = cmd
+        .args(["--sort", "name", "-s", ".md", "-r"])
+        .arg(root.path())
+        .arg("--collapse-names")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let listing = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = listing.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("- [index.md]("));
+    assert!(lines[1].starts_with("  - [b/index.md](") || lines[1].starts_with("  - [a/index.md]("));
+}
+
+#[test]
+fn test_strip_prefix() {
+    let root = tempfile::TempDir::new().unwrap();
+    let deep = root.path().join("projects").join("notes");
+    std::fs::create_dir_all(deep.join("2025")).unwrap();
+    std::fs::write(deep.join("2025").join("a.md"), "").unwrap();
+    std::fs::write(deep.join("b.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--strip-prefix", "--sort", "name", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout("- [a.md](2025/a.md)\n- [b.md](b.md)\n");
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.arg(format!("--strip-prefix={}", root.path().display()))
+        .args(["--sort", "name", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout("- [a.md](projects/notes/2025/a.md)\n- [b.md](projects/notes/b.md)\n");
+}
+
+#[test]
+fn test_slug_titles() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("Release Notes v2.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--slug-titles", "--strip-prefix", "-s", ".md", "-r"])
+        .arg(root.path())
+        .assert()
+        .success()
+        .stdout("- [release-notes-v2](Release Notes v2.md)\n");
+}
+
+#[test]
+fn test_link_text() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(root.path().join("2025")).unwrap();
+    std::fs::write(root.path().join("2025").join("a.md"), "").unwrap();
+
+    for (text, expected) in [
+        ("stem", "- [a](2025/a.md)\n"),
+        ("path", "- [2025/a.md](2025/a.md)\n"),
+        ("parent-name", "- [2025/a.md](2025/a.md)\n"),
+    ] {
+        let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+        cmd.arg(format!("--strip-prefix={}", root.path().display()))
+            .args(["--link-text", text, "-s", ".md", "-r"])
+            .arg(root.path())
+            .assert()
+            .success()
+            .stdout(expected);
+    }
+}
+
+#[test]
+fn test_ref_links() {
+    let root = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.path().join("a.md"), "").unwrap();
+    std::fs::write(root.path().join("b.md"), "").unwrap();
+
+    let mut cmd = Command::cargo_bin("mdfiles").unwrap();
+    cmd.args(["--strip-prefix", "--sort", "name", "-s", ".md", "-r"])
+        .arg(root.path())
+        .arg("--ref-links")
+        .assert()
+        .success()
+        .stdout("- [a.md][1]\n- [b.md][2]\n\n[1]: a.md\n[2]: b.md\n");
+}